@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+
+// Longest partial line kept while waiting for its line break
+const MAX_LINE: usize = 4096;
+
+// Numeric Channels Parsed From Received Text
+//
+// Each received line is split on whitespace, commas, and semicolons. `name=value` and
+// `name:value` tokens feed the named channel; bare numbers feed channels named by position
+// (`#1`, `#2`, ...).
+pub fn parse(line: &str) -> Vec<(String, f64)> {
+    let mut values = Vec::new();
+    let mut position = 0;
+    for token in line
+        .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+        .filter(|token| !token.is_empty())
+    {
+        if let Some((name, value)) = token.split_once(['=', ':']) {
+            if let Ok(value) = value.parse() {
+                values.push((name.to_string(), value));
+            }
+        } else if let Ok(value) = token.parse() {
+            position += 1;
+            values.push((format!("#{position}"), value));
+        }
+    }
+    values
+}
+
+// Limits for One Channel; either bound may be left open
+#[derive(Debug, Clone)]
+pub struct Threshold {
+    pub channel: String,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl Threshold {
+    fn violation(&self, value: f64) -> Option<String> {
+        match (self.min, self.max) {
+            (Some(min), _) if value < min => {
+                Some(format!("{} = {value} below {min}", self.channel))
+            }
+            (_, Some(max)) if value > max => {
+                Some(format!("{} = {value} above {max}", self.channel))
+            }
+            _ => None,
+        }
+    }
+}
+
+// Threshold Monitor
+//
+// An alarm latches when a channel crosses a limit and stays raised until acknowledged, even if
+// the value comes back in range.
+#[derive(Default)]
+pub struct Monitor {
+    pub thresholds: Vec<Threshold>,
+    pub latest: BTreeMap<String, f64>,
+    // Latched alarm messages per channel
+    pub alarms: BTreeMap<String, String>,
+    partial: String,
+}
+
+impl Monitor {
+    // Feed received text; returns alarms raised by it
+    pub fn feed(&mut self, text: &str) -> Vec<String> {
+        let mut raised = Vec::new();
+        self.partial.push_str(text);
+        while let Some(end) = self.partial.find('\n') {
+            let line = self.partial[..end].to_string();
+            self.partial.drain(..=end);
+            for (channel, value) in parse(&line) {
+                self.latest.insert(channel.clone(), value);
+                let violation = self
+                    .thresholds
+                    .iter()
+                    .filter(|threshold| threshold.channel == channel)
+                    .find_map(|threshold| threshold.violation(value));
+                if let Some(violation) = violation
+                    && !self.alarms.contains_key(&channel)
+                {
+                    self.alarms.insert(channel, violation.clone());
+                    raised.push(violation);
+                }
+            }
+        }
+        if self.partial.len() > MAX_LINE {
+            self.partial.clear();
+        }
+        raised
+    }
+    pub fn acknowledge(&mut self) {
+        self.alarms.clear();
+    }
+}
@@ -0,0 +1,172 @@
+use iced::Color;
+
+// ANSI Escape Sequences in Received Text
+//
+// Devices that color their debug output send SGR sequences like ESC[32m. They can be shown
+// as received, stripped, or turned into colored runs of text. Each log line is parsed on its
+// own, so a color left on at the end of one line doesn't carry into the next.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum AnsiMode {
+    Show,
+    Strip,
+    Color,
+}
+
+impl AnsiMode {
+    pub const ALL: [AnsiMode; 3] = [AnsiMode::Show, AnsiMode::Strip, AnsiMode::Color];
+}
+
+impl std::fmt::Display for AnsiMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            AnsiMode::Show => "Show escapes",
+            AnsiMode::Strip => "Strip escapes",
+            AnsiMode::Color => "ANSI colors",
+        };
+        write!(f, "{name}")
+    }
+}
+
+// SGR attributes in effect for a run of text
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Style {
+    pub color: Option<Color>,
+    pub background: Option<Color>,
+    pub bold: bool,
+    pub underline: bool,
+}
+
+const ESC: char = '\x1b';
+const BEL: char = '\x07';
+
+pub fn strip(text: &str) -> String {
+    runs(text).into_iter().map(|(text, _)| text).collect()
+}
+
+// Split text into runs at every SGR sequence. Every other escape sequence, e.g. cursor moves
+// and window titles, is dropped.
+pub fn runs(text: &str) -> Vec<(String, Style)> {
+    let mut runs = Vec::new();
+    let mut current = String::new();
+    let mut style = Style::default();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != ESC {
+            current.push(c);
+            continue;
+        }
+        match chars.next() {
+            // Control Sequence: parameters, intermediates, then one final byte
+            Some('[') => {
+                let mut params = String::new();
+                let mut last = None;
+                for c in chars.by_ref() {
+                    if ('\x40'..='\x7e').contains(&c) {
+                        last = Some(c);
+                        break;
+                    }
+                    params.push(c);
+                }
+                if last == Some('m') {
+                    let mut next = style;
+                    select_graphic_rendition(&mut next, &params);
+                    if next != style {
+                        runs.push((std::mem::take(&mut current), style));
+                        style = next;
+                    }
+                }
+            }
+            // Operating System Command, ended by BEL or ESC \
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == BEL || (c == ESC && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            // Two-byte escapes, possibly with intermediates like ESC ( B
+            Some(_) => {
+                while chars.next_if(|c| ('\x20'..='\x2f').contains(c)).is_some() {}
+                chars.next_if(|c| ('\x30'..='\x7e').contains(c));
+            }
+            None => {}
+        }
+    }
+    runs.push((current, style));
+    runs.retain(|(text, _)| !text.is_empty());
+    runs
+}
+
+fn select_graphic_rendition(style: &mut Style, params: &str) {
+    let mut codes = params
+        .split([';', ':'])
+        .map(|code| code.parse::<u16>().unwrap_or(0));
+    while let Some(code) = codes.next() {
+        match code {
+            0 => *style = Style::default(),
+            1 => style.bold = true,
+            4 => style.underline = true,
+            22 => style.bold = false,
+            24 => style.underline = false,
+            30..=37 => style.color = Some(palette(code - 30)),
+            38 => style.color = extended(&mut codes),
+            39 => style.color = None,
+            40..=47 => style.background = Some(palette(code - 40)),
+            48 => style.background = extended(&mut codes),
+            49 => style.background = None,
+            90..=97 => style.color = Some(palette(code - 90 + 8)),
+            100..=107 => style.background = Some(palette(code - 100 + 8)),
+            _ => {}
+        }
+    }
+}
+
+// 38;5;n picks from the 256 color palette and 38;2;r;g;b gives the color itself
+fn extended(codes: &mut impl Iterator<Item = u16>) -> Option<Color> {
+    let mut next = || codes.next().map(|code| code.min(255) as u8);
+    match next()? {
+        5 => Some(palette(u16::from(next()?))),
+        2 => Some(Color::from_rgb8(next()?, next()?, next()?)),
+        _ => None,
+    }
+}
+
+// xterm's 256 colors: 16 named ones, a 6x6x6 cube, then a grey ramp
+fn palette(index: u16) -> Color {
+    const NAMED: [(u8, u8, u8); 16] = [
+        (0x00, 0x00, 0x00),
+        (0xcd, 0x00, 0x00),
+        (0x00, 0xcd, 0x00),
+        (0xcd, 0xcd, 0x00),
+        (0x00, 0x00, 0xee),
+        (0xcd, 0x00, 0xcd),
+        (0x00, 0xcd, 0xcd),
+        (0xe5, 0xe5, 0xe5),
+        (0x7f, 0x7f, 0x7f),
+        (0xff, 0x00, 0x00),
+        (0x00, 0xff, 0x00),
+        (0xff, 0xff, 0x00),
+        (0x5c, 0x5c, 0xff),
+        (0xff, 0x00, 0xff),
+        (0x00, 0xff, 0xff),
+        (0xff, 0xff, 0xff),
+    ];
+    match index {
+        0..=15 => {
+            let (r, g, b) = NAMED[usize::from(index)];
+            Color::from_rgb8(r, g, b)
+        }
+        16..=231 => {
+            let level = |n: u16| match n {
+                0 => 0,
+                n => (55 + n * 40) as u8,
+            };
+            let n = index - 16;
+            Color::from_rgb8(level(n / 36), level(n / 6 % 6), level(n % 6))
+        }
+        _ => {
+            let grey = (8 + (index.min(255) - 232) * 10) as u8;
+            Color::from_rgb8(grey, grey, grey)
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use crate::parity::LineParity;
+use serialport::{SerialPort, SerialPortBuilder};
+
+// Received Break Detection
+//
+// Linux keeps per-port line error counters that include received breaks. Other platforms
+// don't expose them through the serial driver, so breaks simply aren't reported there.
+pub struct BreakDetector {
+    #[cfg(target_os = "linux")]
+    fd: std::os::fd::RawFd,
+    last_count: Option<i32>,
+}
+
+// Open a port while keeping what's needed to watch it for breaks. The native handle is also
+// where mark and space parity and shared access get switched on.
+#[cfg(target_os = "linux")]
+pub fn open(
+    builder: SerialPortBuilder,
+    parity: LineParity,
+    exclusive: bool,
+) -> serialport::Result<(Box<dyn SerialPort>, BreakDetector)> {
+    use std::os::fd::AsRawFd;
+
+    let mut port = builder.open_native()?;
+    if !exclusive {
+        port.set_exclusive(false)?;
+    }
+    if parity.sticky() {
+        crate::parity::set_sticky(port.as_raw_fd(), parity)?;
+    }
+    let mut detector = BreakDetector {
+        fd: port.as_raw_fd(),
+        last_count: None,
+    };
+    detector.last_count = detector.read_count();
+    Ok((Box::new(port), detector))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn open(
+    builder: SerialPortBuilder,
+    parity: LineParity,
+    exclusive: bool,
+) -> serialport::Result<(Box<dyn SerialPort>, BreakDetector)> {
+    if parity.sticky() {
+        return Err(serialport::Error::new(
+            serialport::ErrorKind::InvalidInput,
+            format!("{parity} parity is only available on Linux"),
+        ));
+    }
+    #[cfg(unix)]
+    let port: Box<dyn SerialPort> = {
+        let mut port = builder.open_native()?;
+        port.set_exclusive(exclusive)?;
+        Box::new(port)
+    };
+    // COM ports can't be shared
+    #[cfg(windows)]
+    let port = {
+        let _ = exclusive;
+        builder.open()?
+    };
+    Ok((port, BreakDetector { last_count: None }))
+}
+
+impl BreakDetector {
+    // Breaks received since the last poll, or None when the driver doesn't count them
+    pub fn poll(&mut self) -> Option<u32> {
+        let last = self.last_count?;
+        let count = self.read_count()?;
+        self.last_count = Some(count);
+        Some(count.wrapping_sub(last).max(0) as u32)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn read_count(&self) -> Option<i32> {
+        // The kernel's `struct serial_icounter_struct`: cts, dsr, rng, dcd, rx, tx, frame,
+        // overrun, parity, brk, buf_overrun, then 9 reserved ints
+        let mut counters = [0i32; 20];
+        // SAFETY: the fd belongs to the open port and the buffer matches the kernel layout
+        let result = unsafe { libc::ioctl(self.fd, libc::TIOCGICOUNT, counters.as_mut_ptr()) };
+        (result == 0).then_some(counters[9])
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn read_count(&self) -> Option<i32> {
+        None
+    }
+}
+
+// Hold a break condition on a clone of the port for `duration`, off the UI thread
+pub fn send(
+    port: Box<dyn SerialPort>,
+    duration: std::time::Duration,
+) -> impl std::future::Future<Output = Result<(), String>> {
+    let (done, result) = iced::futures::channel::oneshot::channel();
+    std::thread::spawn(move || {
+        let sent = port.set_break().and_then(|()| {
+            std::thread::sleep(duration);
+            port.clear_break()
+        });
+        let _ = done.send(sent.map_err(|e| e.to_string()));
+    });
+    async move {
+        result
+            .await
+            .unwrap_or_else(|_| Err(String::from("break thread stopped")))
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{reader, writer};
+use bytes::Bytes;
+use serialport::SerialPort;
+use std::io::ErrorKind;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+// Serial Bridge
+//
+// Two ports joined back to back, with the app sitting between e.g. a PC program and a device.
+// Each direction has its own thread that writes whatever it reads straight to the other port
+// and hands a copy to the UI for the log. As a passive tap the ports only listen, each wired
+// to one direction of someone else's link, and nothing is written.
+pub struct Bridge {
+    stop: Arc<AtomicBool>,
+    events: Receiver<BridgeEvent>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+// Which way bytes crossed: from the app's own port to the far one, or back
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Way {
+    Out,
+    In,
+}
+
+pub enum BridgeEvent {
+    Data { way: Way, data: Bytes, at: Instant },
+    Error(String),
+}
+
+impl Bridge {
+    pub fn spawn(
+        near: &dyn SerialPort,
+        far: Box<dyn SerialPort>,
+        read_size: usize,
+        tap: bool,
+    ) -> serialport::Result<Self> {
+        // Clone everything first so a failure doesn't leave one direction running
+        let near_in = near.try_clone()?;
+        let near_out = match tap {
+            true => None,
+            false => Some(near.try_clone()?),
+        };
+        let far_in = far.try_clone()?;
+        let far_out = (!tap).then_some(far);
+        let stop = Arc::new(AtomicBool::new(false));
+        let (events, received) = mpsc::channel();
+        let threads = vec![
+            forward(near_in, far_out, Way::Out, read_size, &stop, &events),
+            forward(far_in, near_out, Way::In, read_size, &stop, &events),
+        ];
+        Ok(Self {
+            stop,
+            events: received,
+            threads,
+        })
+    }
+    // Everything that arrived since the last call, in the order it was read. The two threads
+    // can hand over their reads slightly out of order, so they're sorted by read time.
+    pub fn drain(&self) -> Vec<BridgeEvent> {
+        let mut events = self.events.try_iter().collect::<Vec<_>>();
+        events.sort_by_key(|event| match event {
+            BridgeEvent::Data { at, .. } => Some(*at),
+            BridgeEvent::Error(_) => None,
+        });
+        events
+    }
+}
+
+impl Drop for Bridge {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+// Copy bytes from one port to the other, or just report them when tapping, until stopped or
+// either side fails
+fn forward(
+    mut from: Box<dyn SerialPort>,
+    mut to: Option<Box<dyn SerialPort>>,
+    way: Way,
+    read_size: usize,
+    stop: &Arc<AtomicBool>,
+    events: &Sender<BridgeEvent>,
+) -> JoinHandle<()> {
+    let stop = Arc::clone(stop);
+    let events = events.clone();
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        while !stop.load(Ordering::Relaxed) {
+            buf.resize(reader::read_len(from.as_ref(), read_size), 0);
+            let failed = match from.read(&mut buf) {
+                Ok(0) => None,
+                Ok(n) => {
+                    let at = Instant::now();
+                    let written = match to.as_mut() {
+                        // A busy far side gets the same retries as a send from the UI
+                        Some(to) => writer::write_all(to.as_mut(), &buf[..n]),
+                        None => Ok(()),
+                    };
+                    match written {
+                        Ok(()) => {
+                            let data = Bytes::copy_from_slice(&buf[..n]);
+                            let _ = events.send(BridgeEvent::Data { way, data, at });
+                            None
+                        }
+                        Err(e) => Some(format!("Bridge failed to write: {e}")),
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::TimedOut => None,
+                Err(e) => Some(format!("Bridge failed to read: {e}")),
+            };
+            if let Some(e) = failed {
+                stop.store(true, Ordering::Relaxed);
+                let _ = events.send(BridgeEvent::Error(e));
+            }
+        }
+    })
+}
@@ -0,0 +1,83 @@
+use crate::framing::TxEnding;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// Quick-Send Buttons: commands sent often, one click each
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuickButton {
+    pub name: String,
+    // Text with \ escapes, or hex bytes when `hex` is set
+    pub payload: String,
+    pub hex: bool,
+    // Only added to text payloads
+    pub ending: TxEnding,
+}
+
+impl QuickButton {
+    fn new() -> Self {
+        Self {
+            name: String::new(),
+            payload: String::new(),
+            hex: false,
+            ending: TxEnding::None,
+        }
+    }
+    fn to_text(&self) -> String {
+        format!(
+            "[button]\nname={}\npayload={}\nhex={}\nending={}\n",
+            self.name, self.payload, self.hex, self.ending
+        )
+    }
+    fn set(&mut self, key: &str, value: &str) {
+        match key {
+            "name" => self.name = value.to_string(),
+            "payload" => self.payload = value.to_string(),
+            "hex" => self.hex = value == "true",
+            "ending" => {
+                self.ending = TxEnding::ALL
+                    .into_iter()
+                    .find(|ending| ending.to_string() == value)
+                    .unwrap_or(self.ending)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn buttons_path() -> io::Result<PathBuf> {
+    crate::workspace::config_dir()
+        .map(|dir| dir.join("buttons"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))
+}
+
+pub fn load() -> Vec<QuickButton> {
+    let Ok(text) = buttons_path().and_then(fs::read_to_string) else {
+        return Vec::new();
+    };
+    let mut buttons = Vec::new();
+    for line in text.lines() {
+        if line.trim() == "[button]" {
+            buttons.push(QuickButton::new());
+        } else if let Some((key, value)) = line.split_once('=')
+            && let Some(button) = buttons.last_mut()
+        {
+            // The payload keeps its spaces, which can be part of the command
+            button.set(key.trim(), value);
+        }
+    }
+    buttons
+}
+
+pub fn save(buttons: &[QuickButton]) -> io::Result<()> {
+    let path = buttons_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let text = buttons
+        .iter()
+        .map(QuickButton::to_text)
+        .collect::<Vec<_>>()
+        .join("\n");
+    fs::write(path, text)
+}
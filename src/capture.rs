@@ -0,0 +1,257 @@
+use rusqlite::{Connection, params};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Rows returned by one query
+const QUERY_LIMIT: usize = 500;
+
+// Direction of a Captured Frame
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Direction {
+    Rx,
+    Tx,
+    // A marker put in the log by hand, with its label as the text and no data
+    Marker,
+}
+
+impl Direction {
+    fn code(self) -> &'static str {
+        match self {
+            Direction::Rx => "rx",
+            Direction::Tx => "tx",
+            Direction::Marker => "marker",
+        }
+    }
+}
+
+struct Frame {
+    time_us: i64,
+    direction: Direction,
+    data: Vec<u8>,
+    text: String,
+}
+
+// Capture Database: every frame of every session, kept across runs
+//
+// Frames are handed to a thread that writes whatever has queued up in one transaction, so
+// the commits never hold up the UI. The thread ends, writing what it has, when the capture
+// is dropped.
+pub struct Capture {
+    frames: Sender<Frame>,
+    // Why the thread stopped writing, picked up by the UI
+    error: Arc<Mutex<Option<String>>>,
+}
+
+pub fn database_path() -> Option<PathBuf> {
+    crate::workspace::config_dir().map(|dir| dir.join("captures.sqlite"))
+}
+
+fn open_database(path: &Path) -> rusqlite::Result<Connection> {
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let conn = Connection::open(path)?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS sessions (
+             id INTEGER PRIMARY KEY,
+             port TEXT NOT NULL,
+             started_us INTEGER NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS frames (
+             id INTEGER PRIMARY KEY,
+             session INTEGER NOT NULL REFERENCES sessions(id),
+             time_us INTEGER NOT NULL,
+             direction TEXT NOT NULL,
+             data BLOB NOT NULL,
+             text TEXT NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS frames_time ON frames(time_us);",
+    )?;
+    Ok(conn)
+}
+
+pub fn unix_micros(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_micros() as i64)
+}
+
+impl Capture {
+    pub fn open(path: &Path, port: &str) -> rusqlite::Result<Self> {
+        let conn = open_database(path)?;
+        conn.execute(
+            "INSERT INTO sessions (port, started_us) VALUES (?1, ?2)",
+            params![port, unix_micros(SystemTime::now())],
+        )?;
+        let session = conn.last_insert_rowid();
+        let (frames, received) = mpsc::channel::<Frame>();
+        let error = Arc::new(Mutex::new(None));
+        let failed = Arc::clone(&error);
+        std::thread::spawn(move || {
+            let mut conn = conn;
+            while let Ok(frame) = received.recv() {
+                let batch = std::iter::once(frame)
+                    .chain(received.try_iter())
+                    .collect::<Vec<_>>();
+                if let Err(e) = insert(&mut conn, session, &batch) {
+                    *failed.lock().unwrap() = Some(e.to_string());
+                    return;
+                }
+            }
+        });
+        Ok(Self { frames, error })
+    }
+    pub fn record(&self, time: SystemTime, direction: Direction, data: &[u8], text: String) {
+        let _ = self.frames.send(Frame {
+            time_us: unix_micros(time),
+            direction,
+            data: data.to_vec(),
+            text,
+        });
+    }
+    pub fn failed(&self) -> Option<String> {
+        self.error.lock().unwrap().clone()
+    }
+}
+
+fn insert(conn: &mut Connection, session: i64, frames: &[Frame]) -> rusqlite::Result<()> {
+    let transaction = conn.transaction()?;
+    {
+        let mut insert = transaction.prepare_cached(
+            "INSERT INTO frames (session, time_us, direction, data, text)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        for frame in frames {
+            insert.execute(params![
+                session,
+                frame.time_us,
+                frame.direction.code(),
+                frame.data,
+                frame.text
+            ])?;
+        }
+    }
+    transaction.commit()
+}
+
+// How Far Back a Query Looks
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Since {
+    AllTime,
+    LastHour,
+    LastDay,
+    LastWeek,
+    LastMonth,
+}
+
+impl Since {
+    pub const ALL: [Since; 5] = [
+        Since::AllTime,
+        Since::LastHour,
+        Since::LastDay,
+        Since::LastWeek,
+        Since::LastMonth,
+    ];
+
+    pub fn start(self, now: SystemTime) -> Option<SystemTime> {
+        let hours = match self {
+            Since::AllTime => return None,
+            Since::LastHour => 1,
+            Since::LastDay => 24,
+            Since::LastWeek => 24 * 7,
+            Since::LastMonth => 24 * 30,
+        };
+        now.checked_sub(Duration::from_secs(hours * 3600))
+    }
+}
+
+impl std::fmt::Display for Since {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Since::AllTime => "All time",
+            Since::LastHour => "Last hour",
+            Since::LastDay => "Last day",
+            Since::LastWeek => "Last week",
+            Since::LastMonth => "Last month",
+        };
+        write!(f, "{name}")
+    }
+}
+
+// Query Filters; empty ones match everything
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    pub bytes: Vec<u8>,
+    pub text: String,
+    pub port: String,
+    pub since: Option<SystemTime>,
+}
+
+// One Row of Query Results
+#[derive(Debug, Clone)]
+pub struct Match {
+    pub time_us: i64,
+    pub port: String,
+    pub direction: String,
+    pub data: Vec<u8>,
+    pub text: String,
+}
+
+// Newest frames first, up to QUERY_LIMIT. Frames are stored as they were read, so a byte
+// pattern or text split across two reads isn't found.
+pub fn query(path: &Path, query: &Query) -> rusqlite::Result<Vec<Match>> {
+    let conn = open_database(path)?;
+    let mut statement = conn.prepare(
+        "SELECT frames.time_us, sessions.port, frames.direction, frames.data, frames.text
+         FROM frames JOIN sessions ON sessions.id = frames.session
+         WHERE (length(?1) = 0 OR instr(frames.data, ?1) > 0)
+           AND (?2 = '' OR instr(frames.text, ?2) > 0)
+           AND (?3 = '' OR sessions.port = ?3)
+           AND frames.time_us >= ?4
+         ORDER BY frames.time_us DESC
+         LIMIT ?5",
+    )?;
+    let rows = statement.query_map(
+        params![
+            query.bytes,
+            query.text,
+            query.port,
+            query.since.map_or(0, unix_micros),
+            QUERY_LIMIT as i64
+        ],
+        |row| {
+            Ok(Match {
+                time_us: row.get(0)?,
+                port: row.get(1)?,
+                direction: row.get(2)?,
+                data: row.get(3)?,
+                text: row.get(4)?,
+            })
+        },
+    )?;
+    rows.collect()
+}
+
+// UTC date and time with microseconds, e.g. 2024-05-01 13:45:07.123456
+pub fn format_time(time_us: i64) -> String {
+    let seconds = time_us.div_euclid(1_000_000);
+    let micros = time_us.rem_euclid(1_000_000);
+    let (days, second) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}.{micros:06}",
+        second / 3600,
+        second / 60 % 60,
+        second % 60
+    )
+}
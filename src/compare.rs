@@ -0,0 +1,66 @@
+// Comparing Two Sessions' Received Traffic
+//
+// Lines are aligned with a longest-common-subsequence diff, so one extra or missing line shows
+// up as a single difference instead of shifting everything after it.
+
+// Lines compared from the end of each stream; the diff table grows with the square of this
+pub const MAX_LINES: usize = 500;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Row {
+    Same(String),
+    // Only in the left session
+    Left(String),
+    // Only in the right session
+    Right(String),
+}
+
+// Offset of the first byte where the streams differ, or where the shorter one ends
+pub fn divergence(left: &[u8], right: &[u8]) -> Option<usize> {
+    match left.iter().zip(right).position(|(l, r)| l != r) {
+        Some(offset) => Some(offset),
+        None if left.len() != right.len() => Some(left.len().min(right.len())),
+        None => None,
+    }
+}
+
+// Split decoded text into lines, keeping the last `MAX_LINES`
+pub fn lines(text: &str) -> Vec<String> {
+    let lines = text
+        .split('\n')
+        .map(|line| line.trim_end_matches('\r').to_string())
+        .collect::<Vec<_>>();
+    let skip = lines.len().saturating_sub(MAX_LINES);
+    lines.into_iter().skip(skip).collect()
+}
+
+pub fn diff(left: &[String], right: &[String]) -> Vec<Row> {
+    // common[i][j]: length of the longest common run of left[i..] and right[j..]
+    let mut common = vec![vec![0u16; right.len() + 1]; left.len() + 1];
+    for i in (0..left.len()).rev() {
+        for j in (0..right.len()).rev() {
+            common[i][j] = match left[i] == right[j] {
+                true => common[i + 1][j + 1] + 1,
+                false => common[i + 1][j].max(common[i][j + 1]),
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut rows = Vec::new();
+    while i < left.len() && j < right.len() {
+        if left[i] == right[j] {
+            rows.push(Row::Same(left[i].clone()));
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            rows.push(Row::Left(left[i].clone()));
+            i += 1;
+        } else {
+            rows.push(Row::Right(right[j].clone()));
+            j += 1;
+        }
+    }
+    rows.extend(left[i..].iter().cloned().map(Row::Left));
+    rows.extend(right[j..].iter().cloned().map(Row::Right));
+    rows
+}
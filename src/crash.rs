@@ -0,0 +1,81 @@
+use crate::workspace;
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Internal log lines kept for the crash report
+const RECENT_LINES: usize = 50;
+
+// What the panic hook knows about the app when it crashes
+struct Context {
+    state: String,
+    recent: VecDeque<String>,
+}
+
+static CONTEXT: Mutex<Context> = Mutex::new(Context {
+    state: String::new(),
+    recent: VecDeque::new(),
+});
+
+fn crash_dir() -> Option<PathBuf> {
+    workspace::config_dir().map(|dir| dir.join("crashes"))
+}
+
+// Write a crash report on panic, then fall through to the default hook
+pub fn install() {
+    let default = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let backtrace = Backtrace::force_capture();
+        let (state, recent) = match CONTEXT.lock() {
+            Ok(context) => (
+                context.state.clone(),
+                context.recent.iter().cloned().collect::<Vec<_>>(),
+            ),
+            Err(_) => (String::from("(unavailable)"), Vec::new()),
+        };
+        let report = format!(
+            "Serial App {} crashed\n\n{info}\n\nState:\n{state}\n\nRecent log:\n{}\n\nBacktrace:\n{backtrace}\n",
+            crate::VERSION,
+            recent.join("\n")
+        );
+        if let Some(dir) = crash_dir()
+            && fs::create_dir_all(&dir).is_ok()
+        {
+            let unix = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_secs());
+            let path = dir.join(format!("crash-{unix}.txt"));
+            if fs::write(&path, report).is_ok() {
+                let _ = fs::write(dir.join("last-crash"), path.to_string_lossy().as_bytes());
+            }
+        }
+        default(info);
+    }));
+}
+
+// Replace the state summary included in a crash report
+pub fn set_state(state: String) {
+    if let Ok(mut context) = CONTEXT.lock() {
+        context.state = state;
+    }
+}
+
+pub fn note(line: String) {
+    if let Ok(mut context) = CONTEXT.lock() {
+        if context.recent.len() == RECENT_LINES {
+            context.recent.pop_front();
+        }
+        context.recent.push_back(line);
+    }
+}
+
+// The report from a crash during the previous run, once
+pub fn take_last_report() -> Option<PathBuf> {
+    let marker = crash_dir()?.join("last-crash");
+    let path = fs::read_to_string(&marker).ok()?;
+    let _ = fs::remove_file(marker);
+    Some(PathBuf::from(path.trim()))
+}
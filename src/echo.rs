@@ -0,0 +1,108 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// How long the device gets to echo what was sent
+const ECHO_TIMEOUT: Duration = Duration::from_millis(500);
+
+// Problems Found While Comparing Received Data Against What Was Sent
+pub enum EchoFault {
+    Mismatch {
+        offset: usize,
+        expected: u8,
+        got: u8,
+    },
+    Missing {
+        bytes: usize,
+    },
+}
+
+impl std::fmt::Display for EchoFault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EchoFault::Mismatch {
+                offset,
+                expected,
+                got,
+            } => write!(
+                f,
+                "Echo mismatch at byte {offset}: expected {expected:02X}, got {got:02X}"
+            ),
+            EchoFault::Missing { bytes } => write!(f, "No echo for the last {bytes} bytes sent"),
+        }
+    }
+}
+
+// Echo Verification: received bytes must repeat transmitted ones in order
+#[derive(Default)]
+pub struct EchoCheck {
+    pending: VecDeque<u8>,
+    // Bytes of the current run already matched, for reporting where a mismatch happened
+    matched: usize,
+    // Set while the bytes are still being written, so the timeout waits for the write to end
+    writing: bool,
+    deadline: Option<Instant>,
+}
+
+impl EchoCheck {
+    // Registered before the write starts, since the echo can come back before it's reported done
+    pub fn expect(&mut self, data: &[u8]) {
+        if self.pending.is_empty() {
+            self.matched = 0;
+        }
+        self.pending.extend(data);
+        self.writing = true;
+        self.deadline = None;
+    }
+    // The write is done, so whatever hasn't come back has the timeout to do so
+    pub fn written(&mut self, now: Instant) {
+        self.writing = false;
+        if !self.pending.is_empty() {
+            self.deadline = Some(now + ECHO_TIMEOUT);
+        }
+    }
+    // A failed or cancelled write's `len` bytes, the newest pending, won't all come back
+    pub fn abandon(&mut self, len: usize, now: Instant) {
+        self.pending
+            .truncate(self.pending.len().saturating_sub(len));
+        self.written(now);
+    }
+    // Compare received bytes with the oldest unechoed ones; anything after a mismatch resyncs
+    pub fn feed(&mut self, data: &[u8], now: Instant) -> Option<EchoFault> {
+        for &got in data {
+            let Some(expected) = self.pending.pop_front() else {
+                break;
+            };
+            if got != expected {
+                let offset = self.matched;
+                self.reset();
+                return Some(EchoFault::Mismatch {
+                    offset,
+                    expected,
+                    got,
+                });
+            }
+            self.matched += 1;
+            if !self.writing {
+                self.deadline = Some(now + ECHO_TIMEOUT);
+            }
+        }
+        if self.pending.is_empty() {
+            self.deadline = None;
+        }
+        None
+    }
+    pub fn check_timeout(&mut self, now: Instant) -> Option<EchoFault> {
+        if self.deadline.is_some_and(|deadline| now >= deadline) {
+            let bytes = self.pending.len();
+            self.reset();
+            return Some(EchoFault::Missing { bytes });
+        }
+        None
+    }
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.matched = 0;
+        self.writing = false;
+        self.deadline = None;
+    }
+}
@@ -0,0 +1,213 @@
+use std::borrow::Cow;
+
+// Code page 437 characters for bytes 0x80-0xFF; the lower half is plain ASCII
+const CP437_HIGH: &str = "ÇüéâäàåçêëèïîìÄÅ\
+                          ÉæÆôöòûùÿÖÜ¢£¥₧ƒ\
+                          áíóúñÑªº¿⌐¬½¼¡«»\
+                          ░▒▓│┤╡╢╖╕╣║╗╝╜╛┐\
+                          └┴┬├─┼╞╟╚╔╩╦╠═╬╧\
+                          ╨╤╥╙╘╒╓╫╪┘┌█▄▌▐▀\
+                          αßΓπΣσµτΦΘΩδ∞φε∩\
+                          ≡±≥≤⌠⌡÷≈°∙·√ⁿ²■\u{a0}";
+
+// Text Encodings for Displaying Received Data and Sending Commands
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TextEncoding {
+    Utf8,
+    Utf16Le,
+    Latin1,
+    Windows1252,
+    ShiftJis,
+    Gbk,
+    Cp437,
+}
+
+impl TextEncoding {
+    pub const ALL: [TextEncoding; 7] = [
+        TextEncoding::Utf8,
+        TextEncoding::Utf16Le,
+        TextEncoding::Latin1,
+        TextEncoding::Windows1252,
+        TextEncoding::ShiftJis,
+        TextEncoding::Gbk,
+        TextEncoding::Cp437,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Utf16Le => "UTF-16LE",
+            TextEncoding::Latin1 => "Latin-1",
+            TextEncoding::Windows1252 => "Windows-1252",
+            TextEncoding::ShiftJis => "Shift-JIS",
+            TextEncoding::Gbk => "GBK",
+            TextEncoding::Cp437 => "CP437",
+        }
+    }
+    fn multibyte(self) -> Option<&'static encoding_rs::Encoding> {
+        match self {
+            TextEncoding::Utf16Le => Some(encoding_rs::UTF_16LE),
+            TextEncoding::Windows1252 => Some(encoding_rs::WINDOWS_1252),
+            TextEncoding::ShiftJis => Some(encoding_rs::SHIFT_JIS),
+            TextEncoding::Gbk => Some(encoding_rs::GBK),
+            _ => None,
+        }
+    }
+    // Bytes that aren't valid in the encoding become U+FFFD
+    pub fn decode(self, data: &[u8]) -> Cow<'_, str> {
+        if let Some(encoding) = self.multibyte() {
+            return encoding.decode_without_bom_handling(data).0;
+        }
+        match self {
+            TextEncoding::Latin1 => Cow::Owned(data.iter().map(|&byte| char::from(byte)).collect()),
+            TextEncoding::Cp437 => Cow::Owned(
+                data.iter()
+                    .map(|&byte| match byte {
+                        0x00..=0x7F => char::from(byte),
+                        _ => CP437_HIGH.chars().nth(usize::from(byte - 0x80)).unwrap(),
+                    })
+                    .collect(),
+            ),
+            _ => String::from_utf8_lossy(data),
+        }
+    }
+    // Like `encode`, with \r \n \t \0 and \\ expanded. \xNN is that byte as is, whatever
+    // the encoding.
+    pub fn encode_escaped(self, text: &str) -> Result<Vec<u8>, String> {
+        let mut bytes = Vec::new();
+        let mut literal = String::new();
+        let mut chars = text.chars();
+        let encode = |literal: &mut String| {
+            self.encode(&std::mem::take(literal))
+                .ok_or_else(|| format!("has characters that can't be sent as {self}"))
+        };
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                literal.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('r') => literal.push('\r'),
+                Some('n') => literal.push('\n'),
+                Some('t') => literal.push('\t'),
+                Some('0') => literal.push('\0'),
+                Some('\\') => literal.push('\\'),
+                Some('x') => {
+                    let hex = chars.by_ref().take(2).collect::<String>();
+                    let byte = match hex.len() == 2 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                        true => u8::from_str_radix(&hex, 16).unwrap(),
+                        false => return Err(format!("'\\x{hex}' isn't a hex byte")),
+                    };
+                    bytes.extend(encode(&mut literal)?);
+                    bytes.push(byte);
+                }
+                Some(other) => return Err(format!("unknown escape '\\{other}'")),
+                None => return Err(String::from("ends with a lone '\\'")),
+            }
+        }
+        bytes.extend(encode(&mut literal)?);
+        Ok(bytes)
+    }
+    // Length of a character cut off at the end of `data`, 0 when it ends on a whole one.
+    // Only UTF-8 and UTF-16LE are checked; the other multi-byte encodings can't be told
+    // apart from the end without decoding from the start.
+    pub fn incomplete_tail(self, data: &[u8]) -> usize {
+        match self {
+            TextEncoding::Utf8 => utf8_incomplete_tail(data),
+            TextEncoding::Utf16Le => {
+                let odd = data.len() % 2;
+                let whole = &data[..data.len() - odd];
+                match whole {
+                    // A high surrogate waits for the low one after it
+                    [.., _, 0xD8..=0xDB] => odd + 2,
+                    _ => odd,
+                }
+            }
+            _ => 0,
+        }
+    }
+    // None when the text has characters the encoding can't represent
+    pub fn encode(self, text: &str) -> Option<Vec<u8>> {
+        // encoding_rs only encodes UTF-16 as UTF-8, so the code units are written out here
+        if self == TextEncoding::Utf16Le {
+            return Some(text.encode_utf16().flat_map(u16::to_le_bytes).collect());
+        }
+        if let Some(encoding) = self.multibyte() {
+            let (bytes, _, unmappable) = encoding.encode(text);
+            return (!unmappable).then(|| bytes.into_owned());
+        }
+        match self {
+            TextEncoding::Latin1 => text
+                .chars()
+                .map(|c| u8::try_from(u32::from(c)).ok())
+                .collect(),
+            TextEncoding::Cp437 => text
+                .chars()
+                .map(|c| match c {
+                    '\0'..='\x7F' => Some(c as u8),
+                    _ => CP437_HIGH
+                        .chars()
+                        .position(|high| high == c)
+                        .map(|i| 0x80 + i as u8),
+                })
+                .collect(),
+            _ => Some(text.as_bytes().to_vec()),
+        }
+    }
+}
+
+// Received text decoded as it streams in. A character split across two reads is held back
+// until the rest of it arrives, instead of turning into two U+FFFD.
+pub struct StreamDecoder {
+    encoding: TextEncoding,
+    // None for the single-byte encodings, which never split
+    decoder: Option<encoding_rs::Decoder>,
+}
+
+impl StreamDecoder {
+    pub fn new(encoding: TextEncoding) -> Self {
+        let decoder = match encoding {
+            TextEncoding::Utf8 => Some(encoding_rs::UTF_8),
+            _ => encoding.multibyte(),
+        };
+        Self {
+            encoding,
+            decoder: decoder.map(encoding_rs::Encoding::new_decoder_without_bom_handling),
+        }
+    }
+    pub fn decode(&mut self, data: &[u8]) -> String {
+        let Some(decoder) = self.decoder.as_mut() else {
+            return self.encoding.decode(data).into_owned();
+        };
+        let capacity = decoder
+            .max_utf8_buffer_length(data.len())
+            .unwrap_or(data.len() * 3 + 4);
+        let mut text = String::with_capacity(capacity);
+        let _ = decoder.decode_to_string(data, &mut text, false);
+        text
+    }
+}
+
+// Length of the UTF-8 sequence cut off at the end of `data`, 0 when it ends on a whole character
+fn utf8_incomplete_tail(data: &[u8]) -> usize {
+    for back in 1..=data.len().min(3) {
+        let byte = data[data.len() - back];
+        if byte & 0xC0 == 0x80 {
+            continue;
+        }
+        let needed = match byte {
+            0xC2..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF4 => 4,
+            _ => 1,
+        };
+        return if needed > back { back } else { 0 };
+    }
+    0
+}
+
+impl std::fmt::Display for TextEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
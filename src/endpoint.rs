@@ -0,0 +1,231 @@
+use serialport::{ClearBuffer, DataBits, FlowControl, Parity, SerialPort, StopBits};
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+// Virtual Serial Ports Exposed as Pipes and Sockets
+//
+// QEMU, VirtualBox and Hyper-V can attach a VM's serial port to a Windows named pipe
+// (`\\.\pipe\name`) or a Unix domain socket instead of real hardware. Both are plain byte
+// streams, so they're wrapped in the `SerialPort` trait and the rest of the app uses them like
+// any other port. Line settings are accepted without effect and there are no control lines.
+pub struct Endpoint {
+    name: String,
+    stream: Stream,
+    timeout: Duration,
+}
+
+#[cfg(unix)]
+type Stream = std::os::unix::net::UnixStream;
+#[cfg(windows)]
+type Stream = std::fs::File;
+
+// Whether a port name refers to a pipe or socket rather than a serial device. Besides socket
+// files, `unix:<path>` always names a stream socket (as in QEMU's `-serial unix:`), and on
+// Linux `@<name>` is a socket in the abstract namespace (socat's ABSTRACT-LISTEN)
+#[cfg(unix)]
+pub fn is_endpoint(name: &str) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+
+    name.starts_with("unix:")
+        || (cfg!(target_os = "linux") && name.starts_with('@'))
+        || std::fs::metadata(name).is_ok_and(|metadata| metadata.file_type().is_socket())
+}
+
+#[cfg(windows)]
+pub fn is_endpoint(name: &str) -> bool {
+    name.get(..9)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case(r"\\.\pipe\"))
+}
+
+pub fn open(name: &str, timeout: Duration) -> serialport::Result<Box<dyn SerialPort>> {
+    #[cfg(unix)]
+    let stream = connect(name)?;
+    #[cfg(windows)]
+    let stream = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(name)?;
+    let mut endpoint = Endpoint {
+        name: name.to_string(),
+        stream,
+        timeout,
+    };
+    endpoint.set_timeout(timeout)?;
+    Ok(Box::new(endpoint))
+}
+
+#[cfg(unix)]
+fn connect(name: &str) -> io::Result<Stream> {
+    #[cfg(target_os = "linux")]
+    if let Some(abstract_name) = name.strip_prefix('@') {
+        use std::os::linux::net::SocketAddrExt;
+        use std::os::unix::net::SocketAddr;
+
+        let address = SocketAddr::from_abstract_name(abstract_name)?;
+        return Stream::connect_addr(&address);
+    }
+    Stream::connect(name.strip_prefix("unix:").unwrap_or(name))
+}
+
+fn unsupported(what: &str) -> serialport::Error {
+    serialport::Error::new(
+        serialport::ErrorKind::Io(io::ErrorKind::Unsupported),
+        format!("{what} isn't available on a pipe or socket"),
+    )
+}
+
+impl Read for Endpoint {
+    // The reader treats an empty read as "no data yet", so the peer closing is an error here
+    #[cfg(unix)]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.stream.read(buf) {
+            Ok(0) if !buf.is_empty() => Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "the other end closed the connection",
+            )),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Err(io::Error::from(io::ErrorKind::TimedOut))
+            }
+            result => result,
+        }
+    }
+
+    // Synchronous pipe reads can't time out, so wait for data to arrive before reading
+    #[cfg(windows)]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        use std::os::windows::io::AsRawHandle;
+        use std::time::Instant;
+        use windows_sys::Win32::System::Pipes::PeekNamedPipe;
+
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let mut available = 0;
+            // SAFETY: the handle is the open pipe and only the byte count is written
+            let peeked = unsafe {
+                PeekNamedPipe(
+                    self.stream.as_raw_handle(),
+                    std::ptr::null_mut(),
+                    0,
+                    std::ptr::null_mut(),
+                    &mut available,
+                    std::ptr::null_mut(),
+                )
+            };
+            if peeked == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if available > 0 {
+                let len = buf.len().min(available as usize);
+                return self.stream.read(&mut buf[..len]);
+            }
+            if Instant::now() >= deadline {
+                return Err(io::Error::from(io::ErrorKind::TimedOut));
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
+
+impl Write for Endpoint {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.stream.write(buf) {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Err(io::Error::from(io::ErrorKind::TimedOut))
+            }
+            result => result,
+        }
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl SerialPort for Endpoint {
+    fn name(&self) -> Option<String> {
+        Some(self.name.clone())
+    }
+    fn baud_rate(&self) -> serialport::Result<u32> {
+        Err(unsupported("Baud rate"))
+    }
+    fn data_bits(&self) -> serialport::Result<DataBits> {
+        Err(unsupported("Data bits"))
+    }
+    fn flow_control(&self) -> serialport::Result<FlowControl> {
+        Ok(FlowControl::None)
+    }
+    fn parity(&self) -> serialport::Result<Parity> {
+        Err(unsupported("Parity"))
+    }
+    fn stop_bits(&self) -> serialport::Result<StopBits> {
+        Err(unsupported("Stop bits"))
+    }
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
+    fn set_baud_rate(&mut self, _: u32) -> serialport::Result<()> {
+        Ok(())
+    }
+    fn set_data_bits(&mut self, _: DataBits) -> serialport::Result<()> {
+        Ok(())
+    }
+    fn set_flow_control(&mut self, _: FlowControl) -> serialport::Result<()> {
+        Ok(())
+    }
+    fn set_parity(&mut self, _: Parity) -> serialport::Result<()> {
+        Ok(())
+    }
+    fn set_stop_bits(&mut self, _: StopBits) -> serialport::Result<()> {
+        Ok(())
+    }
+    fn set_timeout(&mut self, timeout: Duration) -> serialport::Result<()> {
+        // A zero socket timeout would mean "block forever"
+        let timeout = timeout.max(Duration::from_millis(1));
+        #[cfg(unix)]
+        {
+            self.stream.set_read_timeout(Some(timeout))?;
+            self.stream.set_write_timeout(Some(timeout))?;
+        }
+        self.timeout = timeout;
+        Ok(())
+    }
+    fn write_request_to_send(&mut self, _: bool) -> serialport::Result<()> {
+        Err(unsupported("RTS"))
+    }
+    fn write_data_terminal_ready(&mut self, _: bool) -> serialport::Result<()> {
+        Err(unsupported("DTR"))
+    }
+    fn read_clear_to_send(&mut self) -> serialport::Result<bool> {
+        Err(unsupported("CTS"))
+    }
+    fn read_data_set_ready(&mut self) -> serialport::Result<bool> {
+        Err(unsupported("DSR"))
+    }
+    fn read_ring_indicator(&mut self) -> serialport::Result<bool> {
+        Err(unsupported("RI"))
+    }
+    fn read_carrier_detect(&mut self) -> serialport::Result<bool> {
+        Err(unsupported("CD"))
+    }
+    fn bytes_to_read(&self) -> serialport::Result<u32> {
+        Ok(0)
+    }
+    fn bytes_to_write(&self) -> serialport::Result<u32> {
+        Ok(0)
+    }
+    fn clear(&self, _: ClearBuffer) -> serialport::Result<()> {
+        Ok(())
+    }
+    fn try_clone(&self) -> serialport::Result<Box<dyn SerialPort>> {
+        Ok(Box::new(Endpoint {
+            name: self.name.clone(),
+            stream: self.stream.try_clone()?,
+            timeout: self.timeout,
+        }))
+    }
+    fn set_break(&self) -> serialport::Result<()> {
+        Err(unsupported("Break"))
+    }
+    fn clear_break(&self) -> serialport::Result<()> {
+        Err(unsupported("Break"))
+    }
+}
@@ -0,0 +1,138 @@
+use crate::encoding::TextEncoding;
+use crate::log::{LogEntry, LogLevel, Source};
+use crate::stats::{json_string, slug};
+use crate::timestamp::TimestampFormat;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Log Export for Offline Analysis
+//
+// One row per entry with the wall-clock time, time since the port opened (for received data),
+// direction, level, the encoding the text was decoded with, the bytes received or sent as hex,
+// and the text.
+// Folded entries are written once with their repeat count.
+struct Row {
+    timestamp: String,
+    since_open_s: Option<f64>,
+    direction: String,
+    level: &'static str,
+    bytes: String,
+    byte_count: usize,
+    text: String,
+    repeats: usize,
+}
+
+fn row(entry: &LogEntry, encoding: TextEncoding) -> Row {
+    let level = match entry.level {
+        LogLevel::Data => "data",
+        LogLevel::Info => "info",
+        LogLevel::Warning => "warning",
+        LogLevel::Error => "error",
+        LogLevel::Marker => "marker",
+    };
+    // Bridged entries are labelled with the way they went
+    let (direction, text) = match (entry.source(), &entry.data) {
+        (Source::Rx, Some(data)) => (
+            match entry.text.is_empty() {
+                true => String::from("RX"),
+                false => entry.text.clone(),
+            },
+            encoding.decode(data).into_owned(),
+        ),
+        (Source::Tx, _) => (String::from("TX"), entry.text.clone()),
+        _ => (String::from("system"), entry.text.clone()),
+    };
+    let bytes = entry.data.as_deref().or(entry.sent.as_deref());
+    Row {
+        timestamp: TimestampFormat::Iso8601
+            .format(entry.at, None, None)
+            .unwrap_or_default(),
+        since_open_s: entry.time.map(|time| time.as_secs_f64()),
+        direction,
+        level,
+        bytes: bytes.map(hex::encode).unwrap_or_default(),
+        byte_count: bytes.map_or(0, <[u8]>::len),
+        text,
+        repeats: entry.repeats,
+    }
+}
+
+// Quoted when it holds a separator, quote or line break, with quotes doubled
+fn csv_field(value: &str) -> String {
+    match value.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", value.replace('"', "\"\"")),
+        false => value.to_string(),
+    }
+}
+
+fn to_csv(entries: &[LogEntry], encoding: TextEncoding) -> String {
+    let mut csv = String::from(
+        "timestamp,since_open_s,direction,level,encoding,bytes_hex,byte_count,text,repeats\n",
+    );
+    for row in entries.iter().map(|entry| row(entry, encoding)) {
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{},{}",
+            row.timestamp,
+            row.since_open_s
+                .map(|s| format!("{s:.6}"))
+                .unwrap_or_default(),
+            csv_field(&row.direction),
+            row.level,
+            encoding,
+            row.bytes,
+            row.byte_count,
+            csv_field(&row.text),
+            row.repeats
+        );
+    }
+    csv
+}
+
+fn to_json(entries: &[LogEntry], encoding: TextEncoding) -> String {
+    let rows = entries
+        .iter()
+        .map(|entry| {
+            let row = row(entry, encoding);
+            format!(
+                "    {{ \"timestamp\": {}, \"since_open_s\": {}, \"direction\": {}, \"level\": {}, \
+                 \"encoding\": {}, \"bytes_hex\": {}, \"byte_count\": {}, \"text\": {}, \
+                 \"repeats\": {} }}",
+                json_string(&row.timestamp),
+                row.since_open_s
+                    .map_or(String::from("null"), |s| format!("{s:.6}")),
+                json_string(&row.direction),
+                json_string(row.level),
+                json_string(encoding.name()),
+                json_string(&row.bytes),
+                row.byte_count,
+                json_string(&row.text),
+                row.repeats
+            )
+        })
+        .collect::<Vec<_>>();
+    format!("{{\n  \"entries\": [\n{}\n  ]\n}}\n", rows.join(",\n"))
+}
+
+// Write the entries as both CSV and JSON next to each other, returning the CSV path, e.g.
+// serial-log-motor-controller-1700000000.csv
+pub fn export(
+    entries: &[LogEntry],
+    encoding: TextEncoding,
+    dir: &Path,
+    label: &str,
+) -> std::io::Result<PathBuf> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let slug = slug(label);
+    let name = match slug.is_empty() {
+        true => format!("serial-log-{now}"),
+        false => format!("serial-log-{slug}-{now}"),
+    };
+    let csv_path = dir.join(format!("{name}.csv"));
+    std::fs::write(&csv_path, to_csv(entries, encoding))?;
+    std::fs::write(dir.join(format!("{name}.json")), to_json(entries, encoding))?;
+    Ok(csv_path)
+}
@@ -0,0 +1,107 @@
+// Received Data Formatting
+//
+// Chunks are kept as raw bytes in the log and only turned into text when displayed.
+
+pub fn hex(data: &[u8]) -> String {
+    data.iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// Canonical hexdump rows: offset, 16 bytes in two groups of 8, then the printable ones
+pub fn hexdump(data: &[u8]) -> Vec<String> {
+    data.chunks(16)
+        .enumerate()
+        .map(|(row, bytes)| {
+            let mut hex = String::new();
+            for i in 0..16 {
+                if i == 8 {
+                    hex.push(' ');
+                }
+                match bytes.get(i) {
+                    Some(byte) => hex.push_str(&format!("{byte:02X} ")),
+                    None => hex.push_str("   "),
+                }
+            }
+            let ascii = bytes
+                .iter()
+                .map(|&byte| match byte {
+                    0x20..=0x7E => char::from(byte),
+                    _ => '.',
+                })
+                .collect::<String>();
+            format!("{:08X}  {hex} |{ascii}|", row * 16)
+        })
+        .collect()
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+// Standard Base64 with padding
+pub fn base64(data: &[u8]) -> String {
+    let mut text = String::with_capacity(data.len().div_ceil(3) * 4);
+    for group in data.chunks(3) {
+        let bits = group.iter().enumerate().fold(0u32, |bits, (i, &byte)| {
+            bits | u32::from(byte) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            match i <= group.len() {
+                true => text.push(char::from(BASE64[(bits >> (18 - 6 * i) & 0x3F) as usize])),
+                false => text.push('='),
+            }
+        }
+    }
+    text
+}
+
+// Whitespace is ignored and the padding is optional
+pub fn from_base64(text: &str) -> Result<Vec<u8>, String> {
+    let mut data = Vec::with_capacity(text.len() / 4 * 3);
+    let mut bits = 0u32;
+    let mut count = 0;
+    let text = text.trim_end_matches(|c: char| c == '=' || c.is_whitespace());
+    for c in text.chars().filter(|c| !c.is_whitespace()) {
+        let Some(value) = BASE64.iter().position(|&b| char::from(b) == c) else {
+            return Err(format!("'{c}' isn't a Base64 character"));
+        };
+        bits = bits << 6 | value as u32;
+        count += 1;
+        if count == 4 {
+            data.extend_from_slice(&bits.to_be_bytes()[1..]);
+            bits = 0;
+            count = 0;
+        }
+    }
+    match count {
+        0 => {}
+        2 => data.push((bits >> 4) as u8),
+        3 => data.extend_from_slice(&((bits >> 2) as u16).to_be_bytes()),
+        _ => return Err(String::from("Length isn't valid for Base64")),
+    }
+    Ok(data)
+}
+
+// Byte values like "13 10 65 255" or "0x0D, 0x0A, 65", separated by spaces or commas
+pub fn from_byte_list(text: &str) -> Result<Vec<u8>, String> {
+    text.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            let value = match token
+                .strip_prefix("0x")
+                .or_else(|| token.strip_prefix("0X"))
+            {
+                Some(hex) => u8::from_str_radix(hex, 16),
+                None => token.parse(),
+            };
+            value.map_err(|_| format!("'{token}' isn't a byte value (0-255 or 0x00-0xFF)"))
+        })
+        .collect()
+}
+
+pub fn binary(data: &[u8]) -> String {
+    data.iter()
+        .map(|byte| format!("{byte:08b}"))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
@@ -0,0 +1,434 @@
+use crate::encoding::TextEncoding;
+use bytes::Bytes;
+use std::time::{Duration, Instant};
+
+// Received Data Framing
+//
+// The reader hands over whatever each read returned, which cuts device lines and packets at
+// arbitrary points. A framer regroups the bytes into one log entry per line or per frame
+// before they're logged.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Framing {
+    Chunks,
+    Lines(LineEnding),
+    // Frames end with a user-chosen byte sequence, which is kept with the frame
+    Delimiter,
+    // Frames end when the line has been silent for a while, as in Modbus RTU
+    IdleGap,
+    // Every frame is a record of the same length
+    FixedLength,
+    // Zero-terminated frames of Consistent Overhead Byte Stuffing, shown decoded
+    Cobs,
+    // RFC 1055 Serial Line IP frames, shown with the escapes undone
+    Slip,
+}
+
+// How Outgoing Commands Are Framed
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TxFraming {
+    None,
+    Cobs,
+    Slip,
+}
+
+impl TxFraming {
+    pub const ALL: [TxFraming; 3] = [TxFraming::None, TxFraming::Cobs, TxFraming::Slip];
+
+    pub fn apply(self, data: Vec<u8>) -> Vec<u8> {
+        match self {
+            TxFraming::None => data,
+            TxFraming::Cobs => {
+                let mut framed = cobs_encode(&data);
+                framed.push(0);
+                framed
+            }
+            TxFraming::Slip => slip_encode(&data),
+        }
+    }
+}
+
+impl std::fmt::Display for TxFraming {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            TxFraming::None => "Send as typed",
+            TxFraming::Cobs => "COBS",
+            TxFraming::Slip => "SLIP",
+        };
+        write!(f, "{name}")
+    }
+}
+
+// Terminator Appended to Commands Sent as Text
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TxEnding {
+    None,
+    Cr,
+    Lf,
+    CrLf,
+}
+
+impl TxEnding {
+    pub const ALL: [TxEnding; 4] = [TxEnding::None, TxEnding::Cr, TxEnding::Lf, TxEnding::CrLf];
+
+    // Appended to the text before it's encoded, so it's sent as e.g. UTF-16 too
+    pub fn text(self) -> &'static str {
+        match self {
+            TxEnding::None => "",
+            TxEnding::Cr => "\r",
+            TxEnding::Lf => "\n",
+            TxEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+impl std::fmt::Display for TxEnding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            TxEnding::None => "No line ending",
+            TxEnding::Cr => "CR",
+            TxEnding::Lf => "LF",
+            TxEnding::CrLf => "CRLF",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum LineEnding {
+    Lf,
+    Cr,
+    CrLf,
+}
+
+impl LineEnding {
+    fn bytes(self) -> &'static [u8] {
+        match self {
+            LineEnding::Lf => b"\n",
+            LineEnding::Cr => b"\r",
+            LineEnding::CrLf => b"\r\n",
+        }
+    }
+}
+
+impl Framing {
+    pub const ALL: [Framing; 9] = [
+        Framing::Chunks,
+        Framing::Lines(LineEnding::Lf),
+        Framing::Lines(LineEnding::Cr),
+        Framing::Lines(LineEnding::CrLf),
+        Framing::Delimiter,
+        Framing::IdleGap,
+        Framing::FixedLength,
+        Framing::Cobs,
+        Framing::Slip,
+    ];
+}
+
+impl std::fmt::Display for Framing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Framing::Chunks => "Raw reads",
+            Framing::Lines(LineEnding::Lf) => "Lines (LF)",
+            Framing::Lines(LineEnding::Cr) => "Lines (CR)",
+            Framing::Lines(LineEnding::CrLf) => "Lines (CRLF)",
+            Framing::Delimiter => "Delimiter",
+            Framing::IdleGap => "Idle gap",
+            Framing::FixedLength => "Fixed length",
+            Framing::Cobs => "COBS",
+            Framing::Slip => "SLIP",
+        };
+        write!(f, "{name}")
+    }
+}
+
+// Longest frame held back waiting for its delimiter
+const MAX_FRAME: usize = 4096;
+
+// Settings for the framings that need them
+pub struct FrameOptions {
+    pub delimiter: Vec<u8>,
+    pub gap: Duration,
+    pub length: usize,
+    // Raw reads never end partway through a character in this encoding
+    pub encoding: TextEncoding,
+}
+
+pub struct Framer {
+    framing: Framing,
+    options: FrameOptions,
+    pending: Vec<u8>,
+    // When the first and the latest pending bytes arrived
+    started: Option<Instant>,
+    last: Option<Instant>,
+    // Frames that couldn't be decoded since the last check
+    invalid: usize,
+}
+
+impl Framer {
+    pub fn new(framing: Framing, options: FrameOptions) -> Self {
+        Self {
+            framing,
+            options,
+            pending: Vec::new(),
+            started: None,
+            last: None,
+            invalid: 0,
+        }
+    }
+    // Frames completed by `data`, each stamped with the time its first byte arrived
+    pub fn feed(&mut self, data: Bytes, at: Instant) -> Vec<(Bytes, Instant)> {
+        match self.framing {
+            Framing::Chunks
+                if self.pending.is_empty() && self.options.encoding.incomplete_tail(&data) == 0 =>
+            {
+                vec![(data, at)]
+            }
+            // The start of a character cut off by the read waits for the rest of it
+            Framing::Chunks => {
+                if self.pending.is_empty() {
+                    self.started = Some(at);
+                }
+                self.pending.extend_from_slice(&data);
+                self.last = Some(at);
+                let end = self.pending.len() - self.options.encoding.incomplete_tail(&self.pending);
+                if end == 0 {
+                    return Vec::new();
+                }
+                let frame = Bytes::copy_from_slice(&self.pending[..end]);
+                self.pending.drain(..end);
+                let started = self.started.unwrap_or(at);
+                self.started = (!self.pending.is_empty()).then_some(at);
+                vec![(frame, started)]
+            }
+            // The line ending itself isn't shown
+            Framing::Lines(ending) => self.split(&data, at, ending.bytes(), false),
+            Framing::Delimiter if self.options.delimiter.is_empty() => vec![(data, at)],
+            Framing::Delimiter => {
+                let delimiter = std::mem::take(&mut self.options.delimiter);
+                let frames = self.split(&data, at, &delimiter, true);
+                self.options.delimiter = delimiter;
+                frames
+            }
+            Framing::IdleGap => {
+                let mut frames = Vec::new();
+                if self
+                    .last
+                    .is_some_and(|last| at.duration_since(last) >= self.options.gap)
+                {
+                    frames.extend(self.flush());
+                }
+                if self.pending.is_empty() {
+                    self.started = Some(at);
+                }
+                self.pending.extend_from_slice(&data);
+                self.last = Some(at);
+                if self.pending.len() >= MAX_FRAME {
+                    frames.extend(self.flush());
+                }
+                frames
+            }
+            Framing::FixedLength => {
+                let length = self.options.length.max(1);
+                let mut frames = Vec::new();
+                if self.pending.is_empty() {
+                    self.started = Some(at);
+                }
+                self.pending.extend_from_slice(&data);
+                while self.pending.len() >= length {
+                    let record = self.pending.drain(..length).collect::<Vec<_>>();
+                    frames.push((Bytes::from(record), self.started.unwrap_or(at)));
+                    self.started = Some(at);
+                }
+                if self.pending.is_empty() {
+                    self.started = None;
+                }
+                frames
+            }
+            // Frames that don't decode are shown as received
+            Framing::Cobs | Framing::Slip => {
+                let cobs = self.framing == Framing::Cobs;
+                let end = if cobs { 0 } else { SLIP_END };
+                let mut frames = self.split(&data, at, &[end], false);
+                // SLIP senders often start frames with END too, leaving empty frames between
+                frames.retain(|(frame, _)| !frame.is_empty());
+                for (frame, _) in &mut frames {
+                    let decoded = if cobs {
+                        cobs_decode(frame)
+                    } else {
+                        slip_decode(frame)
+                    };
+                    match decoded {
+                        Some(decoded) => *frame = Bytes::from(decoded),
+                        None => self.invalid += 1,
+                    }
+                }
+                frames
+            }
+        }
+    }
+    // Number of frames that failed to decode since the last call
+    pub fn take_invalid(&mut self) -> usize {
+        std::mem::take(&mut self.invalid)
+    }
+    // The pending frame once the line has been quiet for the gap, checked between reads
+    pub fn flush_idle(&mut self, now: Instant) -> Option<(Bytes, Instant)> {
+        match self.framing {
+            // A character that never completes is shown as received once the line goes quiet
+            Framing::IdleGap | Framing::Chunks
+                if now.duration_since(self.last?) >= self.options.gap =>
+            {
+                self.flush()
+            }
+            _ => None,
+        }
+    }
+    // Whatever is still held back, e.g. when the listener stops
+    pub fn flush(&mut self) -> Option<(Bytes, Instant)> {
+        self.last = None;
+        let started = self.started.take()?;
+        Some((Bytes::from(std::mem::take(&mut self.pending)), started))
+    }
+    fn split(
+        &mut self,
+        data: &[u8],
+        at: Instant,
+        delimiter: &[u8],
+        keep: bool,
+    ) -> Vec<(Bytes, Instant)> {
+        let mut frames = Vec::new();
+        if self.pending.is_empty() {
+            self.started = Some(at);
+        }
+        // A delimiter may straddle the previous read and this one
+        let mut search = self.pending.len().saturating_sub(delimiter.len() - 1);
+        self.pending.extend_from_slice(data);
+        while let Some(found) = self.pending[search..]
+            .windows(delimiter.len())
+            .position(|window| window == delimiter)
+        {
+            let end = search + found + delimiter.len();
+            let frame_end = if keep { end } else { search + found };
+            let frame = Bytes::copy_from_slice(&self.pending[..frame_end]);
+            self.pending.drain(..end);
+            frames.push((frame, self.started.unwrap_or(at)));
+            self.started = Some(at);
+            search = 0;
+        }
+        if self.pending.len() >= MAX_FRAME {
+            frames.extend(self.flush());
+        }
+        if self.pending.is_empty() {
+            self.started = None;
+        }
+        frames
+    }
+}
+
+// Parse a delimiter: hex bytes ("7E", "0x03 0x04"), an ASCII control name ("ETX"), or text
+// with escapes ("\r\n>", "\x1b")
+pub fn parse_delimiter(spec: &str) -> Result<Vec<u8>, String> {
+    const NAMES: [&str; 32] = [
+        "NUL", "SOH", "STX", "ETX", "EOT", "ENQ", "ACK", "BEL", "BS", "HT", "LF", "VT", "FF", "CR",
+        "SO", "SI", "DLE", "DC1", "DC2", "DC3", "DC4", "NAK", "SYN", "ETB", "CAN", "EM", "SUB",
+        "ESC", "FS", "GS", "RS", "US",
+    ];
+    let trimmed = spec.trim();
+    if trimmed.is_empty() {
+        return Err(String::from("the delimiter is empty"));
+    }
+    if let Some(code) = NAMES
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(trimmed))
+    {
+        return Ok(vec![code as u8]);
+    }
+    let tokens = trimmed
+        .split_whitespace()
+        .map(|token| token.strip_prefix("0x").unwrap_or(token))
+        .collect::<Vec<_>>();
+    if tokens
+        .iter()
+        .all(|token| token.len() == 2 && token.chars().all(|c| c.is_ascii_hexdigit()))
+    {
+        return tokens
+            .iter()
+            .map(|token| u8::from_str_radix(token, 16).map_err(|e| e.to_string()))
+            .collect();
+    }
+    TextEncoding::Utf8.encode_escaped(spec)
+}
+
+// COBS: each run of non-zero bytes is prefixed with its length plus one, so the encoded frame
+// has no zeros and a 0x00 can end it
+pub fn cobs_encode(data: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![0];
+    let mut code_at = 0;
+    let mut code = 1u8;
+    for &byte in data {
+        if byte != 0 {
+            encoded.push(byte);
+            code += 1;
+        }
+        if byte == 0 || code == 0xFF {
+            encoded[code_at] = code;
+            code_at = encoded.len();
+            encoded.push(0);
+            code = 1;
+        }
+    }
+    encoded[code_at] = code;
+    encoded
+}
+
+pub fn cobs_decode(data: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        let code = data[i] as usize;
+        let end = i + code;
+        if code == 0 || end > data.len() {
+            return None;
+        }
+        decoded.extend_from_slice(&data[i + 1..end]);
+        i = end;
+        if code < 0xFF && i < data.len() {
+            decoded.push(0);
+        }
+    }
+    Some(decoded)
+}
+
+const SLIP_END: u8 = 0xC0;
+const SLIP_ESC: u8 = 0xDB;
+const SLIP_ESC_END: u8 = 0xDC;
+const SLIP_ESC_ESC: u8 = 0xDD;
+
+// SLIP: END and ESC bytes inside the packet are escaped, and END marks both ends
+pub fn slip_encode(data: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![SLIP_END];
+    for &byte in data {
+        match byte {
+            SLIP_END => encoded.extend([SLIP_ESC, SLIP_ESC_END]),
+            SLIP_ESC => encoded.extend([SLIP_ESC, SLIP_ESC_ESC]),
+            _ => encoded.push(byte),
+        }
+    }
+    encoded.push(SLIP_END);
+    encoded
+}
+
+pub fn slip_decode(data: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(data.len());
+    let mut bytes = data.iter();
+    while let Some(&byte) = bytes.next() {
+        match byte {
+            SLIP_ESC => match bytes.next() {
+                Some(&SLIP_ESC_END) => decoded.push(SLIP_END),
+                Some(&SLIP_ESC_ESC) => decoded.push(SLIP_ESC),
+                _ => return None,
+            },
+            _ => decoded.push(byte),
+        }
+    }
+    Some(decoded)
+}
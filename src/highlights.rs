@@ -0,0 +1,95 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// Highlight Rules: received lines matching a pattern are shown in a color of their own
+#[derive(Debug, Clone, PartialEq)]
+pub struct HighlightRule {
+    // A regular expression in the log search's syntax
+    pub pattern: String,
+    // A color name like red, or hex like #ff8000
+    pub color: String,
+}
+
+impl HighlightRule {
+    fn to_text(&self) -> String {
+        format!("[rule]\npattern={}\ncolor={}\n", self.pattern, self.color)
+    }
+}
+
+// Mid-tone colors that read on both light and dark themes
+const NAMED_COLORS: &[(&str, [u8; 3])] = &[
+    ("red", [0xe0, 0x40, 0x40]),
+    ("orange", [0xf0, 0x90, 0x20]),
+    ("yellow", [0xd8, 0xc0, 0x20]),
+    ("green", [0x40, 0xb8, 0x40]),
+    ("cyan", [0x20, 0xb8, 0xc8]),
+    ("blue", [0x40, 0x80, 0xe8]),
+    ("purple", [0xa0, 0x60, 0xe0]),
+    ("magenta", [0xe0, 0x40, 0xd0]),
+    ("gray", [0x90, 0x90, 0x90]),
+];
+
+pub fn color_names() -> impl Iterator<Item = &'static str> {
+    NAMED_COLORS.iter().map(|(name, _)| *name)
+}
+
+// RGB of a color name or #rrggbb
+pub fn parse_color(text: &str) -> Option<[u8; 3]> {
+    let text = text.trim();
+    if let Some(hex) = text.strip_prefix('#') {
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let byte = |at: usize| u8::from_str_radix(&hex[at..at + 2], 16).ok();
+        return Some([byte(0)?, byte(2)?, byte(4)?]);
+    }
+    NAMED_COLORS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(text))
+        .map(|(_, rgb)| *rgb)
+}
+
+fn rules_path() -> io::Result<PathBuf> {
+    crate::workspace::config_dir()
+        .map(|dir| dir.join("highlights"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))
+}
+
+pub fn load() -> Vec<HighlightRule> {
+    let Ok(text) = rules_path().and_then(fs::read_to_string) else {
+        return Vec::new();
+    };
+    let mut rules = Vec::new();
+    for line in text.lines() {
+        if line.trim() == "[rule]" {
+            rules.push(HighlightRule {
+                pattern: String::new(),
+                color: String::new(),
+            });
+        } else if let Some((key, value)) = line.split_once('=')
+            && let Some(rule) = rules.last_mut()
+        {
+            // The pattern keeps its spaces, which can be part of what it matches
+            match key.trim() {
+                "pattern" => rule.pattern = value.to_string(),
+                "color" => rule.color = value.trim().to_string(),
+                _ => {}
+            }
+        }
+    }
+    rules
+}
+
+pub fn save(rules: &[HighlightRule]) -> io::Result<()> {
+    let path = rules_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let text = rules
+        .iter()
+        .map(HighlightRule::to_text)
+        .collect::<Vec<_>>()
+        .join("\n");
+    fs::write(path, text)
+}
@@ -0,0 +1,104 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// Span of the control line chart; older activity is dropped
+pub const WINDOW: Duration = Duration::from_secs(10);
+
+// Control Lines
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Line {
+    Rts,
+    Cts,
+    Dtr,
+    Dsr,
+}
+
+impl Line {
+    pub const ALL: [Line; 4] = [Line::Rts, Line::Cts, Line::Dtr, Line::Dsr];
+
+    fn index(self) -> usize {
+        match self {
+            Line::Rts => 0,
+            Line::Cts => 1,
+            Line::Dtr => 2,
+            Line::Dsr => 3,
+        }
+    }
+}
+
+impl std::fmt::Display for Line {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Line::Rts => "RTS",
+            Line::Cts => "CTS",
+            Line::Dtr => "DTR",
+            Line::Dsr => "DSR",
+        };
+        write!(f, "{name}")
+    }
+}
+
+// Control Line State Changes and Data Activity Over Time
+pub struct LineTimeline {
+    start: Instant,
+    changes: [Vec<(Instant, bool)>; 4],
+    activity: VecDeque<Instant>,
+}
+
+impl LineTimeline {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            changes: Default::default(),
+            activity: VecDeque::new(),
+        }
+    }
+    // Store a line state only when it differs from the last one recorded
+    pub fn record(&mut self, line: Line, state: bool, at: Instant) {
+        let changes = &mut self.changes[line.index()];
+        if changes.last().map(|(_, last)| *last) != Some(state) {
+            changes.push((at, state));
+        }
+    }
+    pub fn record_activity(&mut self, at: Instant) {
+        self.activity.push_back(at);
+        while let Some(first) = self.activity.front()
+            && at.saturating_duration_since(*first) > WINDOW
+        {
+            self.activity.pop_front();
+        }
+    }
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        now.duration_since(self.start)
+    }
+    // Sample a line into `columns` slots covering the `window` that ends at `now`
+    pub fn samples(&self, line: Line, now: Instant, window: Duration, columns: usize) -> Vec<bool> {
+        let changes = &self.changes[line.index()];
+        (0..columns)
+            .map(|column| {
+                let at = slot_time(now, window, columns, column + 1);
+                changes
+                    .iter()
+                    .take_while(|(changed, _)| *changed <= at)
+                    .last()
+                    .is_some_and(|(_, state)| *state)
+            })
+            .collect()
+    }
+    // Mark the slots of the `window` ending at `now` that saw RX or TX data
+    pub fn activity_samples(&self, now: Instant, window: Duration, columns: usize) -> Vec<bool> {
+        let mut samples = vec![false; columns];
+        let from = now.checked_sub(window).unwrap_or(self.start);
+        for at in self.activity.iter().rev().take_while(|at| **at >= from) {
+            let offset = at.duration_since(from).as_secs_f32() / window.as_secs_f32();
+            let column = ((offset * columns as f32) as usize).min(columns - 1);
+            samples[column] = true;
+        }
+        samples
+    }
+}
+
+fn slot_time(now: Instant, window: Duration, columns: usize, column: usize) -> Instant {
+    let back = window.mul_f32(1.0 - column as f32 / columns as f32);
+    now.checked_sub(back).unwrap_or(now)
+}
@@ -0,0 +1,555 @@
+use bytes::Bytes;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::Sender;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Entries paged back in from disk at a time
+pub const PAGE_SIZE: usize = 500;
+
+// Log Entry Severity
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum LogLevel {
+    Data,
+    Info,
+    Warning,
+    Error,
+    // A line put in by hand to mark a point in the log
+    Marker,
+}
+
+impl LogLevel {
+    fn code(self) -> char {
+        match self {
+            LogLevel::Data => 'D',
+            LogLevel::Info => 'I',
+            LogLevel::Warning => 'W',
+            LogLevel::Error => 'E',
+            LogLevel::Marker => 'M',
+        }
+    }
+    fn from_code(code: &str) -> Self {
+        match code {
+            "I" => LogLevel::Info,
+            "W" => LogLevel::Warning,
+            "E" => LogLevel::Error,
+            "M" => LogLevel::Marker,
+            _ => LogLevel::Data,
+        }
+    }
+}
+// Which way an entry's data went; everything else comes from the app itself
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Source {
+    Tx,
+    Rx,
+    System,
+}
+// Log Entry
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub level: LogLevel,
+    pub text: String,
+    // Raw bytes of received data, formatted only when displayed
+    pub data: Option<Bytes>,
+    // Bytes of a send, kept for export and not shown
+    pub sent: Option<Bytes>,
+    // When received data was read, relative to the port opening, to the microsecond
+    pub time: Option<Duration>,
+    // Wall-clock time the entry was logged
+    pub at: SystemTime,
+    pub repeats: usize,
+    // Show every repeat of a folded entry and all the data of a long one
+    pub expanded: bool,
+}
+
+impl LogEntry {
+    pub fn new(level: LogLevel, text: impl Into<String>) -> Self {
+        Self {
+            level,
+            text: text.into(),
+            data: None,
+            sent: None,
+            time: None,
+            at: SystemTime::now(),
+            repeats: 1,
+            expanded: false,
+        }
+    }
+    pub fn received(data: Bytes, time: Duration) -> Self {
+        Self {
+            data: Some(data),
+            time: Some(time),
+            ..Self::new(LogLevel::Data, String::new())
+        }
+    }
+    pub fn sent(text: impl Into<String>, data: Bytes) -> Self {
+        Self {
+            sent: Some(data),
+            ..Self::new(LogLevel::Data, text)
+        }
+    }
+    // Received and bridged entries carry their bytes, while sends are logged as text once
+    // they've gone out
+    pub fn source(&self) -> Source {
+        match (&self.data, self.level) {
+            (Some(_), _) => Source::Rx,
+            (None, LogLevel::Data) => Source::Tx,
+            (None, _) => Source::System,
+        }
+    }
+    // Rough inline + heap footprint, counted against the memory budget
+    fn size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.text.len()
+            + self.data.as_ref().map_or(0, Bytes::len)
+            + self.sent.as_ref().map_or(0, Bytes::len)
+    }
+    // One line per entry: level, repeat count, time in microseconds, wall-clock time in
+    // microseconds since 1970, data (or the bytes sent) as hex, and the text with line breaks
+    // escaped
+    fn to_line(&self) -> String {
+        let text = self
+            .text
+            .replace('\\', "\\\\")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r");
+        let data = self
+            .data
+            .as_deref()
+            .or(self.sent.as_deref())
+            .map(hex::encode)
+            .unwrap_or_default();
+        let time = self
+            .time
+            .map(|time| time.as_micros().to_string())
+            .unwrap_or_default();
+        let at = self
+            .at
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_micros());
+        format!(
+            "{}\t{}\t{time}\t{at}\t{data}\t{text}\n",
+            self.level.code(),
+            self.repeats
+        )
+    }
+    fn from_line(line: &str) -> Self {
+        let mut fields = line.trim_end_matches('\n').splitn(6, '\t');
+        let level = LogLevel::from_code(fields.next().unwrap_or_default());
+        let repeats = fields.next().and_then(|r| r.parse().ok()).unwrap_or(1);
+        let time = fields
+            .next()
+            .and_then(|time| time.parse().ok())
+            .map(Duration::from_micros);
+        let at = fields
+            .next()
+            .and_then(|at| at.parse().ok())
+            .map_or(UNIX_EPOCH, |at| UNIX_EPOCH + Duration::from_micros(at));
+        let data = fields
+            .next()
+            .filter(|data| !data.is_empty())
+            .and_then(|data| hex::decode(data).ok())
+            .map(Bytes::from);
+        // Only received data has a time, so bytes without one were sent
+        let (data, sent) = match (level, time) {
+            (LogLevel::Data, None) => (None, data),
+            _ => (data, None),
+        };
+        let mut text = String::new();
+        let mut chars = fields.next().unwrap_or_default().chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                text.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => text.push('\n'),
+                Some('r') => text.push('\r'),
+                Some(escaped) => text.push(escaped),
+                None => {}
+            }
+        }
+        Self {
+            level,
+            text,
+            data,
+            sent,
+            time,
+            at,
+            repeats,
+            expanded: false,
+        }
+    }
+}
+
+// Older entries written out to a temp file once the memory budget is exceeded
+struct Spill {
+    file: File,
+    path: PathBuf,
+    offsets: Vec<u64>,
+    errors: Vec<usize>,
+    markers: Vec<usize>,
+}
+
+impl Drop for Spill {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+// A window of spilled entries read back for scrollback
+pub struct Page {
+    pub start: usize,
+    pub entries: Vec<LogEntry>,
+}
+
+// What a clear took away, kept so the last clear can be undone
+pub struct Cleared {
+    entries: Vec<LogEntry>,
+    memory: usize,
+    dropped: usize,
+    spill: Option<Spill>,
+    rx_index: Vec<(u64, usize)>,
+    rx_bytes: u64,
+}
+
+impl Cleared {
+    pub fn len(&self) -> usize {
+        self.dropped
+            + self.spill.as_ref().map_or(0, |spill| spill.offsets.len())
+            + self.entries.len()
+    }
+}
+
+// Session Log: the newest entries in memory, older ones spilled to disk or, when they aren't
+// kept, dropped. Entries keep their index either way, so the dropped ones are counted.
+pub struct Log {
+    entries: Vec<LogEntry>,
+    memory: usize,
+    budget: usize,
+    max_entries: Option<usize>,
+    keep_on_disk: bool,
+    dropped: usize,
+    spill: Option<Spill>,
+    page: Option<Page>,
+    // Offset of each data entry in the received byte stream, for go-to-offset
+    rx_index: Vec<(u64, usize)>,
+    rx_bytes: u64,
+    // Gets a copy of every entry while logging to a file
+    sink: Option<Sender<LogEntry>>,
+}
+
+impl Log {
+    pub fn new(budget: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            memory: 0,
+            budget,
+            max_entries: None,
+            keep_on_disk: true,
+            dropped: 0,
+            spill: None,
+            page: None,
+            rx_index: Vec::new(),
+            rx_bytes: 0,
+            sink: None,
+        }
+    }
+    pub fn set_sink(&mut self, sink: Option<Sender<LogEntry>>) {
+        self.sink = sink;
+    }
+    pub fn push(&mut self, entry: LogEntry) {
+        if let Some(sink) = &self.sink {
+            let _ = sink.send(entry.clone());
+        }
+        if entry.level != LogLevel::Data {
+            crate::crash::note(format!("[{}] {}", entry.level.code(), entry.text));
+        }
+        self.append(entry);
+    }
+    fn append(&mut self, entry: LogEntry) {
+        if let Some(data) = &entry.data {
+            self.rx_index.push((self.rx_bytes, self.len()));
+            self.rx_bytes += data.len() as u64;
+        }
+        self.memory += entry.size();
+        self.entries.push(entry);
+        self.enforce_limits();
+    }
+    // Append an entry, folding it into the previous one when it repeats the same data line
+    pub fn push_folded(&mut self, entry: LogEntry) {
+        match self.entries.last_mut() {
+            Some(last)
+                if last.level == LogLevel::Data
+                    && last.text == entry.text
+                    && last.data == entry.data =>
+            {
+                if let Some(sink) = &self.sink {
+                    let _ = sink.send(entry.clone());
+                }
+                last.repeats += 1;
+                self.rx_bytes += entry.data.map_or(0, |data| data.len() as u64);
+            }
+            _ => self.push(entry),
+        }
+    }
+    // Number of entries dropped, which come first
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+    // Number of entries on disk, which come after the dropped ones and before everything in
+    // memory
+    pub fn spilled(&self) -> usize {
+        self.spill.as_ref().map_or(0, |spill| spill.offsets.len())
+    }
+    pub fn first_in_memory(&self) -> usize {
+        self.dropped + self.spilled()
+    }
+    pub fn len(&self) -> usize {
+        self.first_in_memory() + self.entries.len()
+    }
+    pub fn memory_usage(&self) -> usize {
+        self.memory
+    }
+    // Entries held in memory, starting at index `first_in_memory()`
+    pub fn entries(&self) -> &[LogEntry] {
+        &self.entries
+    }
+    pub fn page(&self) -> Option<&Page> {
+        self.page.as_ref()
+    }
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut LogEntry> {
+        let first = self.first_in_memory();
+        if index >= first {
+            return self.entries.get_mut(index - first);
+        }
+        let page = self.page.as_mut()?;
+        page.entries.get_mut(index.checked_sub(page.start)?)
+    }
+    // Drop every entry, including any spilled to disk, handing them back for an undo. A file
+    // the log is copied to keeps everything.
+    pub fn clear(&mut self) -> Cleared {
+        self.page = None;
+        Cleared {
+            entries: std::mem::take(&mut self.entries),
+            memory: std::mem::take(&mut self.memory),
+            dropped: std::mem::take(&mut self.dropped),
+            spill: self.spill.take(),
+            rx_index: std::mem::take(&mut self.rx_index),
+            rx_bytes: std::mem::take(&mut self.rx_bytes),
+        }
+    }
+    // Put cleared entries back in front of everything logged since
+    pub fn restore(&mut self, cleared: Cleared) -> io::Result<()> {
+        let mut since = self.read_spilled()?;
+        since.append(&mut self.entries);
+        self.entries = cleared.entries;
+        self.memory = cleared.memory;
+        self.dropped = cleared.dropped;
+        self.spill = cleared.spill;
+        self.page = None;
+        self.rx_index = cleared.rx_index;
+        self.rx_bytes = cleared.rx_bytes;
+        self.set_keep_on_disk(self.keep_on_disk);
+        for entry in since {
+            self.append(entry);
+        }
+        Ok(())
+    }
+    // Every entry on disk, oldest first
+    fn read_spilled(&mut self) -> io::Result<Vec<LogEntry>> {
+        let Some(spill) = self.spill.as_mut() else {
+            return Ok(Vec::new());
+        };
+        spill.file.seek(SeekFrom::Start(0))?;
+        BufReader::new(&spill.file)
+            .lines()
+            .map(|line| line.map(|line| LogEntry::from_line(&line)))
+            .collect()
+    }
+    // Index of the entry holding byte `offset` of everything received, unless it was dropped
+    pub fn find_offset(&self, offset: u64) -> Option<usize> {
+        if offset >= self.rx_bytes {
+            return None;
+        }
+        let next = self.rx_index.partition_point(|(start, _)| *start <= offset);
+        Some(self.rx_index[next.checked_sub(1)?].1)
+    }
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.enforce_limits();
+    }
+    pub fn set_max_entries(&mut self, max_entries: Option<usize>) {
+        self.max_entries = max_entries;
+        self.enforce_limits();
+    }
+    // Once older entries stop being kept on disk, those already there are dropped too, so the
+    // dropped entries always come first
+    pub fn set_keep_on_disk(&mut self, keep: bool) {
+        self.keep_on_disk = keep;
+        if !keep && let Some(spill) = self.spill.take() {
+            self.forget(spill.offsets.len());
+            self.page = None;
+        }
+    }
+    pub fn error_count(&self) -> usize {
+        self.level_indices(LogLevel::Error).count()
+    }
+    pub fn marker_count(&self) -> usize {
+        self.level_indices(LogLevel::Marker).count()
+    }
+    // Errors or markers, the levels the spill file keeps track of
+    fn level_indices(&self, level: LogLevel) -> impl Iterator<Item = usize> + '_ {
+        let first = self.first_in_memory();
+        let on_disk = self.spill.iter().flat_map(move |spill| {
+            let spilled = match level {
+                LogLevel::Marker => &spill.markers,
+                _ => &spill.errors,
+            };
+            spilled.iter().map(|i| self.dropped + i)
+        });
+        let in_memory = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(move |(_, entry)| entry.level == level)
+            .map(move |(i, _)| first + i);
+        on_disk.chain(in_memory)
+    }
+    // Search the log for the nearest error before or after `from`, wrapping around the ends
+    pub fn find_error(&self, from: Option<usize>, forward: bool) -> Option<usize> {
+        self.find_level(LogLevel::Error, from, forward)
+    }
+    pub fn find_marker(&self, from: Option<usize>, forward: bool) -> Option<usize> {
+        self.find_level(LogLevel::Marker, from, forward)
+    }
+    fn find_level(&self, level: LogLevel, from: Option<usize>, forward: bool) -> Option<usize> {
+        let found = self.level_indices(level).collect::<Vec<_>>();
+        if forward {
+            from.and_then(|from| found.iter().find(|i| **i > from))
+                .or(found.first())
+                .copied()
+        } else {
+            from.and_then(|from| found.iter().rev().find(|i| **i < from))
+                .or(found.last())
+                .copied()
+        }
+    }
+    // Read up to PAGE_SIZE spilled entries starting at index `start` back from disk
+    pub fn page_in(&mut self, start: usize) -> io::Result<()> {
+        let Some(spill) = self.spill.as_mut() else {
+            return Ok(());
+        };
+        let start = start
+            .saturating_sub(self.dropped)
+            .min(spill.offsets.len().saturating_sub(PAGE_SIZE));
+        let count = PAGE_SIZE.min(spill.offsets.len() - start);
+        spill.file.seek(SeekFrom::Start(spill.offsets[start]))?;
+        let mut reader = BufReader::new(&spill.file);
+        let mut entries = Vec::with_capacity(count);
+        let mut line = String::new();
+        for _ in 0..count {
+            line.clear();
+            reader.read_line(&mut line)?;
+            entries.push(LogEntry::from_line(&line));
+        }
+        self.page = Some(Page {
+            start: self.dropped + start,
+            entries,
+        });
+        Ok(())
+    }
+    pub fn close_page(&mut self) {
+        self.page = None;
+    }
+    // Move the oldest in-memory entries out once the memory budget or entry limit is passed:
+    // to comfortably under budget, and down to the limit. The limit is let run over by an
+    // eighth first, so entries go in batches rather than one per push.
+    fn enforce_limits(&mut self) {
+        let over_limit = self
+            .max_entries
+            .is_some_and(|max| self.entries.len() > max + max / 8);
+        if self.memory <= self.budget && !over_limit {
+            return;
+        }
+        let target = self.budget / 4 * 3;
+        let keep = self.max_entries.unwrap_or(usize::MAX);
+        let mut count = 0;
+        let mut freed = 0;
+        // The newest entry always stays in memory so folding keeps working
+        while count + 1 < self.entries.len()
+            && (self.memory - freed > target || self.entries.len() - count > keep)
+        {
+            freed += self.entries[count].size();
+            count += 1;
+        }
+        if count == 0 {
+            return;
+        }
+        if self.keep_on_disk {
+            // Without a spill file everything stays in memory rather than being dropped
+            if self.spill_oldest(count).is_ok() {
+                self.memory -= freed;
+            }
+        } else {
+            self.entries.drain(..count);
+            self.memory -= freed;
+            self.forget(count);
+        }
+    }
+    // Count the oldest `count` entries after those already dropped as dropped
+    fn forget(&mut self, count: usize) {
+        self.dropped += count;
+        let dropped = self.dropped;
+        self.rx_index.retain(|(_, index)| *index >= dropped);
+    }
+    fn spill_oldest(&mut self, count: usize) -> io::Result<()> {
+        if self.spill.is_none() {
+            // Each log gets its own file, since several can live in one process
+            static NEXT_SPILL: AtomicUsize = AtomicUsize::new(0);
+            let path = std::env::temp_dir().join(format!(
+                "serial-app-{}-{}.log",
+                std::process::id(),
+                NEXT_SPILL.fetch_add(1, Ordering::Relaxed)
+            ));
+            let file = OpenOptions::new()
+                .create(true)
+                .truncate(true)
+                .read(true)
+                .write(true)
+                .open(&path)?;
+            self.spill = Some(Spill {
+                file,
+                path,
+                offsets: Vec::new(),
+                errors: Vec::new(),
+                markers: Vec::new(),
+            });
+        }
+        let spill = self.spill.as_mut().unwrap();
+        let mut offset = spill.file.seek(SeekFrom::End(0))?;
+        let mut chunk = String::new();
+        let mut offsets = Vec::with_capacity(count);
+        for entry in &self.entries[..count] {
+            let line = entry.to_line();
+            offsets.push(offset);
+            offset += line.len() as u64;
+            chunk.push_str(&line);
+        }
+        spill.file.write_all(chunk.as_bytes())?;
+        let first = spill.offsets.len();
+        for (i, entry) in self.entries[..count].iter().enumerate() {
+            match entry.level {
+                LogLevel::Error => spill.errors.push(first + i),
+                LogLevel::Marker => spill.markers.push(first + i),
+                _ => {}
+            }
+        }
+        spill.offsets.extend(offsets);
+        self.entries.drain(..count);
+        Ok(())
+    }
+}
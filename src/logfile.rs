@@ -0,0 +1,94 @@
+use crate::encoding::TextEncoding;
+use crate::format;
+use crate::log::{LogEntry, LogLevel};
+use crate::timestamp::TimestampFormat;
+use std::fs::OpenOptions;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+
+// Logging to a File
+//
+// Every entry pushed to the session log is handed to a thread that appends it to the file as
+// one line, so a slow disk never holds up the UI. The thread ends, flushing what it has,
+// when the log stops sending to it.
+pub struct LogFile {
+    pub path: PathBuf,
+    // Why the thread stopped writing, picked up by the UI
+    error: Arc<Mutex<Option<String>>>,
+}
+
+impl LogFile {
+    // Opened here so a bad path fails right away; received text is decoded as `encoding`
+    pub fn start(path: &Path, encoding: TextEncoding) -> io::Result<(Self, Sender<LogEntry>)> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let (sender, entries) = mpsc::channel::<LogEntry>();
+        let error = Arc::new(Mutex::new(None));
+        let failed = Arc::clone(&error);
+        std::thread::spawn(move || {
+            let mut file = BufWriter::new(file);
+            while let Ok(entry) = entries.recv() {
+                // Flush whenever the queue runs dry, so the file stays current
+                let written = std::iter::once(entry)
+                    .chain(entries.try_iter())
+                    .try_for_each(|entry| writeln!(file, "{}", line(&entry, encoding)))
+                    .and_then(|()| file.flush());
+                if let Err(e) = written {
+                    *failed.lock().unwrap() = Some(e.to_string());
+                    return;
+                }
+            }
+        });
+        let log_file = Self {
+            path: path.to_path_buf(),
+            error,
+        };
+        Ok((log_file, sender))
+    }
+    pub fn failed(&self) -> Option<String> {
+        self.error.lock().unwrap().clone()
+    }
+}
+
+// e.g. "2024-05-01T13:45:07.123+02:00 RX 6 bytes: hello\r\n [68 65 6C 6C 6F 0D 0A]"
+fn line(entry: &LogEntry, encoding: TextEncoding) -> String {
+    let time = TimestampFormat::Iso8601
+        .format(entry.at, None, None)
+        .unwrap_or_default();
+    let Some(data) = &entry.data else {
+        let level = match entry.level {
+            LogLevel::Data => "DATA",
+            LogLevel::Info => "INFO",
+            LogLevel::Warning => "WARN",
+            LogLevel::Error => "ERROR",
+            LogLevel::Marker => "MARK",
+        };
+        return format!("{time} {level} {}", escape(&entry.text));
+    };
+    // Bridged data is labelled with the way it went
+    let label = match entry.text.is_empty() {
+        true => String::from("RX"),
+        false => entry.text.clone(),
+    };
+    format!(
+        "{time} {label} {} bytes: {} [{}]",
+        data.len(),
+        escape(&encoding.decode(data)),
+        format::hex(data)
+    )
+}
+
+// One entry per line, so line breaks and other control characters are written as escapes
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\r' => String::from("\\r"),
+            '\n' => String::from("\\n"),
+            '\t' => String::from("\\t"),
+            '\\' => String::from("\\\\"),
+            c if c.is_control() => format!("\\x{:02x}", u32::from(c)),
+            c => c.to_string(),
+        })
+        .collect()
+}
@@ -4,56 +4,1059 @@
 // Prevent terminal from running in the background on Windows
 #![windows_subsystem = "windows"]
 
+mod alarms;
+mod ansi;
+mod breaks;
+mod bridge;
+mod buttons;
+mod capture;
+mod compare;
+mod crash;
+mod echo;
+mod encoding;
+mod endpoint;
+mod export;
+mod format;
+mod framing;
+mod highlights;
+mod lines;
+mod log;
+mod logfile;
+mod mavlink;
+mod modbus;
+mod nmea;
+mod parity;
+mod ports;
+mod profiles;
+mod reader;
+mod ring;
+mod schedule;
+mod search;
+mod setup;
+mod slcan;
+mod stats;
+mod timestamp;
+mod ttl;
+mod upload;
+mod values;
+mod workspace;
+mod writer;
+mod xmodem;
+mod zmodem;
+
+use alarms::{Monitor, Threshold};
+use ansi::AnsiMode;
+use breaks::BreakDetector;
+use bridge::{Bridge, BridgeEvent, Way};
+use buttons::QuickButton;
+use bytes::Bytes;
+use capture::{Capture, Direction, Since};
+use compare::Row;
+use echo::EchoCheck;
+use encoding::{StreamDecoder, TextEncoding};
+use framing::{FrameOptions, Framer, Framing, TxEnding, TxFraming};
+use highlights::HighlightRule;
+use iced::advanced::widget::{self, operation::focusable};
 use iced::border::Radius;
+use iced::keyboard::{self, Key, Modifiers, key::Named};
 use iced::time::{Duration, every};
 use iced::widget::{
-    button, checkbox, column, combo_box, container, radio, row, scrollable, text, text_input,
+    Column, button, checkbox, column, combo_box, container, horizontal_space, mouse_area,
+    progress_bar, radio, rich_text, row, scrollable, span, text, text_editor, text_input,
+    vertical_space,
+};
+use iced::{
+    Border, Bottom, Center, Color, Element, Event, Fill, Font, Point, Size, Subscription, Task,
+    Theme, event, window,
 };
-use iced::{Border, Element, Fill, Size, Subscription, Theme, window};
-use serialport::{DataBits, Parity, StopBits};
-use std::io::Write;
+use lines::{Line, LineTimeline, WINDOW};
+use log::{Cleared, Log, LogEntry, LogLevel, Source};
+use logfile::LogFile;
+use modbus::{Slave, Table};
+use parity::LineParity;
+use ports::PortEntry;
+use profiles::Profile;
+use reader::{OverflowPolicy, Reader, RxEvent};
+use search::Search;
+use serialport::{DataBits, FlowControl, StopBits};
+use slcan::Bitrate;
+use stats::SessionStats;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::ffi::OsString;
+use std::fmt::Write as _;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::{Range, RangeInclusive};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
+use timestamp::TimestampFormat;
+use ttl::{Script, Step};
+use upload::{Upload, UploadStep};
+use values::Layout;
+use workspace::Session;
+use writer::{Control, Pacing, WriteError, WriteEvent};
+use xmodem::{Receiver, Sender, Transfer, Variant, XmodemStep};
+use zmodem::Offer;
 
 const VERSION: &str = "v0.7";
+const DEFAULT_LOG_BUDGET_MB: usize = 64;
+// Received bytes shown for an entry until it is expanded
+const PREVIEW_BYTES: usize = 256;
+// Matches listed in the command palette
+const PALETTE_ROWS: usize = 10;
+const DEFAULT_READ_TIMEOUT_MS: u64 = 10;
+const DEFAULT_POLL_MS: u64 = 10;
+// Silence that ends a frame in idle gap framing
+const DEFAULT_FRAME_GAP_MS: u64 = 20;
+const DEFAULT_FRAME_LENGTH: u64 = 12;
+// Time between sends of a repeated command
+const DEFAULT_REPEAT_MS: u64 = 500;
+// Pause after each line when the editor sends line by line
+const DEFAULT_LINE_DELAY_MS: u64 = 100;
+// How long Send File waits for an ACK, and how often a chunk is re-sent without one
+const DEFAULT_ACK_TIMEOUT_MS: u64 = 1000;
+const DEFAULT_ACK_RETRIES: u32 = 3;
+// Sent commands kept for Up/Down recall
+const HISTORY_LEN: usize = 100;
+// Logs with more entries than this only lay out the rows near the viewport
+const LAZY_ENTRIES: usize = 500;
+// Height of one line of log text: the default 16 px text at 1.3 line height
+const LOG_ROW_HEIGHT: f32 = 20.8;
 
 fn main() -> iced::Result {
+    crash::install();
+    // Run App
+    iced::daemon(Windows::title, Windows::update, Windows::view)
+        .subscription(Windows::subscription)
+        .theme(Windows::theme)
+        .run_with(Windows::new)
+}
+// Comparison of Two Tabs' Received Traffic, e.g. old and new firmware running side by side
+fn compare_view<'a>(left: &SerialApp, right: &SerialApp) -> Element<'a, WindowMessage> {
+    let (left_data, right_data) = (left.received(), right.received());
+    let summary = match compare::divergence(&left_data, &right_data) {
+        None => format!("Received streams match ({} bytes)", left_data.len()),
+        Some(offset) => format!(
+            "Received streams diverge at byte {offset} ({} vs {} bytes)",
+            left_data.len(),
+            right_data.len()
+        ),
+    };
+    let rows = compare::diff(
+        &compare::lines(&left.selected_encoding.unwrap().decode(&left_data)),
+        &compare::lines(&right.selected_encoding.unwrap().decode(&right_data)),
+    );
+    let rows = rows.into_iter().map(|row| match row {
+        Row::Same(line) => text(format!("  {line}")).into(),
+        Row::Left(line) => text(format!("- {line}")).style(text::danger).into(),
+        Row::Right(line) => text(format!("+ {line}")).style(text::success).into(),
+    });
+    column![
+        text(summary),
+        text(format!(
+            "- only in {}    + only in {}",
+            left.tab_label(),
+            right.tab_label()
+        ))
+        .size(12),
+        scrollable(column(rows).width(Fill))
+            .anchor_bottom()
+            .height(Fill),
+    ]
+    .spacing(10)
+    .padding(20)
+    .into()
+}
+// Recovery Dialog after a crash in the previous run
+fn crash_view(path: &Path) -> Element<'_, Message> {
+    container(
+        column![
+            text("Serial App closed unexpectedly").size(24),
+            text("A crash report with a backtrace and recent activity was saved to:"),
+            text(path.display().to_string()),
+            text("Please attach it when reporting the problem."),
+            row![
+                horizontal_space(),
+                button("Copy Path")
+                    .padding(10)
+                    .on_press(Message::CopyCrashReportPath),
+                button("Continue")
+                    .padding(10)
+                    .on_press(Message::DismissCrashReport),
+            ]
+            .spacing(20),
+        ]
+        .spacing(20),
+    )
+    .padding(20)
+    .into()
+}
+// A positive number from an advanced setting, or its default
+fn tuning(value: &str, default: u64) -> u64 {
+    value
+        .trim()
+        .parse()
+        .ok()
+        .filter(|value| *value > 0)
+        .unwrap_or(default)
+}
+// Device names for the permission checks
+fn port_names(ports: &[PortEntry]) -> Vec<String> {
+    ports.iter().map(|port| port.name.clone()).collect()
+}
+// Initial Window Settings
+fn window_settings() -> window::Settings {
     let rs232_icon = window::icon::from_rgba(include_bytes!("icon.png").to_vec(), 24, 24).ok(); // TESTING
 
-    // Initial Window Settings
-    let settings = window::Settings {
+    window::Settings {
         size: Size::new(500.0, 500.0),
         min_size: Some(Size::new(500.0, 500.0)),
         icon: rs232_icon, // TESTING
         ..Default::default()
-    };
-    // Run App
-    iced::application(SerialApp::title, SerialApp::update, SerialApp::view)
-        .subscription(SerialApp::subscription)
-        .window(settings)
-        .theme(SerialApp::theme)
-        .run()
+    }
+}
+// Open Windows, each holding tabs of independent apps with their own port, settings, and log
+struct Windows {
+    tabs: BTreeMap<window::Id, Tabs>,
+    sizes: BTreeMap<window::Id, Size>,
+    positions: BTreeMap<window::Id, Point>,
+    // Windows being opened by a workspace restore, with the workspace name and their sessions
+    pending: BTreeMap<window::Id, (String, Vec<Session>)>,
+    // Tabs on their way into a window of their own
+    detached: BTreeMap<window::Id, SerialApp>,
+    // Report left by a crash in the previous run, shown in the first window
+    crash_report: Option<PathBuf>,
+    // Hash of what the crash report summary shows, to rebuild it only when that changes
+    crash_state: u64,
+}
+// Tabs in one Window. Ids are never reused, so messages still in flight for a closed tab
+// are dropped instead of reaching its neighbour.
+struct Tabs {
+    apps: Vec<(usize, SerialApp)>,
+    active: usize,
+    // Tab shown beside the active one in split view
+    split: Option<usize>,
+    // Show the split tabs' received traffic compared line by line instead of their views
+    compare: bool,
+    next_id: usize,
+}
+impl Tabs {
+    fn new() -> Self {
+        Self {
+            apps: Vec::new(),
+            active: 0,
+            split: None,
+            compare: false,
+            next_id: 0,
+        }
+    }
+    // Add a tab and switch to it
+    fn add(&mut self, app: SerialApp) -> usize {
+        let tab = self.next_id;
+        self.next_id += 1;
+        self.apps.push((tab, app));
+        self.active = tab;
+        tab
+    }
+    fn get_mut(&mut self, tab: usize) -> Option<&mut SerialApp> {
+        self.apps
+            .iter_mut()
+            .find(|(id, _)| *id == tab)
+            .map(|(_, app)| app)
+    }
+    fn get(&self, tab: usize) -> Option<&SerialApp> {
+        self.apps
+            .iter()
+            .find(|(id, _)| *id == tab)
+            .map(|(_, app)| app)
+    }
+    fn active(&self) -> Option<&SerialApp> {
+        self.get(self.active)
+    }
+    // Selecting the tab in the other pane swaps the two panes
+    fn select(&mut self, tab: usize) {
+        if self.split == Some(tab) {
+            self.split = Some(self.active);
+        }
+        self.active = tab;
+    }
+    // Split view pairs the active tab with the one after it
+    fn toggle_split(&mut self) {
+        self.compare = false;
+        self.split = match self.split {
+            Some(_) => None,
+            None => self
+                .apps
+                .iter()
+                .position(|(id, _)| *id == self.active)
+                .map(|index| self.apps[(index + 1) % self.apps.len()].0)
+                .filter(|tab| *tab != self.active),
+        };
+    }
+    // Remove a tab to close or detach it, moving to its neighbour if it was active
+    fn take(&mut self, tab: usize) -> Option<SerialApp> {
+        let index = self.apps.iter().position(|(id, _)| *id == tab)?;
+        let (_, app) = self.apps.remove(index);
+        if self.split == Some(tab) {
+            self.split = None;
+            self.compare = false;
+        }
+        if tab == self.active
+            && let Some(split) = self.split.take()
+        {
+            self.active = split;
+            self.compare = false;
+        } else if tab == self.active
+            && let Some((id, _)) = self.apps.get(index.min(self.apps.len().saturating_sub(1)))
+        {
+            self.active = *id;
+        }
+        Some(app)
+    }
+    // Cycle to the next tab
+    fn next(&mut self) {
+        if let Some(index) = self.apps.iter().position(|(id, _)| *id == self.active) {
+            self.select(self.apps[(index + 1) % self.apps.len()].0);
+        }
+    }
+}
+// Window Messages
+#[derive(Debug, Clone)]
+enum WindowMessage {
+    Opened(window::Id),
+    Closed(window::Id),
+    Resized(window::Id, Size),
+    Moved(window::Id, Point),
+    // Keyboard shortcuts go to whichever tab is active when they arrive
+    Shortcut(window::Id, Message),
+    App(window::Id, usize, Message),
+}
+// Window Functions
+impl Windows {
+    fn new() -> (Self, Task<WindowMessage>) {
+        let (_, open) = window::open(window_settings());
+        let windows = Self {
+            tabs: BTreeMap::new(),
+            sizes: BTreeMap::new(),
+            positions: BTreeMap::new(),
+            pending: BTreeMap::new(),
+            detached: BTreeMap::new(),
+            crash_report: crash::take_last_report(),
+            crash_state: 0,
+        };
+        (windows, open.map(WindowMessage::Opened))
+    }
+    fn title(&self, id: window::Id) -> String {
+        self.tabs
+            .get(&id)
+            .and_then(Tabs::active)
+            .map(SerialApp::title)
+            .unwrap_or_default()
+    }
+    fn update(&mut self, message: WindowMessage) -> Task<WindowMessage> {
+        let task = self.handle(message);
+        let state = self.state_hash();
+        if state != self.crash_state {
+            self.crash_state = state;
+            crash::set_state(self.summary());
+        }
+        task
+    }
+    // Changes when a tab or window comes or goes, or a port, listener or send starts or stops
+    fn state_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.tabs.len().hash(&mut hasher);
+        for tabs in self.tabs.values() {
+            tabs.apps.len().hash(&mut hasher);
+            for (_, app) in &tabs.apps {
+                app.selected_port.hash(&mut hasher);
+                app.port.is_some().hash(&mut hasher);
+                app.selected_baud_rate.hash(&mut hasher);
+                matches!(app.recv_state, RecvState::Listening).hash(&mut hasher);
+                app.sending.is_some().hash(&mut hasher);
+            }
+        }
+        hasher.finish()
+    }
+    // One line per tab for crash reports
+    fn summary(&self) -> String {
+        self.tabs
+            .values()
+            .enumerate()
+            .flat_map(|(i, tabs)| {
+                tabs.apps.iter().enumerate().map(move |(j, (_, app))| {
+                    format!(
+                        "Window {} tab {}: port {} ({}), {} baud, listener {}, sending {}",
+                        i + 1,
+                        j + 1,
+                        app.selected_port.as_deref().unwrap_or("none"),
+                        if app.port.is_some() { "open" } else { "closed" },
+                        app.selected_baud_rate.unwrap_or_default(),
+                        match app.recv_state {
+                            RecvState::Idle => "off",
+                            RecvState::Listening => "on",
+                        },
+                        app.sending.is_some()
+                    )
+                })
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+    fn handle(&mut self, message: WindowMessage) -> Task<WindowMessage> {
+        match message {
+            WindowMessage::Opened(id) => {
+                let mut tabs = Tabs::new();
+                let mut tasks = Vec::new();
+                if let Some(app) = self.detached.remove(&id) {
+                    tabs.add(app);
+                    self.tabs.insert(id, tabs);
+                    return Task::none();
+                }
+                match self.pending.remove(&id) {
+                    Some((name, sessions)) => {
+                        // Start on the tab that was showing, or the first one
+                        let (mut active, mut split) = (None, None);
+                        for session in sessions {
+                            let mut app = SerialApp::new();
+                            app.workspace_name = name.clone();
+                            let restored = app.restore(&session);
+                            let tab = tabs.add(app);
+                            if session.active {
+                                active = Some(tab);
+                            }
+                            if session.split {
+                                split = Some(tab);
+                            }
+                            tasks.push(
+                                restored.map(move |message| WindowMessage::App(id, tab, message)),
+                            );
+                        }
+                        tabs.active = active
+                            .or(tabs.apps.first().map(|(tab, _)| *tab))
+                            .unwrap_or_default();
+                        tabs.split = split.filter(|split| *split != tabs.active);
+                    }
+                    None => {
+                        tabs.add(SerialApp::new());
+                    }
+                }
+                if let Some((_, app)) = tabs.apps.first_mut() {
+                    app.crash_report = self.crash_report.take();
+                }
+                self.tabs.insert(id, tabs);
+                Task::batch(tasks)
+            }
+            WindowMessage::Closed(id) => {
+                self.tabs.remove(&id);
+                self.sizes.remove(&id);
+                self.positions.remove(&id);
+                if self.tabs.is_empty() && self.pending.is_empty() && self.detached.is_empty() {
+                    iced::exit()
+                } else {
+                    Task::none()
+                }
+            }
+            WindowMessage::Resized(id, size) => {
+                self.sizes.insert(id, size);
+                Task::none()
+            }
+            WindowMessage::Moved(id, position) => {
+                self.positions.insert(id, position);
+                Task::none()
+            }
+            WindowMessage::Shortcut(id, message) => match self.tabs.get(&id) {
+                Some(tabs) => self.handle(WindowMessage::App(id, tabs.active, message)),
+                None => Task::none(),
+            },
+            WindowMessage::App(_, _, Message::NewWindow) => {
+                let (_, open) = window::open(window_settings());
+                open.map(WindowMessage::Opened)
+            }
+            WindowMessage::App(id, _, Message::NewTab) => {
+                if let Some(tabs) = self.tabs.get_mut(&id) {
+                    tabs.add(SerialApp::new());
+                }
+                Task::none()
+            }
+            WindowMessage::App(id, tab, Message::SelectTab) => {
+                if let Some(tabs) = self.tabs.get_mut(&id) {
+                    tabs.select(tab);
+                }
+                Task::none()
+            }
+            WindowMessage::App(id, _, Message::SplitView) => {
+                if let Some(tabs) = self.tabs.get_mut(&id) {
+                    tabs.toggle_split();
+                }
+                Task::none()
+            }
+            WindowMessage::App(id, tab, Message::DetachTab) => self.detach(id, tab),
+            WindowMessage::App(id, _, Message::CompareView) => {
+                if let Some(tabs) = self.tabs.get_mut(&id) {
+                    tabs.compare = !tabs.compare;
+                }
+                Task::none()
+            }
+            WindowMessage::App(id, _, Message::NextTab) => {
+                if let Some(tabs) = self.tabs.get_mut(&id) {
+                    tabs.next();
+                }
+                Task::none()
+            }
+            // Closing the last tab closes its window
+            WindowMessage::App(id, tab, Message::CloseTab) => match self.tabs.get_mut(&id) {
+                Some(tabs) => {
+                    tabs.take(tab);
+                    match tabs.apps.is_empty() {
+                        true => window::close(id),
+                        false => Task::none(),
+                    }
+                }
+                None => Task::none(),
+            },
+            WindowMessage::App(id, tab, Message::SaveWorkspace) => {
+                self.save_workspace(id, tab);
+                Task::none()
+            }
+            WindowMessage::App(id, tab, Message::RestoreWorkspace) => {
+                self.restore_workspace(id, tab)
+            }
+            // Every tab in every window with an open port writes the broadcast
+            WindowMessage::App(id, tab, Message::Broadcast(kind, label, reported, data)) => {
+                let mut tasks = Vec::new();
+                for (window, tabs) in self.tabs.iter_mut() {
+                    for (to, app) in tabs.apps.iter_mut().filter(|(_, app)| app.port.is_some()) {
+                        let (window, to) = (*window, *to);
+                        let message =
+                            Message::Broadcast(kind, label.clone(), reported, data.clone());
+                        tasks.push(
+                            app.update(message)
+                                .map(move |message| WindowMessage::App(window, to, message)),
+                        );
+                    }
+                }
+                if tasks.is_empty()
+                    && let Some(app) = self.tabs.get_mut(&id).and_then(|tabs| tabs.get_mut(tab))
+                {
+                    app.log_messages
+                        .push(LogEntry::new(LogLevel::Warning, "No open ports to send to"));
+                }
+                Task::batch(tasks)
+            }
+            WindowMessage::App(id, _, Message::RequestAttention) => {
+                window::request_user_attention(id, Some(window::UserAttention::Critical))
+            }
+            WindowMessage::App(id, tab, message) => {
+                match self.tabs.get_mut(&id).and_then(|tabs| tabs.get_mut(tab)) {
+                    Some(app) => app
+                        .update(message)
+                        .map(move |message| WindowMessage::App(id, tab, message)),
+                    None => Task::none(),
+                }
+            }
+        }
+    }
+    fn view(&self, id: window::Id) -> Element<'_, WindowMessage> {
+        let Some(tabs) = self.tabs.get(&id) else {
+            return horizontal_space().into();
+        };
+        let Some(app) = tabs.active() else {
+            return horizontal_space().into();
+        };
+        let active = tabs.active;
+        let bar = tabs.apps.iter().fold(row![], |bar, (tab, app)| {
+            let tab = *tab;
+            let select = button(text(app.tab_label()).size(14))
+                .padding([5, 10])
+                .style(match tab == active {
+                    true => button::primary,
+                    false if tabs.split == Some(tab) => button::success,
+                    false => button::secondary,
+                })
+                .on_press(WindowMessage::App(id, tab, Message::SelectTab));
+            let close = button(text("x").size(14))
+                .padding([5, 8])
+                .style(button::text)
+                .on_press(WindowMessage::App(id, tab, Message::CloseTab));
+            bar.push(row![select, close].align_y(Center))
+        });
+        let new_tab = button(text("+").size(14))
+            .padding([5, 10])
+            .style(button::text)
+            .on_press(WindowMessage::App(id, active, Message::NewTab));
+        let split = button(
+            text(if tabs.split.is_some() {
+                "Unsplit"
+            } else {
+                "Split"
+            })
+            .size(14),
+        )
+        .padding([5, 10])
+        .style(button::text)
+        .on_press_maybe((tabs.apps.len() > 1).then_some(WindowMessage::App(
+            id,
+            active,
+            Message::SplitView,
+        )));
+        let compare = button(text(if tabs.compare { "Uncompare" } else { "Compare" }).size(14))
+            .padding([5, 10])
+            .style(button::text)
+            .on_press_maybe(tabs.split.is_some().then_some(WindowMessage::App(
+                id,
+                active,
+                Message::CompareView,
+            )));
+        let detach = button(text("Detach").size(14))
+            .padding([5, 10])
+            .style(button::text)
+            .on_press_maybe((tabs.apps.len() > 1).then_some(WindowMessage::App(
+                id,
+                active,
+                Message::DetachTab,
+            )));
+        let bar = bar
+            .push(new_tab)
+            .push(horizontal_space())
+            .push(detach)
+            .push(compare)
+            .push(split)
+            .spacing(5)
+            .align_y(Center);
+        if tabs.compare
+            && let Some(right) = tabs.split.and_then(|tab| tabs.get(tab))
+        {
+            return column![container(bar).padding([5, 20]), compare_view(app, right)].into();
+        }
+        // Split view: the active tab on the left, the paired tab on the right
+        let panes = row![
+            app.view()
+                .map(move |message| WindowMessage::App(id, active, message))
+        ]
+        .push_maybe(tabs.split.and_then(|tab| {
+            tabs.get(tab).map(|app| {
+                app.view()
+                    .map(move |message| WindowMessage::App(id, tab, message))
+            })
+        }));
+        column![container(bar).padding([5, 20]), panes].into()
+    }
+    fn theme(&self, id: window::Id) -> Theme {
+        self.tabs
+            .get(&id)
+            .and_then(Tabs::active)
+            .map(SerialApp::theme)
+            .unwrap_or(Theme::CatppuccinFrappe)
+    }
+    // Every tab keeps its own subscription, so background tabs keep listening
+    fn subscription(&self) -> Subscription<WindowMessage> {
+        let apps = self.tabs.iter().flat_map(|(id, tabs)| {
+            tabs.apps.iter().map(|(tab, app)| {
+                app.subscription()
+                    .with((*id, *tab))
+                    .map(|((id, tab), message)| WindowMessage::App(id, tab, message))
+            })
+        });
+        Subscription::batch(apps.chain([
+            window::close_events().map(WindowMessage::Closed),
+            event::listen_with(Windows::event),
+        ]))
+    }
+    // Keyboard Shortcuts go to the window that has focus; window geometry is kept for workspaces
+    fn event(event: Event, status: event::Status, id: window::Id) -> Option<WindowMessage> {
+        match (event, status) {
+            (Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }), status) => {
+                SerialApp::shortcut(key, modifiers, status)
+                    .map(|message| WindowMessage::Shortcut(id, message))
+            }
+            (Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)), _) => Some(
+                WindowMessage::Shortcut(id, Message::ModifiersChanged(modifiers)),
+            ),
+            (Event::Window(window::Event::Resized(size)), _) => {
+                Some(WindowMessage::Resized(id, size))
+            }
+            (Event::Window(window::Event::Moved(position)), _) => {
+                Some(WindowMessage::Moved(id, position))
+            }
+            _ => None,
+        }
+    }
+    // Move a tab into a new window. Results of a write still in flight are addressed to the old
+    // window, so the tab has to be idle.
+    fn detach(&mut self, id: window::Id, tab: usize) -> Task<WindowMessage> {
+        let Some(tabs) = self.tabs.get_mut(&id) else {
+            return Task::none();
+        };
+        if tabs.apps.len() < 2 {
+            return Task::none();
+        }
+        if let Some(app) = tabs.get_mut(tab)
+            && (app.sending.is_some() || app.sending_break)
+        {
+            app.log_messages.push(LogEntry::new(
+                LogLevel::Warning,
+                "Wait for the current send to finish before detaching the tab",
+            ));
+            return Task::none();
+        }
+        let Some(app) = tabs.take(tab) else {
+            return Task::none();
+        };
+        let (new_id, open) = window::open(window_settings());
+        self.detached.insert(new_id, app);
+        open.map(WindowMessage::Opened)
+    }
+    // Workspace Save: every open tab's session, reported in the tab that asked
+    fn save_workspace(&mut self, from: window::Id, from_tab: usize) {
+        let sessions = self
+            .tabs
+            .iter()
+            .flat_map(|(id, tabs)| {
+                tabs.apps.iter().enumerate().map(|(i, (tab, app))| {
+                    let mut session = app.session();
+                    session.active = *tab == tabs.active;
+                    session.split = tabs.split == Some(*tab);
+                    if let Some(size) = self.sizes.get(id) {
+                        session.width = size.width;
+                        session.height = size.height;
+                    }
+                    session.position = self.positions.get(id).map(|p| (p.x, p.y));
+                    session.tab = i > 0;
+                    session
+                })
+            })
+            .collect::<Vec<_>>();
+        let Some(app) = self
+            .tabs
+            .get_mut(&from)
+            .and_then(|tabs| tabs.get_mut(from_tab))
+        else {
+            return;
+        };
+        let name = app.workspace_name.trim();
+        let saved = workspace::save(name, &sessions);
+        if saved.is_ok() {
+            app.workspace_list = combo_box::State::new(workspace::list());
+        }
+        let entry = match saved {
+            Ok(path) => LogEntry::new(
+                LogLevel::Info,
+                format!(
+                    "Workspace '{name}' ({} sessions) saved to '{}'",
+                    sessions.len(),
+                    path.display()
+                ),
+            ),
+            Err(e) => LogEntry::new(
+                LogLevel::Error,
+                format!("Failed to save workspace '{name}': {e}"),
+            ),
+        };
+        app.log_messages.push(entry);
+    }
+    // Workspace Restore: replace every open window with the saved ones
+    fn restore_workspace(&mut self, from: window::Id, from_tab: usize) -> Task<WindowMessage> {
+        let Some(app) = self
+            .tabs
+            .get_mut(&from)
+            .and_then(|tabs| tabs.get_mut(from_tab))
+        else {
+            return Task::none();
+        };
+        let name = app.workspace_name.trim().to_string();
+        let sessions = match workspace::load(&name) {
+            Ok(sessions) => sessions,
+            Err(e) => {
+                app.log_messages.push(LogEntry::new(
+                    LogLevel::Error,
+                    format!("Failed to restore workspace '{name}': {e}"),
+                ));
+                return Task::none();
+            }
+        };
+        // Drop the current sessions right away so their ports are free to be reopened
+        let closing = std::mem::take(&mut self.tabs);
+        let mut tasks = closing
+            .keys()
+            .map(|id| window::close(*id))
+            .collect::<Vec<_>>();
+        // Tabs join the window of the session before them
+        let mut windows: Vec<Vec<Session>> = Vec::new();
+        for session in sessions {
+            match windows.last_mut() {
+                Some(window) if session.tab => window.push(session),
+                _ => windows.push(vec![session]),
+            }
+        }
+        for sessions in windows {
+            let first = &sessions[0];
+            let (id, open) = window::open(window::Settings {
+                size: Size::new(first.width, first.height),
+                position: first.position.map_or(window::Position::Default, |(x, y)| {
+                    window::Position::Specific(Point::new(x, y))
+                }),
+                ..window_settings()
+            });
+            self.pending.insert(id, (name.clone(), sessions));
+            tasks.push(open.map(WindowMessage::Opened));
+        }
+        drop(closing);
+        Task::batch(tasks)
+    }
 }
 // App State
 struct SerialApp {
-    port_list: combo_box::State<String>,
+    ports: Vec<PortEntry>,
+    port_list: combo_box::State<PortEntry>,
+    bridge_list: combo_box::State<PortEntry>,
     baud_rate_list: combo_box::State<u32>,
     data_bits_list: combo_box::State<DataBits>,
-    parity_list: combo_box::State<Parity>,
+    parity_list: combo_box::State<LineParity>,
     stop_bits_list: combo_box::State<StopBits>,
+    flow_control_list: combo_box::State<FlowControl>,
+    // Label the user gave this session, e.g. "Motor controller"
+    session_name: String,
     selected_port: Option<String>,
+    endpoint_path: String,
     selected_baud_rate: Option<u32>,
+    custom_baud_rate: String,
     selected_data_bits: Option<DataBits>,
-    selected_parity: Option<Parity>,
+    selected_parity: Option<LineParity>,
     selected_stop_bits: Option<StopBits>,
+    selected_flow_control: Option<FlowControl>,
     theme_list: combo_box::State<Theme>,
     selected_theme: Option<Theme>,
     port: Option<Box<dyn serialport::SerialPort>>,
+    // Levels the app last drove the output control lines to
+    rts: bool,
+    dtr: bool,
+    break_ms: String,
+    sending_break: bool,
+    // CTS, DSR, RI and CD as last polled; None when the port can't report a line
+    modem_lines: [Option<bool>; 4],
+    reader: Option<Reader>,
+    // Far port of the bridge and the threads forwarding between it and `port`
+    bridge_port: Option<String>,
+    bridge: Option<Bridge>,
+    // Only listen on both ports, each tapping one direction of another link
+    tap: bool,
+    send_to_all: bool,
+    // Re-send the command every `repeat_ms` until stopped, e.g. to poll a sensor
+    repeat: bool,
+    repeat_ms: String,
+    // A countdown like 30s or a clock time like 14:05 for Send Later
+    schedule_input: String,
+    scheduled: Option<Scheduled>,
+    auto_reconnect: bool,
+    // Lock the port against other programs while it's open (TIOCEXCL on Unix)
+    exclusive: bool,
+    show_advanced: bool,
+    read_timeout_ms: String,
+    char_delay_ms: String,
+    line_delay_ms: String,
+    read_size: String,
+    ring_capacity: String,
+    poll_ms: String,
+    reconnect: Option<Reconnect>,
+    sending: Option<Sending>,
+    // Tags each write, so events from one that was dropped can't finish a later one
+    write_id: u64,
+    // Writes waiting for `sending` to finish, in the order they were made
+    tx_queue: VecDeque<Pending>,
+    flush_after_send: bool,
+    verify_echo: bool,
+    // Hold sends until CTS is asserted, for devices that need it without RTS/CTS flow control
+    cts_gate: bool,
+    echo_check: EchoCheck,
+    tx_queued: u32,
+    overflow_policy_list: combo_box::State<OverflowPolicy>,
+    selected_overflow_policy: Option<OverflowPolicy>,
+    framing_list: combo_box::State<Framing>,
+    selected_framing: Option<Framing>,
+    frame_delimiter: String,
+    frame_gap_ms: String,
+    frame_length: String,
+    // Received bytes regrouped into log entries by the selected framing
+    framer: Framer,
+    // Received text for captures, scripts and alarms, kept whole across reads
+    rx_decoder: StreamDecoder,
+    reported_drops: usize,
+    break_detector: Option<BreakDetector>,
     command: String,
-    log_messages: Vec<String>,
+    command_id: text_input::Id,
+    // Sent commands, oldest first, and the one recalled while stepping through them
+    history: Vec<String>,
+    history_list: combo_box::State<String>,
+    history_pos: Option<usize>,
+    // What was typed before recalling, brought back by stepping past the newest command
+    history_draft: String,
+    log_messages: Log,
+    log_id: scrollable::Id,
+    log_budget_mb: String,
+    // Entries kept in memory, or no limit while empty, and whether older ones go to disk
+    log_max_lines: String,
+    log_keep_on_disk: bool,
+    workspace_name: String,
+    workspace_list: combo_box::State<String>,
+    profiles: Vec<Profile>,
+    profile_list: combo_box::State<Profile>,
+    selected_profile: Option<Profile>,
+    import_path: String,
+    macro_path: String,
+    file_path: String,
+    file_chunk: String,
+    file_delay_ms: String,
+    // Send File a chunk at a time, waiting for `ack_pattern` after each one
+    file_ack: bool,
+    ack_pattern: String,
+    ack_timeout_ms: String,
+    ack_retries: String,
+    ack_by_line: bool,
+    upload: Option<Upload>,
+    // XMODEM sends the file at `file_path`, or receives into it
+    xmodem: Option<Transfer>,
+    xmodem_list: combo_box::State<Variant>,
+    selected_xmodem: Option<Variant>,
+    xmodem_path: PathBuf,
+    // A ZMODEM transfer the remote side started, waiting to be accepted
+    zmodem_detector: zmodem::Detector,
+    zmodem_offer: Option<Offer>,
+    // Multi-line editor: the block goes out as one write, or a line at a time from
+    // `editor_queue` with `editor_delay_ms` between the end of one write and the next line
+    show_editor: bool,
+    editor: text_editor::Content,
+    editor_by_line: bool,
+    editor_delay_ms: String,
+    editor_queue: VecDeque<String>,
+    editor_next: Option<Instant>,
+    quick_buttons: Vec<QuickButton>,
+    // The form filled in by clicking a button while editing, saved over the button of that name
+    edit_buttons: bool,
+    button_name: String,
+    button_payload: String,
+    button_hex: bool,
+    button_ending_list: combo_box::State<TxEnding>,
+    selected_button_ending: Option<TxEnding>,
+    script: Option<Script>,
+    palette: Option<Palette>,
+    setup: Option<Setup>,
+    crash_report: Option<PathBuf>,
+    palette_id: text_input::Id,
+    selected_entry: Option<usize>,
+    // Lines picked for copying by clicking their numbers, Shift+click extending from the first
+    selection: Option<(usize, usize)>,
+    modifiers: Modifiers,
+    go_to: String,
+    marker_label: String,
+    // Numbers the markers left unlabelled
+    markers_added: usize,
+    // Log Search, compiled whenever the query or its options change
+    search_id: text_input::Id,
+    search_query: String,
+    search_ignore_case: bool,
+    search_regex: bool,
+    search: Option<Search>,
+    search_error: Option<String>,
+    // Display Filter: entries are hidden, never removed
+    filter_query: String,
+    filter_ignore_case: bool,
+    filter_regex: bool,
+    filter_invert: bool,
+    filter: Option<Search>,
+    filter_error: Option<String>,
+    // What the search and filter found in each entry in earlier frames
+    match_cache: RefCell<MatchCache>,
+    // Device traffic, and the app's own messages, can each be hidden
+    show_traffic: bool,
+    show_system: bool,
+    // Scroll Pause: the log shows only the entries before this index while capture goes on
+    frozen: Option<usize>,
+    // Paused by scrolling up rather than the button, so scrolling back down resumes
+    frozen_by_scroll: bool,
+    // Where the log is scrolled, up from the bottom, and how tall it is, for lazy rendering
+    log_viewport: (f32, f32),
+    // Sends in a pane of their own, left of the rest
+    split_log: bool,
+    tx_log_id: scrollable::Id,
+    tx_log_viewport: (f32, f32),
+    // The entries taken by the last Clear, until Undo Clear puts them back
+    cleared: Option<Cleared>,
     recv_state: RecvState,
+    // Display and send modes belong to the tab, e.g. text lines for a GPS and hex for Modbus
     radio_choice: Option<RadioChoice>,
+    tx_framing_list: combo_box::State<TxFraming>,
+    selected_tx_framing: Option<TxFraming>,
+    tx_ending_list: combo_box::State<TxEnding>,
+    selected_tx_ending: Option<TxEnding>,
+    // Expand \r, \n, \t, \0, \\ and \xNN in text commands
+    tx_escapes: bool,
     rx_utf8_checked: bool,
     rx_hex_checked: bool,
     rx_binary_checked: bool,
+    rx_dump_checked: bool,
+    rx_base64_checked: bool,
+    encoding_list: combo_box::State<TextEncoding>,
+    selected_encoding: Option<TextEncoding>,
+    ansi_mode_list: combo_box::State<AnsiMode>,
+    selected_ansi_mode: Option<AnsiMode>,
+    show_deltas: bool,
+    timestamp_list: combo_box::State<TimestampFormat>,
+    selected_timestamp: Option<TimestampFormat>,
+    line_timeline: LineTimeline,
+    show_timeline: bool,
+    session_stats: SessionStats,
+    export_stats_on_close: bool,
+    monitor: Monitor,
+    show_alarms: bool,
+    alarm_channel: String,
+    alarm_min: String,
+    alarm_max: String,
+    flash_on_alarm: bool,
+    highlight_rules: Vec<HighlightRule>,
+    highlighters: Vec<(Search, Color)>,
+    show_highlights: bool,
+    highlight_pattern: String,
+    highlight_color: String,
+    modbus: Slave,
+    simulate_modbus: bool,
+    // NMEA sentences in the received text, decoded beside the log
+    nmea: nmea::Decoder,
+    show_nmea: bool,
+    // MAVLink packets in the received bytes, the latest of each shown beside the log
+    mavlink: mavlink::Dissector,
+    show_mavlink: bool,
+    // SLCAN: frames from a USB-CAN adapter in columns, and a form to send them
+    slcan: slcan::Decoder,
+    show_slcan: bool,
+    slcan_bitrate_list: combo_box::State<Bitrate>,
+    selected_slcan_bitrate: Option<Bitrate>,
+    slcan_id: String,
+    slcan_data: String,
+    slcan_extended: bool,
+    slcan_remote: bool,
+    modbus_unit: String,
+    modbus_table_list: combo_box::State<Table>,
+    selected_modbus_table: Option<Table>,
+    modbus_address: String,
+    modbus_value: String,
+    capture_enabled: bool,
+    log_file_path: String,
+    log_file: Option<LogFile>,
+    capture: Option<Capture>,
+    show_query: bool,
+    show_values: bool,
+    // Fields each received frame is decoded into, shown under it in the log
+    value_layout: String,
+    layout: Option<Layout>,
+    query_bytes: String,
+    query_text: String,
+    query_port: String,
+    query_since_list: combo_box::State<Since>,
+    selected_query_since: Option<Since>,
+    query_results: Option<Vec<capture::Match>>,
 }
 // Default App State
 impl Default for SerialApp {
@@ -66,6 +1069,78 @@ impl Default for SerialApp {
 enum RadioChoice {
     Utf8,
     Hex,
+    Base64,
+    Decimal,
+}
+// Background Write in Progress
+struct Sending {
+    id: u64,
+    kind: &'static str,
+    label: String,
+    // Byte count shown in the log once the write completes
+    reported: usize,
+    sent: usize,
+    total: usize,
+    draining: bool,
+    // Whether the payload is awaited in the echo check
+    echo: bool,
+    // The payload, logged with the send and recorded when capturing
+    data: Bytes,
+    control: Arc<Control>,
+}
+// A write dropped with the port, tab or window stops instead of going on behind its back
+impl Drop for Sending {
+    fn drop(&mut self) {
+        self.control.cancel();
+    }
+}
+// Command Encoded Now and Sent Later
+struct Scheduled {
+    at: Instant,
+    command: String,
+    kind: &'static str,
+    label: String,
+    reported: usize,
+    data: Vec<u8>,
+}
+// Write Waiting Its Turn
+struct Pending {
+    kind: &'static str,
+    label: String,
+    reported: usize,
+    data: Vec<u8>,
+    pacing: Pacing,
+}
+// Waiting for a Lost Device to Come Back
+struct Reconnect {
+    attempts: u32,
+}
+// First-Run Wizard
+struct Setup {
+    step: SetupStep,
+    // Guidance for ports the user can't open
+    problems: Vec<String>,
+    ports: usize,
+}
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum SetupStep {
+    Permissions,
+    Port,
+    Theme,
+}
+// Command Palette Search
+#[derive(Default)]
+struct Palette {
+    query: String,
+    selected: usize,
+}
+// What the Browse button picks: a file to send, files for a batch upload, or a directory to
+// receive into
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Browse {
+    File,
+    Files,
+    Folder,
 }
 // Listener State
 enum RecvState {
@@ -75,240 +1150,4738 @@ enum RecvState {
 // App Messages
 #[derive(Debug, Clone)]
 enum Message {
+    NewWindow,
+    NewTab,
+    SelectTab,
+    NextTab,
+    CloseTab,
+    SplitView,
+    CompareView,
+    DetachTab,
+    DismissCrashReport,
+    CopyCrashReportPath,
+    SetupRecheck,
+    SetupNext,
+    SetupBack,
+    SetupFinish,
+    TogglePalette,
+    ClosePalette,
+    ChangePaletteQuery(String),
+    MovePalette(isize),
+    RunCommand(usize),
+    ClearLog,
+    UndoClear,
+    ModifiersChanged(Modifiers),
+    SelectLine(usize),
+    CopySelection,
+    CopyAll,
+    ChangeWorkspaceName(String),
+    SelectProfile(Profile),
+    ChangeImportPath(String),
+    ImportProfiles,
+    ChangeMacroPath(String),
+    ChangeFilePath(String),
+    BrowseFile(Browse),
+    FileChosen(Option<String>),
+    ChangeFileChunk(String),
+    ChangeFileDelay(String),
+    SendFile,
+    CheckBoxFileAck(bool),
+    ChangeAckPattern(String),
+    ChangeAckTimeout(String),
+    ChangeAckRetries(String),
+    CheckBoxAckByLine(bool),
+    StepUpload,
+    StopUpload,
+    SelectXmodem(Variant),
+    XmodemSend,
+    XmodemReceive,
+    StepXmodem,
+    StopXmodem,
+    AcceptZmodem,
+    DeclineZmodem,
+    CheckBoxEditor(bool),
+    EditBlock(text_editor::Action),
+    CheckBoxByLine(bool),
+    ChangeLineDelay(String),
+    SendBlock,
+    SendNextLine,
+    StopBlock,
+    QuickSend(usize),
+    CheckBoxEditButtons(bool),
+    ChangeButtonName(String),
+    ChangeButtonPayload(String),
+    CheckBoxButtonHex(bool),
+    SelectButtonEnding(TxEnding),
+    SaveButton,
+    RemoveButton,
+    RunMacro,
+    StopMacro,
+    StepMacro,
+    SaveWorkspace,
+    RestoreWorkspace,
+    OpenWorkspace(String),
     ChangeCmd(String),
+    RecallCommand(isize, widget::Id),
+    SelectHistory(String),
     SelectPort(String),
+    RescanPorts,
+    PollPorts,
+    ChangeEndpointPath(String),
     SelectBaudRate(u32),
+    ChangeCustomBaudRate(String),
+    SetCustomBaudRate,
     SelectDataBits(DataBits),
-    SelectParity(Parity),
+    SelectParity(LineParity),
     SelectStopBits(StopBits),
+    SelectFlowControl(FlowControl),
     SelectTheme(Theme),
     HoverTheme(Theme),
     OpenPort,
     ClosePort,
+    RetryConnect,
+    CheckBoxReconnect(bool),
+    CheckBoxExclusive(bool),
+    CheckBoxAdvanced(bool),
+    ChangeReadTimeout(String),
+    ChangeCharDelay(String),
+    ChangeLineEndDelay(String),
+    ChangeReadSize(String),
+    ChangeRingCapacity(String),
+    ChangePollInterval(String),
     Send,
+    CheckBoxSendToAll(bool),
+    ToggleRepeat,
+    ChangeRepeatInterval(String),
+    RepeatSend,
+    ChangeSchedule(String),
+    ScheduleSend,
+    CancelScheduled,
+    ScheduledTick,
+    ChangeSessionName(String),
+    // Kind, label, reported length, and bytes of a command sent to every open port
+    Broadcast(&'static str, String, usize, Vec<u8>),
     Recv,
     ToggleListener,
+    SelectBridgePort(String),
+    ToggleBridge,
+    DrainBridge,
+    CheckBoxTap(bool),
     SelectRadio(RadioChoice),
     CheckBoxUTF8(bool),
     CheckBoxHEX(bool),
     CheckBoxBIN(bool),
+    CheckBoxDump(bool),
+    CheckBoxBase64(bool),
+    SelectEncoding(TextEncoding),
+    SelectAnsiMode(AnsiMode),
+    CheckBoxDeltas(bool),
+    SelectTimestamp(TimestampFormat),
+    Write(u64, WriteEvent),
+    TogglePauseSend,
+    CancelSend,
+    DropQueued(usize),
+    CheckBoxFlush(bool),
+    CheckBoxRts(bool),
+    CheckBoxDtr(bool),
+    ChangeBreakMs(String),
+    SendBreak,
+    BreakSent(Result<(), String>),
+    CheckBoxEcho(bool),
+    CheckBoxCtsGate(bool),
+    NextError,
+    PrevError,
+    ChangeGoTo(String),
+    ChangeMarkerLabel(String),
+    AddMarker,
+    NextMarker,
+    PrevMarker,
+    GoTo,
+    FindInLog,
+    ChangeSearch(String),
+    CheckBoxSearchIgnoreCase(bool),
+    CheckBoxSearchRegex(bool),
+    NextMatch,
+    PrevMatch,
+    ChangeFilter(String),
+    CheckBoxFilterIgnoreCase(bool),
+    CheckBoxFilterRegex(bool),
+    CheckBoxFilterInvert(bool),
+    CheckBoxShowTraffic(bool),
+    CheckBoxShowSystem(bool),
+    ToggleScrollPause,
+    LogScrolled(scrollable::Viewport),
+    TxLogScrolled(scrollable::Viewport),
+    CheckBoxSplitLog(bool),
+    ToggleFold(usize),
+    PollLines,
+    CheckBoxTimeline(bool),
+    ExportStats,
+    ExportLog,
+    ChangeLogBudget(String),
+    ChangeLogMaxLines(String),
+    CheckBoxKeepOnDisk(bool),
+    SelectOverflowPolicy(OverflowPolicy),
+    SelectFraming(Framing),
+    ChangeFrameDelimiter(String),
+    SetFrameDelimiter,
+    ChangeFrameGap(String),
+    SetFrameGap,
+    ChangeFrameLength(String),
+    SetFrameLength,
+    SelectTxFraming(TxFraming),
+    SelectTxEnding(TxEnding),
+    CheckBoxEscapes(bool),
+    PageOlder,
+    PageNewer,
+    ClosePage,
+    CheckBoxStatsOnClose(bool),
+    CheckBoxAlarms(bool),
+    ChangeAlarmChannel(String),
+    ChangeAlarmMin(String),
+    ChangeAlarmMax(String),
+    AddThreshold,
+    RemoveThreshold(usize),
+    CheckBoxHighlights(bool),
+    ChangeHighlightPattern(String),
+    ChangeHighlightColor(String),
+    AddHighlight,
+    RemoveHighlight(usize),
+    CheckBoxModbus(bool),
+    CheckBoxNmea(bool),
+    ResetNmea,
+    CheckBoxMavlink(bool),
+    ResetMavlink,
+    CheckBoxSlcan(bool),
+    SelectSlcanBitrate(Bitrate),
+    OpenSlcan,
+    CloseSlcan,
+    ChangeSlcanId(String),
+    ChangeSlcanData(String),
+    CheckBoxSlcanExtended(bool),
+    CheckBoxSlcanRemote(bool),
+    SendSlcan,
+    ClearSlcan,
+    ChangeModbusUnit(String),
+    SelectModbusTable(Table),
+    ChangeModbusAddress(String),
+    ChangeModbusValue(String),
+    SetModbusValue,
+    RemoveModbusValue(Table, u16),
+    AcknowledgeAlarms,
+    CheckBoxFlashOnAlarm(bool),
+    RequestAttention,
+    CheckBoxCapture(bool),
+    ChangeLogFilePath(String),
+    ToggleLogFile,
+    CheckLogFile,
+    CheckBoxQuery(bool),
+    CheckBoxValues(bool),
+    ChangeValueLayout(String),
+    SetValueLayout,
+    ClearValueLayout,
+    ChangeQueryBytes(String),
+    ChangeQueryText(String),
+    ChangeQueryPort(String),
+    SelectQuerySince(Since),
+    RunQuery,
 }
 // App Functions
 impl SerialApp {
     // App Title and Version
     fn title(&self) -> String {
-        format!("Serial App {VERSION}")
+        match self.session_name.trim() {
+            "" => format!("Serial App {VERSION}"),
+            name => format!("{name} - Serial App {VERSION}"),
+        }
+    }
+    // Received bytes still in memory, in order, with folded repeats expanded
+    fn received(&self) -> Vec<u8> {
+        let mut received = Vec::new();
+        for entry in self.log_messages.entries() {
+            if let Some(data) = &entry.data
+                && entry.text.is_empty()
+            {
+                for _ in 0..entry.repeats {
+                    received.extend_from_slice(data);
+                }
+            }
+        }
+        received
+    }
+    // Tab Label: the session name or else the port, marked while the port is open
+    fn tab_label(&self) -> String {
+        let label = match (self.session_name.trim(), &self.selected_port) {
+            ("", Some(port)) => port.clone(),
+            ("", None) => String::from("New Session"),
+            (name, _) => name.to_string(),
+        };
+        match self.port {
+            Some(_) => format!("{label} *"),
+            None => label,
+        }
     }
     // Initial App State
     fn new() -> Self {
-        let ports = serialport::available_ports()
-            .expect("No ports found")
+        let ports = ports::available();
+        let profiles = profiles::load();
+        let preferences = setup::load();
+        let highlight_rules = highlights::load();
+        let setup = match preferences {
+            Some(_) => None,
+            None => Some(Setup {
+                step: SetupStep::Permissions,
+                problems: setup::permission_problems(&port_names(&ports)),
+                ports: ports.len(),
+            }),
+        };
+        let preferences = preferences.unwrap_or_default();
+        let theme = Theme::ALL
             .iter()
-            .map(|port| port.port_name.clone())
-            .collect::<Vec<_>>();
-        let baud_rates = vec![9600, 19200, 38400, 57600, 115200];
+            .find(|theme| preferences.theme.as_deref() == Some(&theme.to_string()))
+            .cloned()
+            .unwrap_or(Theme::CatppuccinFrappe);
+        let baud_rates = vec![9600, 19200, 38400, 57600, 115200, 230400, 460800, 921600];
         let data_bits = vec![
             DataBits::Five,
             DataBits::Six,
             DataBits::Seven,
             DataBits::Eight,
         ];
-        let parity = vec![Parity::None, Parity::Odd, Parity::Even];
+        let parity = LineParity::ALL.to_vec();
         let stop_bits = vec![StopBits::One, StopBits::Two];
+        let flow_control = vec![
+            FlowControl::None,
+            FlowControl::Software,
+            FlowControl::Hardware,
+        ];
         let themes = Theme::ALL.to_vec();
         Self {
-            port_list: combo_box::State::new(ports),
+            ports: ports.clone(),
+            port_list: combo_box::State::new(ports.clone()),
+            bridge_list: combo_box::State::new(ports),
             baud_rate_list: combo_box::State::new(baud_rates),
             data_bits_list: combo_box::State::new(data_bits),
             parity_list: combo_box::State::new(parity),
             stop_bits_list: combo_box::State::new(stop_bits),
-            selected_port: None,
+            flow_control_list: combo_box::State::new(flow_control),
+            session_name: String::new(),
+            selected_port: preferences.port,
+            endpoint_path: String::new(),
             selected_baud_rate: Some(9600),
+            custom_baud_rate: String::new(),
             selected_data_bits: Some(DataBits::Eight),
-            selected_parity: Some(Parity::None),
+            selected_parity: Some(LineParity::None),
             selected_stop_bits: Some(StopBits::One),
+            selected_flow_control: Some(FlowControl::None),
             theme_list: combo_box::State::new(themes),
-            selected_theme: Some(Theme::CatppuccinFrappe),
+            selected_theme: Some(theme),
             port: None,
+            rts: true,
+            dtr: true,
+            break_ms: String::from("250"),
+            sending_break: false,
+            modem_lines: [None; 4],
+            reader: None,
+            bridge_port: None,
+            bridge: None,
+            tap: false,
+            send_to_all: false,
+            repeat: false,
+            repeat_ms: DEFAULT_REPEAT_MS.to_string(),
+            schedule_input: String::from("30s"),
+            scheduled: None,
+            auto_reconnect: true,
+            exclusive: true,
+            show_advanced: false,
+            read_timeout_ms: DEFAULT_READ_TIMEOUT_MS.to_string(),
+            char_delay_ms: String::from("0"),
+            line_delay_ms: String::from("0"),
+            read_size: reader::READ_SIZE.to_string(),
+            ring_capacity: reader::RING_CAPACITY.to_string(),
+            poll_ms: DEFAULT_POLL_MS.to_string(),
+            reconnect: None,
+            sending: None,
+            write_id: 0,
+            tx_queue: VecDeque::new(),
+            flush_after_send: true,
+            verify_echo: false,
+            cts_gate: false,
+            echo_check: EchoCheck::default(),
+            tx_queued: 0,
+            overflow_policy_list: combo_box::State::new(OverflowPolicy::ALL.to_vec()),
+            selected_overflow_policy: Some(OverflowPolicy::DropNewest),
+            framing_list: combo_box::State::new(Framing::ALL.to_vec()),
+            selected_framing: Some(Framing::Chunks),
+            frame_delimiter: String::from("7E"),
+            frame_gap_ms: DEFAULT_FRAME_GAP_MS.to_string(),
+            frame_length: DEFAULT_FRAME_LENGTH.to_string(),
+            framer: Framer::new(
+                Framing::Chunks,
+                FrameOptions {
+                    delimiter: Vec::new(),
+                    gap: Duration::from_millis(DEFAULT_FRAME_GAP_MS),
+                    length: DEFAULT_FRAME_LENGTH as usize,
+                    encoding: TextEncoding::Utf8,
+                },
+            ),
+            rx_decoder: StreamDecoder::new(TextEncoding::Utf8),
+            reported_drops: 0,
+            break_detector: None,
             command: String::new(),
-            log_messages: Vec::new(),
+            command_id: text_input::Id::unique(),
+            history: Vec::new(),
+            history_list: combo_box::State::new(Vec::new()),
+            history_pos: None,
+            history_draft: String::new(),
+            log_messages: Log::new(DEFAULT_LOG_BUDGET_MB * 1024 * 1024),
+            log_id: scrollable::Id::unique(),
+            log_budget_mb: DEFAULT_LOG_BUDGET_MB.to_string(),
+            log_max_lines: String::new(),
+            log_keep_on_disk: true,
+            workspace_name: String::new(),
+            workspace_list: combo_box::State::new(workspace::list()),
+            profiles: profiles.clone(),
+            profile_list: combo_box::State::new(profiles),
+            selected_profile: None,
+            import_path: String::new(),
+            macro_path: String::new(),
+            file_path: String::new(),
+            file_chunk: writer::CHUNK_SIZE.to_string(),
+            file_delay_ms: String::from("0"),
+            file_ack: false,
+            ack_pattern: String::from("OK"),
+            ack_timeout_ms: DEFAULT_ACK_TIMEOUT_MS.to_string(),
+            ack_retries: DEFAULT_ACK_RETRIES.to_string(),
+            ack_by_line: true,
+            upload: None,
+            xmodem: None,
+            xmodem_list: combo_box::State::new(Variant::ALL.to_vec()),
+            selected_xmodem: Some(Variant::XmodemCrc),
+            xmodem_path: PathBuf::new(),
+            zmodem_detector: zmodem::Detector::default(),
+            zmodem_offer: None,
+            show_editor: false,
+            editor: text_editor::Content::new(),
+            editor_by_line: true,
+            editor_delay_ms: DEFAULT_LINE_DELAY_MS.to_string(),
+            editor_queue: VecDeque::new(),
+            editor_next: None,
+            quick_buttons: buttons::load(),
+            edit_buttons: false,
+            button_name: String::new(),
+            button_payload: String::new(),
+            button_hex: false,
+            button_ending_list: combo_box::State::new(TxEnding::ALL.to_vec()),
+            selected_button_ending: Some(TxEnding::CrLf),
+            script: None,
+            palette: None,
+            setup,
+            crash_report: None,
+            palette_id: text_input::Id::unique(),
+            selected_entry: None,
+            selection: None,
+            modifiers: Modifiers::default(),
+            go_to: String::new(),
+            marker_label: String::new(),
+            markers_added: 0,
+            search_id: text_input::Id::unique(),
+            search_query: String::new(),
+            search_ignore_case: true,
+            search_regex: false,
+            search: None,
+            search_error: None,
+            filter_query: String::new(),
+            filter_ignore_case: true,
+            filter_regex: false,
+            filter_invert: false,
+            match_cache: RefCell::default(),
+            filter: None,
+            filter_error: None,
+            show_traffic: true,
+            show_system: true,
+            frozen: None,
+            frozen_by_scroll: false,
+            log_viewport: (0.0, 1000.0),
+            split_log: false,
+            tx_log_id: scrollable::Id::unique(),
+            tx_log_viewport: (0.0, 1000.0),
+            cleared: None,
             recv_state: RecvState::Idle,
             radio_choice: Some(RadioChoice::Utf8),
+            tx_framing_list: combo_box::State::new(TxFraming::ALL.to_vec()),
+            selected_tx_framing: Some(TxFraming::None),
+            tx_ending_list: combo_box::State::new(TxEnding::ALL.to_vec()),
+            selected_tx_ending: Some(TxEnding::None),
+            tx_escapes: true,
             rx_utf8_checked: false,
             rx_hex_checked: true,
             rx_binary_checked: false,
+            rx_dump_checked: false,
+            rx_base64_checked: false,
+            encoding_list: combo_box::State::new(TextEncoding::ALL.to_vec()),
+            selected_encoding: Some(TextEncoding::Utf8),
+            ansi_mode_list: combo_box::State::new(AnsiMode::ALL.to_vec()),
+            selected_ansi_mode: Some(AnsiMode::Show),
+            show_deltas: false,
+            timestamp_list: combo_box::State::new(TimestampFormat::ALL.to_vec()),
+            selected_timestamp: Some(TimestampFormat::Off),
+            line_timeline: LineTimeline::new(),
+            show_timeline: false,
+            session_stats: SessionStats::new(""),
+            export_stats_on_close: false,
+            monitor: Monitor::default(),
+            show_alarms: false,
+            alarm_channel: String::new(),
+            alarm_min: String::new(),
+            alarm_max: String::new(),
+            flash_on_alarm: false,
+            highlighters: compile_highlights(&highlight_rules),
+            highlight_rules,
+            show_highlights: false,
+            highlight_pattern: String::new(),
+            highlight_color: String::from("red"),
+            modbus: Slave::new(),
+            simulate_modbus: false,
+            nmea: nmea::Decoder::default(),
+            show_nmea: false,
+            mavlink: mavlink::Dissector::default(),
+            show_mavlink: false,
+            slcan: slcan::Decoder::default(),
+            show_slcan: false,
+            slcan_bitrate_list: combo_box::State::new(Bitrate::ALL.to_vec()),
+            selected_slcan_bitrate: Some(Bitrate::DEFAULT),
+            slcan_id: String::new(),
+            slcan_data: String::new(),
+            slcan_extended: false,
+            slcan_remote: false,
+            modbus_unit: String::from("1"),
+            modbus_table_list: combo_box::State::new(Table::ALL.to_vec()),
+            selected_modbus_table: Some(Table::HoldingRegister),
+            modbus_address: String::new(),
+            modbus_value: String::new(),
+            capture_enabled: false,
+            log_file_path: String::new(),
+            log_file: None,
+            capture: None,
+            show_query: false,
+            show_values: false,
+            value_layout: String::new(),
+            layout: None,
+            query_bytes: String::new(),
+            query_text: String::new(),
+            query_port: String::new(),
+            query_since_list: combo_box::State::new(Since::ALL.to_vec()),
+            selected_query_since: Some(Since::AllTime),
+            query_results: None,
         }
     }
     // App Logic
-    fn update(&mut self, message: Message) {
+    fn update(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::SelectPort(port) => self.selected_port = Some(port),
-            Message::SelectBaudRate(baud_rate) => self.selected_baud_rate = Some(baud_rate),
-            Message::SelectDataBits(data_bits) => self.selected_data_bits = Some(data_bits),
-            Message::SelectParity(parity) => self.selected_parity = Some(parity),
-            Message::SelectStopBits(stop_bits) => self.selected_stop_bits = Some(stop_bits),
+            Message::ChangeEndpointPath(path) => self.endpoint_path = path,
+            Message::RescanPorts => {
+                if !self.refresh_ports() {
+                    self.log_messages.push(LogEntry::new(
+                        LogLevel::Info,
+                        format!("No port changes ({} found)", self.ports.len()),
+                    ));
+                }
+            }
+            Message::PollPorts => {
+                self.refresh_ports();
+            }
+            Message::SelectBaudRate(baud_rate) => {
+                self.selected_baud_rate = Some(baud_rate);
+                self.custom_baud_rate.clear();
+                self.apply_settings();
+            }
+            Message::ChangeCustomBaudRate(baud_rate) => self.custom_baud_rate = baud_rate,
+            Message::SetCustomBaudRate => {
+                if self.set_custom_baud_rate() {
+                    self.apply_settings();
+                }
+            }
+            Message::SelectDataBits(data_bits) => {
+                self.selected_data_bits = Some(data_bits);
+                self.apply_settings();
+            }
+            Message::SelectParity(parity) => {
+                self.selected_parity = Some(parity);
+                self.apply_settings();
+            }
+            Message::SelectStopBits(stop_bits) => {
+                self.selected_stop_bits = Some(stop_bits);
+                self.apply_settings();
+            }
+            Message::SelectFlowControl(flow_control) => {
+                self.selected_flow_control = Some(flow_control);
+                self.apply_settings();
+            }
             Message::SelectRadio(choice) => self.radio_choice = Some(choice),
             Message::CheckBoxUTF8(clicked) => self.rx_utf8_checked = clicked,
             Message::CheckBoxHEX(clicked) => self.rx_hex_checked = clicked,
             Message::CheckBoxBIN(clicked) => self.rx_binary_checked = clicked,
-            Message::ChangeCmd(cmd) => self.command = cmd,
+            Message::CheckBoxDump(clicked) => self.rx_dump_checked = clicked,
+            Message::CheckBoxBase64(clicked) => self.rx_base64_checked = clicked,
+            Message::SelectEncoding(encoding) => self.set_encoding(encoding),
+            Message::SelectAnsiMode(mode) => self.selected_ansi_mode = Some(mode),
+            Message::CheckBoxDeltas(clicked) => self.show_deltas = clicked,
+            Message::SelectTimestamp(format) => self.selected_timestamp = Some(format),
+            Message::ChangeCmd(cmd) => {
+                self.command = cmd;
+                self.history_pos = None;
+            }
+            Message::RecallCommand(step, focused) => {
+                if focused == self.command_id.clone().into() {
+                    return self.recall_command(step);
+                }
+            }
+            Message::SelectHistory(cmd) => {
+                self.command = cmd;
+                self.history_pos = None;
+                return text_input::focus(self.command_id.clone());
+            }
             Message::SelectTheme(theme) => self.selected_theme = Some(theme),
             Message::HoverTheme(theme) => self.selected_theme = Some(theme),
-            Message::OpenPort => {
-                if self.selected_port.is_none() {
-                    self.log_messages.push("No port selected".to_string());
-                    return;
-                }
-                self.port = match serialport::new(
-                    self.selected_port.as_deref().unwrap(),
-                    self.selected_baud_rate.unwrap(),
-                )
-                .data_bits(self.selected_data_bits.unwrap())
-                .parity(self.selected_parity.unwrap())
-                .stop_bits(self.selected_stop_bits.unwrap())
-                .timeout(Duration::from_millis(10))
-                .open()
-                {
-                    Ok(port) => {
-                        self.log_messages.push(format!(
-                            "Successfully opened port '{}'",
-                            self.selected_port.as_deref().unwrap()
-                        ));
-                        Some(port)
+            Message::NextError => return self.jump_to_error(true),
+            Message::PrevError => return self.jump_to_error(false),
+            Message::ChangeMarkerLabel(label) => self.marker_label = label,
+            Message::AddMarker => self.add_marker(),
+            Message::NextMarker => return self.jump_to_marker(true),
+            Message::PrevMarker => return self.jump_to_marker(false),
+            Message::ChangeGoTo(target) => self.go_to = target,
+            Message::GoTo => return self.go_to(),
+            Message::FindInLog => return text_input::focus(self.search_id.clone()),
+            Message::ChangeSearch(query) => {
+                self.search_query = query;
+                self.compile_search();
+            }
+            Message::CheckBoxSearchIgnoreCase(clicked) => {
+                self.search_ignore_case = clicked;
+                self.compile_search();
+            }
+            Message::CheckBoxSearchRegex(clicked) => {
+                self.search_regex = clicked;
+                self.compile_search();
+            }
+            Message::NextMatch => return self.jump_to_match(true),
+            Message::PrevMatch => return self.jump_to_match(false),
+            Message::ChangeFilter(query) => {
+                self.filter_query = query;
+                self.compile_filter();
+            }
+            Message::CheckBoxFilterIgnoreCase(clicked) => {
+                self.filter_ignore_case = clicked;
+                self.compile_filter();
+            }
+            Message::CheckBoxFilterRegex(clicked) => {
+                self.filter_regex = clicked;
+                self.compile_filter();
+            }
+            Message::CheckBoxFilterInvert(clicked) => self.filter_invert = clicked,
+            Message::CheckBoxShowTraffic(clicked) => self.show_traffic = clicked,
+            Message::CheckBoxShowSystem(clicked) => self.show_system = clicked,
+            Message::ToggleScrollPause => {
+                self.frozen_by_scroll = false;
+                return match self.frozen {
+                    Some(_) => self.resume_scroll(),
+                    None => {
+                        self.frozen = Some(self.log_messages.len());
+                        Task::none()
                     }
-                    Err(e) => {
-                        self.log_messages.push(format!(
-                            "Failed to open port '{}': {e}",
-                            self.selected_port.as_deref().unwrap()
-                        ));
-                        None
+                };
+            }
+            Message::LogScrolled(viewport) => {
+                // The log is anchored to the bottom, so the offset is the distance up from it
+                self.log_viewport = (viewport.absolute_offset().y, viewport.bounds().height);
+                let up = self.log_viewport.0 > 1.0;
+                if up && self.frozen.is_none() {
+                    self.frozen = Some(self.log_messages.len());
+                    self.frozen_by_scroll = true;
+                } else if !up && self.frozen_by_scroll {
+                    self.frozen = None;
+                    self.frozen_by_scroll = false;
+                }
+            }
+            Message::TxLogScrolled(viewport) => {
+                self.tx_log_viewport = (viewport.absolute_offset().y, viewport.bounds().height);
+            }
+            Message::CheckBoxSplitLog(clicked) => self.split_log = clicked,
+            Message::CheckBoxTimeline(clicked) => self.show_timeline = clicked,
+            Message::CheckBoxStatsOnClose(clicked) => self.export_stats_on_close = clicked,
+            Message::CheckBoxCapture(clicked) => {
+                self.capture_enabled = clicked;
+                if !clicked {
+                    self.check_capture();
+                    self.capture = None;
+                } else if self.port.is_some() && self.capture.is_none() {
+                    self.start_capture();
+                }
+            }
+            Message::ChangeLogFilePath(path) => self.log_file_path = path,
+            Message::ToggleLogFile => self.toggle_log_file(),
+            Message::CheckLogFile => {
+                if let Some(e) = self.log_file.as_ref().and_then(LogFile::failed) {
+                    let path = self.log_file.take().unwrap().path;
+                    self.log_messages.set_sink(None);
+                    self.log_messages.push(LogEntry::new(
+                        LogLevel::Error,
+                        format!("Stopped logging to '{}': {e}", path.display()),
+                    ));
+                }
+            }
+            Message::CheckBoxQuery(clicked) => self.show_query = clicked,
+            Message::CheckBoxValues(clicked) => self.show_values = clicked,
+            Message::ChangeValueLayout(layout) => self.value_layout = layout,
+            Message::SetValueLayout => self.set_value_layout(),
+            Message::ClearValueLayout => {
+                self.value_layout.clear();
+                self.layout = None;
+            }
+            Message::CheckBoxAlarms(clicked) => self.show_alarms = clicked,
+            Message::ChangeAlarmChannel(channel) => self.alarm_channel = channel,
+            Message::ChangeAlarmMin(min) => self.alarm_min = min,
+            Message::ChangeAlarmMax(max) => self.alarm_max = max,
+            Message::AddThreshold => self.add_threshold(),
+            Message::RemoveThreshold(index) => {
+                if index < self.monitor.thresholds.len() {
+                    self.monitor.thresholds.remove(index);
+                }
+            }
+            Message::AcknowledgeAlarms => self.monitor.acknowledge(),
+            Message::CheckBoxHighlights(clicked) => self.show_highlights = clicked,
+            Message::ChangeHighlightPattern(pattern) => self.highlight_pattern = pattern,
+            Message::ChangeHighlightColor(color) => self.highlight_color = color,
+            Message::AddHighlight => self.add_highlight(),
+            Message::CheckBoxNmea(clicked) => self.show_nmea = clicked,
+            Message::ResetNmea => self.nmea.reset(),
+            Message::CheckBoxMavlink(clicked) => self.show_mavlink = clicked,
+            Message::ResetMavlink => self.mavlink.reset(),
+            Message::CheckBoxSlcan(clicked) => self.show_slcan = clicked,
+            Message::SelectSlcanBitrate(bitrate) => self.selected_slcan_bitrate = Some(bitrate),
+            Message::OpenSlcan => {
+                let bitrate = self.selected_slcan_bitrate.unwrap();
+                let label = format!("SLCAN channel opened at {bitrate}");
+                return self.send_slcan(bitrate.open_command(), label);
+            }
+            Message::CloseSlcan => {
+                let close = slcan::CLOSE_COMMAND.to_vec();
+                return self.send_slcan(close, String::from("SLCAN channel closed"));
+            }
+            Message::ChangeSlcanId(id) => self.slcan_id = id,
+            Message::ChangeSlcanData(data) => self.slcan_data = data,
+            Message::CheckBoxSlcanExtended(clicked) => self.slcan_extended = clicked,
+            Message::CheckBoxSlcanRemote(clicked) => self.slcan_remote = clicked,
+            Message::SendSlcan => {
+                let frame = slcan::compose(
+                    &self.slcan_id,
+                    &self.slcan_data,
+                    self.slcan_extended,
+                    self.slcan_remote,
+                );
+                match frame {
+                    Ok(frame) => {
+                        let label = format!(
+                            "Sent CAN frame {} [{}] {}",
+                            frame.id_text(),
+                            frame.dlc,
+                            frame.data_text()
+                        );
+                        return self.send_slcan(frame.encode(), label);
                     }
+                    Err(e) => self.log_messages.push(LogEntry::new(LogLevel::Error, e)),
                 }
             }
-            Message::ClosePort => {
-                if self.port.is_some() {
-                    self.port = None;
-                    self.log_messages.push("Port closed".to_string());
-                    self.recv_state = RecvState::Idle;
+            Message::ClearSlcan => self.slcan.clear(),
+            Message::CheckBoxModbus(clicked) => {
+                self.simulate_modbus = clicked;
+                self.modbus.reset();
+            }
+            Message::ChangeModbusUnit(unit) => {
+                // Unit 0 is the broadcast address and 248 on are reserved
+                if let Ok(id) = unit.trim().parse::<u8>()
+                    && (1..=247).contains(&id)
+                {
+                    self.modbus.unit = id;
                 }
+                self.modbus_unit = unit;
             }
-            Message::Send => match self.port {
-                Some(ref mut port) => {
-                    let cmd = &self.command;
-                    if self.radio_choice == Some(RadioChoice::Hex) {
-                        let hex_string = cmd.replace(" ", "");
-                        if !hex_string.len().is_multiple_of(2) {
-                            self.log_messages.push("Invalid hex string".to_string());
-                            return;
-                        }
-                        let hex_bytes = match hex::decode(&hex_string) {
-                            Ok(decoded_hex) => decoded_hex,
-                            Err(e) => {
-                                self.log_messages.push(format!("Error decoding hex: {e}"));
-                                return;
-                            }
-                        };
-                        match port.write_all(&hex_bytes) {
-                            Ok(_) => {}
-                            Err(e) => {
-                                self.log_messages
-                                    .push(format!("Error sending hex command: {e}"));
-                                return;
-                            }
-                        }
-                    } else if self.radio_choice == Some(RadioChoice::Utf8) {
-                        match port.write_all(cmd.as_bytes()) {
-                            Ok(_) => {}
-                            Err(e) => {
-                                self.log_messages
-                                    .push(format!("Error sending utf8 command: {e}"));
-                                return;
-                            }
-                        }
+            Message::SelectModbusTable(table) => self.selected_modbus_table = Some(table),
+            Message::ChangeModbusAddress(address) => self.modbus_address = address,
+            Message::ChangeModbusValue(value) => self.modbus_value = value,
+            Message::SetModbusValue => self.set_modbus_value(),
+            Message::RemoveModbusValue(table, address) => {
+                self.modbus.map.remove(&(table, address));
+            }
+            Message::RemoveHighlight(index) => {
+                if index < self.highlight_rules.len() {
+                    self.highlight_rules.remove(index);
+                    self.save_highlights();
+                }
+            }
+            Message::CheckBoxFlashOnAlarm(clicked) => self.flash_on_alarm = clicked,
+            Message::ChangeQueryBytes(bytes) => self.query_bytes = bytes,
+            Message::ChangeQueryText(text) => self.query_text = text,
+            Message::ChangeQueryPort(port) => self.query_port = port,
+            Message::SelectQuerySince(since) => self.selected_query_since = Some(since),
+            Message::RunQuery => self.run_query(),
+            Message::ExportStats => self.export_stats(),
+            Message::ExportLog => self.export_log(),
+            Message::ChangeWorkspaceName(name) => self.workspace_name = name,
+            Message::OpenWorkspace(name) => {
+                self.workspace_name = name;
+                return Task::done(Message::RestoreWorkspace);
+            }
+            Message::SelectProfile(profile) => {
+                self.selected_port = Some(profile.port.clone());
+                self.selected_baud_rate = Some(profile.baud_rate);
+                self.selected_data_bits = Some(profile.data_bits);
+                self.selected_parity = Some(profile.parity);
+                self.selected_stop_bits = Some(profile.stop_bits);
+                self.selected_flow_control = Some(profile.flow_control);
+                self.selected_profile = Some(profile);
+            }
+            Message::ChangeImportPath(path) => self.import_path = path,
+            Message::ImportProfiles => self.import_profiles(),
+            Message::ChangeMacroPath(path) => self.macro_path = path,
+            Message::ChangeFilePath(path) => self.file_path = path,
+            Message::BrowseFile(browse) => {
+                return Task::perform(
+                    browse_file(browse, self.file_path.clone()),
+                    Message::FileChosen,
+                );
+            }
+            Message::FileChosen(path) => {
+                if let Some(path) = path {
+                    self.file_path = path;
+                }
+            }
+            Message::ChangeFileChunk(bytes) => self.file_chunk = bytes,
+            Message::ChangeFileDelay(ms) => self.file_delay_ms = ms,
+            Message::SendFile => return self.send_file(),
+            Message::CheckBoxFileAck(clicked) => self.file_ack = clicked,
+            Message::ChangeAckPattern(pattern) => self.ack_pattern = pattern,
+            Message::ChangeAckTimeout(ms) => self.ack_timeout_ms = ms,
+            Message::ChangeAckRetries(retries) => self.ack_retries = retries,
+            Message::CheckBoxAckByLine(clicked) => self.ack_by_line = clicked,
+            Message::StepUpload => return self.step_upload(),
+            Message::SelectXmodem(variant) => self.selected_xmodem = Some(variant),
+            Message::XmodemSend => return self.start_xmodem(false),
+            Message::XmodemReceive => return self.start_xmodem(true),
+            Message::StepXmodem => return self.step_xmodem(),
+            Message::AcceptZmodem => {
+                if let Some(offer) = self.zmodem_offer.take() {
+                    self.selected_xmodem = Some(Variant::Zmodem);
+                    let task = self.start_xmodem(offer == Offer::Receive);
+                    // Stays up when the path needs fixing first
+                    if self.xmodem.is_none() {
+                        self.zmodem_offer = Some(offer);
                     }
-                    let bytes_sent = cmd.clone().into_bytes().len();
+                    return task;
+                }
+            }
+            Message::DeclineZmodem => {
+                if self.zmodem_offer.take().is_some() && self.port.is_some() {
+                    let cancel = zmodem::cancel();
+                    return self.start_write(
+                        "XMODEM",
+                        String::from("ZMODEM transfer declined"),
+                        cancel.len(),
+                        cancel,
+                        Pacing::default(),
+                    );
+                }
+            }
+            Message::StopXmodem => {
+                if let Some(transfer) = self.xmodem.take() {
+                    self.log_messages.push(LogEntry::new(
+                        LogLevel::Info,
+                        format!("{} transfer stopped", transfer.variant()),
+                    ));
+                    // Tell the other side to give up too
+                    let cancel = transfer.cancel();
+                    return self.start_write(
+                        "XMODEM",
+                        String::from("XMODEM cancel sent"),
+                        cancel.len(),
+                        cancel,
+                        Pacing::default(),
+                    );
+                }
+            }
+            Message::StopUpload => {
+                if self.upload.take().is_some() {
                     self.log_messages
-                        .push(format!("Sent {} bytes: {}", bytes_sent, cmd));
+                        .push(LogEntry::new(LogLevel::Info, "Upload stopped"));
                 }
-                None => {
-                    self.log_messages.push("Port not open".to_string());
+            }
+            Message::CheckBoxEditor(clicked) => self.show_editor = clicked,
+            Message::EditBlock(action) => self.editor.perform(action),
+            Message::CheckBoxByLine(clicked) => self.editor_by_line = clicked,
+            Message::ChangeLineDelay(ms) => self.editor_delay_ms = ms,
+            Message::SendBlock => return self.send_block(),
+            Message::SendNextLine => return self.send_next_line(),
+            Message::QuickSend(index) if self.edit_buttons => {
+                let button = &self.quick_buttons[index];
+                self.button_name = button.name.clone();
+                self.button_payload = button.payload.clone();
+                self.button_hex = button.hex;
+                self.selected_button_ending = Some(button.ending);
+            }
+            Message::QuickSend(index) => return self.quick_send(index),
+            Message::CheckBoxEditButtons(clicked) => self.edit_buttons = clicked,
+            Message::ChangeButtonName(name) => self.button_name = name,
+            Message::ChangeButtonPayload(payload) => self.button_payload = payload,
+            Message::CheckBoxButtonHex(clicked) => self.button_hex = clicked,
+            Message::SelectButtonEnding(ending) => self.selected_button_ending = Some(ending),
+            Message::SaveButton => self.save_button(),
+            Message::RemoveButton => {
+                let name = self.button_name.trim();
+                self.quick_buttons.retain(|button| button.name != name);
+                self.save_buttons();
+            }
+            Message::StopBlock => {
+                self.editor_queue.clear();
+                self.log_messages
+                    .push(LogEntry::new(LogLevel::Info, "Block stopped"));
+            }
+            Message::RunMacro => return self.run_macro(),
+            Message::StopMacro => {
+                if self.script.take().is_some() {
+                    self.log_messages
+                        .push(LogEntry::new(LogLevel::Info, "Macro stopped"));
                 }
-            },
-            Message::Recv => match self.port {
-                Some(ref mut port) => {
-                    if port.bytes_to_read().unwrap() > 0 {
-                        let mut buffer = vec![0; 16];
-                        match port.read(&mut buffer) {
-                            Ok(b) => {
-                                if self.rx_hex_checked {
-                                    let hex_string = buffer
-                                        .iter()
-                                        .map(|byte| format!("{byte:02X}"))
-                                        .collect::<Vec<String>>()
-                                        .join(" ");
-                                    self.log_messages
-                                        .push(format!("Received {b} bytes: {hex_string}"));
-                                }
-                                if self.rx_binary_checked {
-                                    let binary_string = buffer
-                                        .iter()
-                                        .map(|byte| format!("{byte:08b}"))
-                                        .collect::<Vec<String>>()
-                                        .join(" ");
-                                    self.log_messages
-                                        .push(format!("Received {b} bytes: {binary_string}"));
-                                }
-                                if self.rx_utf8_checked {
-                                    let utf8_string = String::from_utf8(buffer).unwrap();
-                                    self.log_messages
-                                        .push(format!("Received {b} bytes: {utf8_string}"));
-                                }
-                            }
-                            Err(e) => {
-                                self.log_messages.push(e.to_string());
-                            }
-                        }
-                    }
+            }
+            Message::StepMacro => return self.step_macro(),
+            Message::DismissCrashReport => self.crash_report = None,
+            Message::CopyCrashReportPath => {
+                if let Some(path) = &self.crash_report {
+                    return iced::clipboard::write(path.display().to_string());
                 }
-                None => {
-                    self.log_messages.push("Port not open".to_string());
+            }
+            Message::SetupRecheck => {
+                let ports = ports::available();
+                if let Some(setup) = self.setup.as_mut() {
+                    setup.problems = setup::permission_problems(&port_names(&ports));
+                    setup.ports = ports.len();
                 }
-            },
-            Message::ToggleListener => {
-                if self.port.is_some() {
-                    match self.recv_state {
-                        RecvState::Idle => {
-                            self.recv_state = RecvState::Listening;
-                            self.log_messages.push("Listener started".to_string());
-                        }
-                        RecvState::Listening => {
-                            self.recv_state = RecvState::Idle;
-                            self.log_messages.push("Listener stopped".to_string());
+                self.ports = ports.clone();
+                self.port_list = combo_box::State::new(ports);
+            }
+            Message::SetupNext | Message::SetupBack => {
+                if let Some(setup) = self.setup.as_mut() {
+                    let forward = matches!(message, Message::SetupNext);
+                    setup.step = match (setup.step, forward) {
+                        (SetupStep::Permissions, true) | (SetupStep::Theme, false) => {
+                            SetupStep::Port
                         }
+                        (SetupStep::Port, true) | (SetupStep::Theme, true) => SetupStep::Theme,
+                        (_, false) => SetupStep::Permissions,
+                    };
+                }
+            }
+            Message::SetupFinish => {
+                self.setup = None;
+                let preferences = setup::Preferences {
+                    port: self.selected_port.clone(),
+                    theme: Some(self.theme().to_string()),
+                };
+                if let Err(e) = setup::save(&preferences) {
+                    self.log_messages.push(LogEntry::new(
+                        LogLevel::Error,
+                        format!("Failed to save setup choices: {e}"),
+                    ));
+                }
+            }
+            Message::TogglePalette => {
+                if self.palette.take().is_none() {
+                    self.palette = Some(Palette::default());
+                    return text_input::focus(self.palette_id.clone());
+                }
+            }
+            Message::ClosePalette => self.palette = None,
+            Message::ChangePaletteQuery(query) => {
+                if let Some(palette) = self.palette.as_mut() {
+                    palette.query = query;
+                    palette.selected = 0;
+                }
+            }
+            // Without the palette, Up/Down step through the history if the command has focus
+            Message::MovePalette(step) if self.palette.is_none() => {
+                return widget::operate(focusable::find_focused())
+                    .map(move |focused| Message::RecallCommand(step, focused));
+            }
+            Message::MovePalette(step) => {
+                let count = self.palette_matches().len();
+                if let Some(palette) = self.palette.as_mut()
+                    && count > 0
+                {
+                    palette.selected = palette.selected.saturating_add_signed(step).min(count - 1);
+                }
+            }
+            // Commands go back through the runtime so window-level ones reach the window manager
+            Message::RunCommand(index) => {
+                let command = self.palette_matches().into_iter().nth(index);
+                self.palette = None;
+                if let Some((_, message)) = command {
+                    return Task::done(message);
+                }
+            }
+            Message::ClearLog => {
+                self.cleared = Some(self.log_messages.clear());
+                self.match_cache.get_mut().entries.clear();
+                self.selected_entry = None;
+                self.selection = None;
+                if self.frozen.is_some() {
+                    self.frozen = Some(0);
+                }
+            }
+            Message::ModifiersChanged(modifiers) => self.modifiers = modifiers,
+            Message::SelectLine(index) => {
+                self.selection = match self.selection {
+                    Some((anchor, _)) if self.modifiers.shift() => Some((anchor, index)),
+                    Some((anchor, end)) if anchor == index && end == index => None,
+                    _ => Some((index, index)),
+                };
+                self.selected_entry = Some(index);
+            }
+            Message::CopySelection => {
+                if let Some((anchor, end)) = self.selection {
+                    let text = self.log_text(Some(anchor.min(end)..=anchor.max(end)));
+                    return iced::clipboard::write(text);
+                }
+            }
+            Message::CopyAll => return iced::clipboard::write(self.log_text(None)),
+            Message::UndoClear => {
+                if let Some(cleared) = self.cleared.take() {
+                    let restored = cleared.len();
+                    // Entries logged since the clear move up behind the restored ones
+                    self.match_cache.get_mut().entries.clear();
+                    match self.log_messages.restore(cleared) {
+                        Ok(()) => self.frozen = self.frozen.map(|frozen| frozen + restored),
+                        Err(e) => self.log_messages.push(LogEntry::new(
+                            LogLevel::Error,
+                            format!("Failed to restore the cleared log: {e}"),
+                        )),
                     }
-                } else {
-                    self.log_messages.push("Port not open".to_string());
                 }
             }
-        }
-    }
+            // Windows, tabs, workspaces, and attention requests are handled by the window
+            // manager above the app
+            Message::NewWindow
+            | Message::NewTab
+            | Message::SelectTab
+            | Message::NextTab
+            | Message::CloseTab
+            | Message::SplitView
+            | Message::CompareView
+            | Message::DetachTab
+            | Message::SaveWorkspace
+            | Message::RestoreWorkspace
+            | Message::RequestAttention => {}
+            Message::ChangeLogBudget(budget) => {
+                if let Ok(mb) = budget.parse::<usize>()
+                    && mb > 0
+                {
+                    match mb.checked_mul(1024 * 1024) {
+                        Some(bytes) => self.log_messages.set_budget(bytes),
+                        None => self.log_messages.push(LogEntry::new(
+                            LogLevel::Warning,
+                            format!("Log budget of {mb} MB is too large, keeping the current one"),
+                        )),
+                    }
+                }
+                self.log_budget_mb = budget;
+            }
+            Message::ChangeLogMaxLines(lines) => {
+                let max = lines.trim().parse::<usize>().ok().filter(|max| *max > 0);
+                if max.is_some() || lines.trim().is_empty() {
+                    self.log_messages.set_max_entries(max);
+                }
+                self.log_max_lines = lines;
+            }
+            Message::CheckBoxKeepOnDisk(clicked) => {
+                self.log_keep_on_disk = clicked;
+                self.log_messages.set_keep_on_disk(clicked);
+            }
+            Message::SelectOverflowPolicy(policy) => {
+                self.selected_overflow_policy = Some(policy);
+                if let Some(reader) = &self.reader {
+                    reader.set_policy(policy);
+                }
+            }
+            Message::SelectFraming(framing) => self.set_framing(framing),
+            Message::ChangeFrameDelimiter(delimiter) => self.frame_delimiter = delimiter,
+            Message::SetFrameDelimiter => self.set_framing(Framing::Delimiter),
+            Message::ChangeFrameGap(ms) => self.frame_gap_ms = ms,
+            Message::SetFrameGap => self.set_framing(Framing::IdleGap),
+            Message::ChangeFrameLength(length) => self.frame_length = length,
+            Message::SetFrameLength => self.set_framing(Framing::FixedLength),
+            Message::SelectTxFraming(framing) => self.selected_tx_framing = Some(framing),
+            Message::SelectTxEnding(ending) => self.selected_tx_ending = Some(ending),
+            Message::CheckBoxEscapes(clicked) => self.tx_escapes = clicked,
+            Message::PageOlder => {
+                let start = match self.log_messages.page() {
+                    Some(page) => page.start.saturating_sub(log::PAGE_SIZE),
+                    None => self
+                        .log_messages
+                        .first_in_memory()
+                        .saturating_sub(log::PAGE_SIZE),
+                };
+                self.page_in(start);
+            }
+            Message::PageNewer => {
+                if let Some(page) = self.log_messages.page() {
+                    let start = page.start + log::PAGE_SIZE;
+                    if start >= self.log_messages.first_in_memory() {
+                        self.log_messages.close_page();
+                    } else {
+                        self.page_in(start);
+                    }
+                }
+            }
+            Message::ClosePage => self.log_messages.close_page(),
+            Message::PollLines => {
+                if let Some(ref mut port) = self.port {
+                    let now = Instant::now();
+                    self.modem_lines = [
+                        port.read_clear_to_send().ok(),
+                        port.read_data_set_ready().ok(),
+                        port.read_ring_indicator().ok(),
+                        port.read_carrier_detect().ok(),
+                    ];
+                    if let Some(cts) = self.modem_lines[0] {
+                        self.line_timeline.record(Line::Cts, cts, now);
+                    }
+                    if let Some(dsr) = self.modem_lines[1] {
+                        self.line_timeline.record(Line::Dsr, dsr, now);
+                    }
+                    self.tx_queued = port.bytes_to_write().unwrap_or(0);
+                }
+                if let Some(breaks) = self.break_detector.as_mut().and_then(BreakDetector::poll)
+                    && breaks > 0
+                {
+                    self.log_messages.push(LogEntry::new(
+                        LogLevel::Info,
+                        format!("Break condition received ({breaks})"),
+                    ));
+                }
+            }
+            Message::ToggleFold(index) => {
+                if let Some(entry) = self.log_messages.get_mut(index) {
+                    entry.expanded = !entry.expanded;
+                }
+            }
+            Message::OpenPort => {
+                if !self.set_custom_baud_rate() {
+                    return Task::none();
+                }
+                if self.selected_port.is_none() {
+                    self.log_messages
+                        .push(LogEntry::new(LogLevel::Warning, "No port selected"));
+                    return Task::none();
+                }
+                self.reconnect = None;
+                self.port = match self.connect() {
+                    Ok((port, break_detector)) => {
+                        self.break_detector = break_detector;
+                        // Opening a port asserts RTS and DTR until something drives them low
+                        let now = Instant::now();
+                        self.line_timeline = LineTimeline::new();
+                        self.line_timeline.record(Line::Rts, true, now);
+                        self.line_timeline.record(Line::Dtr, true, now);
+                        self.rts = true;
+                        self.dtr = true;
+                        self.session_stats =
+                            SessionStats::new(self.selected_port.as_deref().unwrap());
+                        self.log_messages.push(LogEntry::new(
+                            LogLevel::Info,
+                            format!(
+                                "Successfully opened port '{}'",
+                                self.selected_port.as_deref().unwrap()
+                            ),
+                        ));
+                        Some(port)
+                    }
+                    Err(e) => {
+                        self.log_messages.push(LogEntry::new(
+                            LogLevel::Error,
+                            format!(
+                                "Failed to open port '{}': {e}",
+                                self.selected_port.as_deref().unwrap()
+                            ),
+                        ));
+                        None
+                    }
+                };
+                if self.port.is_some() && self.capture_enabled {
+                    self.start_capture();
+                }
+            }
+            Message::ClosePort => {
+                if self.reconnect.take().is_some() {
+                    self.log_messages
+                        .push(LogEntry::new(LogLevel::Info, "Stopped reconnecting"));
+                }
+                if self.port.is_some() {
+                    self.release_port();
+                    self.log_messages
+                        .push(LogEntry::new(LogLevel::Info, "Port closed"));
+                    if self.export_stats_on_close {
+                        self.export_stats();
+                    }
+                }
+            }
+            // Send to all: encoded once here, then written by every tab with an open port
+            Message::Send if self.send_to_all => {
+                if let Some((kind, label, reported, data)) = self.encode_command() {
+                    self.remember_command();
+                    return Task::done(Message::Broadcast(kind, label, reported, data));
+                }
+            }
+            Message::Send => {
+                if self.port.is_none() {
+                    self.log_messages
+                        .push(LogEntry::new(LogLevel::Warning, "Port not open"));
+                    return Task::none();
+                }
+                if let Some((kind, label, reported, data)) = self.encode_command() {
+                    self.remember_command();
+                    return self.start_write(kind, label, reported, data, Pacing::default());
+                }
+            }
+            Message::Broadcast(kind, label, reported, data) => {
+                return self.start_write(kind, label, reported, data, Pacing::default());
+            }
+            Message::CheckBoxSendToAll(clicked) => self.send_to_all = clicked,
+            Message::ToggleRepeat => self.repeat = !self.repeat,
+            Message::ChangeRepeatInterval(ms) => self.repeat_ms = ms,
+            Message::RepeatSend => return self.repeat_send(),
+            Message::ChangeSchedule(input) => self.schedule_input = input,
+            Message::ScheduleSend => self.schedule_send(),
+            Message::CancelScheduled => self.cancel_scheduled(),
+            Message::ScheduledTick => return self.scheduled_tick(),
+            Message::ChangeSessionName(name) => self.session_name = name,
+            Message::Write(id, event) => return self.handle_write(id, event),
+            Message::TogglePauseSend => {
+                if let Some(sending) = self.sending.as_ref() {
+                    sending.control.set_paused(!sending.control.is_paused());
+                }
+            }
+            Message::CancelSend => self.cancel_sends(),
+            Message::DropQueued(index) => {
+                self.tx_queue.remove(index);
+            }
+            Message::CheckBoxFlush(clicked) => self.flush_after_send = clicked,
+            Message::CheckBoxCtsGate(clicked) => self.cts_gate = clicked,
+            Message::RetryConnect => return self.retry_connect(),
+            Message::CheckBoxReconnect(clicked) => self.auto_reconnect = clicked,
+            Message::CheckBoxExclusive(clicked) => self.exclusive = clicked,
+            Message::CheckBoxAdvanced(clicked) => self.show_advanced = clicked,
+            Message::ChangeReadTimeout(ms) => self.read_timeout_ms = ms,
+            Message::ChangeCharDelay(ms) => self.char_delay_ms = ms,
+            Message::ChangeLineEndDelay(ms) => self.line_delay_ms = ms,
+            Message::ChangeReadSize(bytes) => self.read_size = bytes,
+            Message::ChangeRingCapacity(chunks) => self.ring_capacity = chunks,
+            Message::ChangePollInterval(ms) => self.poll_ms = ms,
+            Message::CheckBoxRts(level) => self.drive_line(Line::Rts, level),
+            Message::CheckBoxDtr(level) => self.drive_line(Line::Dtr, level),
+            Message::ChangeBreakMs(ms) => self.break_ms = ms,
+            Message::SendBreak => return self.send_break(),
+            Message::BreakSent(result) => {
+                self.sending_break = false;
+                match result {
+                    Ok(()) => self.log_messages.push(LogEntry::new(
+                        LogLevel::Info,
+                        format!("Break sent ({} ms)", self.break_ms.trim()),
+                    )),
+                    Err(e) => self.log_messages.push(LogEntry::new(
+                        LogLevel::Error,
+                        format!("Failed to send break: {e}"),
+                    )),
+                }
+            }
+            Message::CheckBoxEcho(clicked) => {
+                self.verify_echo = clicked;
+                self.echo_check.reset();
+            }
+            Message::Recv => return self.drain_reader(),
+            Message::SelectBridgePort(name) => self.bridge_port = Some(name),
+            Message::ToggleBridge => self.toggle_bridge(),
+            Message::DrainBridge => self.drain_bridge(),
+            Message::CheckBoxTap(clicked) => self.tap = clicked,
+            // The bridge thread already reads the port, and a listener would race it for bytes
+            Message::ToggleListener
+                if self.bridge.is_some() && matches!(self.recv_state, RecvState::Idle) =>
+            {
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Warning,
+                    "Stop the bridge to use the listener",
+                ));
+            }
+            Message::ToggleListener => match (&self.port, &self.recv_state) {
+                (None, _) => {
+                    self.log_messages
+                        .push(LogEntry::new(LogLevel::Warning, "Port not open"));
+                }
+                (Some(port), RecvState::Idle) => match port.try_clone() {
+                    Ok(clone) => {
+                        self.reader = Some(Reader::spawn(
+                            clone,
+                            self.selected_overflow_policy.unwrap(),
+                            tuning(&self.read_size, reader::READ_SIZE as u64) as usize,
+                            tuning(&self.ring_capacity, reader::RING_CAPACITY as u64) as usize,
+                        ));
+                        self.reported_drops = 0;
+                        self.recv_state = RecvState::Listening;
+                        self.log_messages
+                            .push(LogEntry::new(LogLevel::Info, "Listener started"));
+                    }
+                    Err(e) => {
+                        self.log_messages.push(LogEntry::new(
+                            LogLevel::Error,
+                            format!("Failed to start listener: {e}"),
+                        ));
+                    }
+                },
+                (Some(_), RecvState::Listening) => {
+                    self.reader = None;
+                    self.flush_frame();
+                    self.recv_state = RecvState::Idle;
+                    self.log_messages
+                        .push(LogEntry::new(LogLevel::Info, "Listener stopped"));
+                }
+            },
+        }
+        Task::none()
+    }
+    // Profile Import: imported profiles replace saved ones with the same name
+    fn import_profiles(&mut self) {
+        let path = PathBuf::from(self.import_path.trim());
+        let imported = match profiles::import(&path) {
+            Ok(imported) => imported,
+            Err(e) => {
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Error,
+                    format!("Failed to import '{}': {e}", path.display()),
+                ));
+                return;
+            }
+        };
+        for profile in &imported {
+            self.profiles.retain(|saved| saved.name != profile.name);
+            self.profiles.push(profile.clone());
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Info,
+                format!(
+                    "Imported profile '{}' ({})",
+                    profile.name,
+                    profile.summary()
+                ),
+            ));
+        }
+        if imported.is_empty() {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Warning,
+                format!("No serial sessions found in '{}'", path.display()),
+            ));
+        }
+        self.profile_list = combo_box::State::new(self.profiles.clone());
+        if let Err(e) = profiles::save(&self.profiles) {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Error,
+                format!("Failed to save profiles: {e}"),
+            ));
+        }
+    }
+    // Settings Saved With a Workspace
+    fn session(&self) -> Session {
+        Session {
+            name: self.session_name.clone(),
+            port: self.selected_port.clone(),
+            baud_rate: self.selected_baud_rate.unwrap(),
+            data_bits: self.selected_data_bits.unwrap(),
+            parity: self.selected_parity.unwrap(),
+            stop_bits: self.selected_stop_bits.unwrap(),
+            flow_control: self.selected_flow_control.unwrap(),
+            theme: self.theme().to_string(),
+            overflow_policy: self.selected_overflow_policy.unwrap().to_string(),
+            framing: self.selected_framing.unwrap().to_string(),
+            frame_delimiter: self.frame_delimiter.clone(),
+            frame_gap_ms: self.frame_gap_ms.clone(),
+            frame_length: self.frame_length.clone(),
+            rx_utf8: self.rx_utf8_checked,
+            rx_hex: self.rx_hex_checked,
+            rx_binary: self.rx_binary_checked,
+            rx_dump: self.rx_dump_checked,
+            rx_base64: self.rx_base64_checked,
+            value_layout: self
+                .layout
+                .as_ref()
+                .map(|_| self.value_layout.clone())
+                .unwrap_or_default(),
+            encoding: self.selected_encoding.unwrap().to_string(),
+            ansi: self.selected_ansi_mode.unwrap().to_string(),
+            show_deltas: self.show_deltas,
+            timestamps: self.selected_timestamp.unwrap().to_string(),
+            tx_hex: self.radio_choice == Some(RadioChoice::Hex),
+            tx_base64: self.radio_choice == Some(RadioChoice::Base64),
+            tx_decimal: self.radio_choice == Some(RadioChoice::Decimal),
+            tx_ending: self.selected_tx_ending.unwrap().to_string(),
+            tx_escapes: self.tx_escapes,
+            flush_after_send: self.flush_after_send,
+            verify_echo: self.verify_echo,
+            cts_gate: self.cts_gate,
+            show_timeline: self.show_timeline,
+            split_log: self.split_log,
+            export_stats_on_close: self.export_stats_on_close,
+            log_budget_mb: self.log_budget_mb.clone(),
+            log_max_lines: self.log_max_lines.clone(),
+            log_keep_on_disk: self.log_keep_on_disk,
+            port_open: self.port.is_some(),
+            listening: matches!(self.recv_state, RecvState::Listening),
+            ..Session::default()
+        }
+    }
+    // Apply a saved session, reopening the port and listener if they were running
+    fn restore(&mut self, session: &Session) -> Task<Message> {
+        self.session_name = session.name.clone();
+        self.selected_port = session.port.clone();
+        self.selected_baud_rate = Some(session.baud_rate);
+        self.selected_data_bits = Some(session.data_bits);
+        self.selected_parity = Some(session.parity);
+        self.selected_stop_bits = Some(session.stop_bits);
+        self.selected_flow_control = Some(session.flow_control);
+        if let Some(theme) = Theme::ALL.iter().find(|t| t.to_string() == session.theme) {
+            self.selected_theme = Some(theme.clone());
+        }
+        if let Some(policy) = OverflowPolicy::ALL
+            .into_iter()
+            .find(|p| p.to_string() == session.overflow_policy)
+        {
+            self.selected_overflow_policy = Some(policy);
+        }
+        if !session.frame_delimiter.is_empty() {
+            self.frame_delimiter = session.frame_delimiter.clone();
+        }
+        if !session.frame_gap_ms.is_empty() {
+            self.frame_gap_ms = session.frame_gap_ms.clone();
+        }
+        if !session.frame_length.is_empty() {
+            self.frame_length = session.frame_length.clone();
+        }
+        if let Some(framing) = Framing::ALL
+            .into_iter()
+            .find(|f| f.to_string() == session.framing)
+        {
+            self.set_framing(framing);
+        }
+        self.rx_utf8_checked = session.rx_utf8;
+        self.rx_hex_checked = session.rx_hex;
+        self.rx_binary_checked = session.rx_binary;
+        self.rx_dump_checked = session.rx_dump;
+        self.rx_base64_checked = session.rx_base64;
+        self.value_layout = session.value_layout.clone();
+        self.set_value_layout();
+        if let Some(encoding) = TextEncoding::ALL
+            .into_iter()
+            .find(|e| e.to_string() == session.encoding)
+        {
+            self.set_encoding(encoding);
+        }
+        if let Some(mode) = AnsiMode::ALL
+            .into_iter()
+            .find(|m| m.to_string() == session.ansi)
+        {
+            self.selected_ansi_mode = Some(mode);
+        }
+        self.show_deltas = session.show_deltas;
+        if let Some(format) = TimestampFormat::ALL
+            .into_iter()
+            .find(|format| format.to_string() == session.timestamps)
+        {
+            self.selected_timestamp = Some(format);
+        }
+        self.radio_choice = Some(
+            match (session.tx_hex, session.tx_base64, session.tx_decimal) {
+                (true, _, _) => RadioChoice::Hex,
+                (false, true, _) => RadioChoice::Base64,
+                (false, false, true) => RadioChoice::Decimal,
+                (false, false, false) => RadioChoice::Utf8,
+            },
+        );
+        if let Some(ending) = TxEnding::ALL
+            .into_iter()
+            .find(|e| e.to_string() == session.tx_ending)
+        {
+            self.selected_tx_ending = Some(ending);
+        }
+        self.tx_escapes = session.tx_escapes;
+        self.flush_after_send = session.flush_after_send;
+        self.verify_echo = session.verify_echo;
+        self.cts_gate = session.cts_gate;
+        self.show_timeline = session.show_timeline;
+        self.split_log = session.split_log;
+        self.export_stats_on_close = session.export_stats_on_close;
+        let mut tasks = vec![
+            self.update(Message::ChangeLogBudget(session.log_budget_mb.clone())),
+            self.update(Message::ChangeLogMaxLines(session.log_max_lines.clone())),
+            self.update(Message::CheckBoxKeepOnDisk(session.log_keep_on_disk)),
+        ];
+        if session.port_open {
+            tasks.push(self.update(Message::OpenPort));
+            if session.listening && self.port.is_some() {
+                tasks.push(self.update(Message::ToggleListener));
+            }
+        }
+        Task::batch(tasks)
+    }
+    // Hotplug: pick up adapters plugged in or pulled since the last scan. The list is only
+    // rebuilt on a change, since that resets whatever is typed in the port combo box.
+    fn refresh_ports(&mut self) -> bool {
+        let ports = ports::available();
+        if ports == self.ports {
+            return false;
+        }
+        for port in ports.iter().filter(|port| !self.ports.contains(port)) {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Info,
+                format!("Port '{port}' connected"),
+            ));
+        }
+        for port in self.ports.iter().filter(|port| !ports.contains(port)) {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Info,
+                format!("Port '{port}' removed"),
+            ));
+        }
+        self.ports = ports.clone();
+        self.port_list = combo_box::State::new(ports.clone());
+        self.bridge_list = combo_box::State::new(ports);
+        true
+    }
+    // The selected port with its USB details when it's one of the listed ports
+    fn selected_port_entry(&self) -> Option<PortEntry> {
+        let name = self.selected_port.as_deref()?;
+        let entry = self.ports.iter().find(|port| port.name == name).cloned();
+        Some(entry.unwrap_or_else(|| PortEntry::bare(name)))
+    }
+    // Manual RTS/DTR, e.g. to reset a board or put it into its bootloader
+    fn drive_line(&mut self, line: Line, level: bool) {
+        let Some(port) = self.port.as_mut() else {
+            return;
+        };
+        let driven = match line {
+            Line::Rts => port.write_request_to_send(level),
+            _ => port.write_data_terminal_ready(level),
+        };
+        match driven {
+            Ok(()) => {
+                match line {
+                    Line::Rts => self.rts = level,
+                    _ => self.dtr = level,
+                }
+                self.line_timeline.record(line, level, Instant::now());
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Info,
+                    format!("{line} {}", if level { "asserted" } else { "cleared" }),
+                ));
+            }
+            Err(e) => self.log_messages.push(LogEntry::new(
+                LogLevel::Error,
+                format!("Failed to set {line}: {e}"),
+            )),
+        }
+    }
+    // Break: the line is held low for the chosen time, e.g. to enter a device's config console
+    fn send_break(&mut self) -> Task<Message> {
+        let Some(port) = &self.port else {
+            self.log_messages
+                .push(LogEntry::new(LogLevel::Warning, "Port not open"));
+            return Task::none();
+        };
+        let ms = match self.break_ms.trim().parse::<u64>() {
+            Ok(ms) if ms > 0 => ms,
+            _ => {
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Error,
+                    "Break duration must be a whole number of milliseconds",
+                ));
+                return Task::none();
+            }
+        };
+        match port.try_clone() {
+            Ok(clone) => {
+                self.sending_break = true;
+                Task::perform(
+                    breaks::send(clone, Duration::from_millis(ms)),
+                    Message::BreakSent,
+                )
+            }
+            Err(e) => {
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Error,
+                    format!("Failed to send break: {e}"),
+                ));
+                Task::none()
+            }
+        }
+    }
+    // Open the selected port with the current settings
+    fn connect(
+        &self,
+    ) -> serialport::Result<(Box<dyn serialport::SerialPort>, Option<BreakDetector>)> {
+        self.connect_to(self.selected_port.as_deref().unwrap())
+    }
+    // Open a port with the current settings. VM console pipes and sockets have no line
+    // settings or break counters.
+    fn connect_to(
+        &self,
+        name: &str,
+    ) -> serialport::Result<(Box<dyn serialport::SerialPort>, Option<BreakDetector>)> {
+        let timeout = Duration::from_millis(tuning(&self.read_timeout_ms, DEFAULT_READ_TIMEOUT_MS));
+        match endpoint::is_endpoint(name) {
+            true => endpoint::open(name, timeout).map(|port| (port, None)),
+            false => {
+                let builder = serialport::new(name, self.selected_baud_rate.unwrap())
+                    .data_bits(self.selected_data_bits.unwrap())
+                    .parity(self.selected_parity.unwrap().serial())
+                    .stop_bits(self.selected_stop_bits.unwrap())
+                    .flow_control(self.selected_flow_control.unwrap())
+                    .timeout(timeout);
+                breaks::open(builder, self.selected_parity.unwrap(), self.exclusive)
+                    .map(|(port, detector)| (port, Some(detector)))
+                    .map_err(|e| match e.kind() {
+                        // Opening fails with EBUSY or a lock error when the port is locked
+                        serialport::ErrorKind::NoDevice if cfg!(unix) => serialport::Error::new(
+                            e.kind(),
+                            format!("another program has the port open exclusively ({e})"),
+                        ),
+                        _ => e,
+                    })
+            }
+        }
+    }
+    // Reconfigure an open port in place; the reader and writer clones share its settings
+    fn apply_settings(&mut self) {
+        let Some(port) = self.port.as_mut() else {
+            return;
+        };
+        // Pipes and sockets have no line settings, so the choices wait for the next real port
+        if let Some(name) = port.name()
+            && endpoint::is_endpoint(&name)
+        {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Info,
+                format!("Line settings don't apply to {name}, a pipe or socket"),
+            ));
+            return;
+        }
+        let applied = port
+            .set_baud_rate(self.selected_baud_rate.unwrap())
+            .and_then(|()| port.set_data_bits(self.selected_data_bits.unwrap()))
+            .and_then(|()| port.set_parity(self.selected_parity.unwrap().serial()))
+            .and_then(|()| port.set_stop_bits(self.selected_stop_bits.unwrap()))
+            .and_then(|()| port.set_flow_control(self.selected_flow_control.unwrap()));
+        let parity = self.selected_parity.unwrap().letter();
+        let settings = format!(
+            "{} {}{parity}{}, {} flow control",
+            self.selected_baud_rate.unwrap(),
+            u8::from(self.selected_data_bits.unwrap()),
+            u8::from(self.selected_stop_bits.unwrap()),
+            self.selected_flow_control
+                .unwrap()
+                .to_string()
+                .to_lowercase()
+        );
+        match applied {
+            // Mark and space parity are set up when the port is opened
+            Ok(()) if self.selected_parity.unwrap().sticky() => {
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Warning,
+                    format!("Reopen the port to switch to {settings}"),
+                ))
+            }
+            Ok(()) => self.log_messages.push(LogEntry::new(
+                LogLevel::Info,
+                format!("Port now running at {settings}"),
+            )),
+            Err(e) => {
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Error,
+                    format!("Failed to change port settings to {settings}: {e}"),
+                ));
+                self.read_back_settings();
+            }
+        }
+    }
+    // Show what the port is actually running at after a change it refused, part way through
+    fn read_back_settings(&mut self) {
+        let Some(port) = self.port.as_ref() else {
+            return;
+        };
+        if let Ok(baud_rate) = port.baud_rate() {
+            self.selected_baud_rate = Some(baud_rate);
+        }
+        if let Ok(data_bits) = port.data_bits() {
+            self.selected_data_bits = Some(data_bits);
+        }
+        // Mark and space read back as plain parity, so they're kept unless it differs
+        if let Ok(parity) = port.parity()
+            && self.selected_parity.unwrap().serial() != parity
+        {
+            self.selected_parity = Some(LineParity::from_serial(parity));
+        }
+        if let Ok(stop_bits) = port.stop_bits() {
+            self.selected_stop_bits = Some(stop_bits);
+        }
+        if let Ok(flow_control) = port.flow_control() {
+            self.selected_flow_control = Some(flow_control);
+        }
+    }
+    // Drop the port and everything running on it
+    fn release_port(&mut self) {
+        self.flush_frame();
+        self.check_capture();
+        self.capture = None;
+        self.reader = None;
+        self.bridge = None;
+        self.sending = None;
+        self.echo_check.reset();
+        self.script = None;
+        self.repeat = false;
+        self.editor_queue.clear();
+        self.tx_queue.clear();
+        self.upload = None;
+        self.xmodem = None;
+        self.zmodem_offer = None;
+        self.zmodem_detector.reset();
+        self.port = None;
+        self.break_detector = None;
+        self.recv_state = RecvState::Idle;
+    }
+    // Bridge: forward everything between the open port and a second one with the same settings,
+    // or with Tap only listen on both
+    fn toggle_bridge(&mut self) {
+        let mode = if self.tap { "Tap" } else { "Bridge" };
+        if self.bridge.take().is_some() {
+            self.log_messages
+                .push(LogEntry::new(LogLevel::Info, format!("{mode} stopped")));
+            return;
+        }
+        let Some(port) = self.port.as_ref() else {
+            self.log_messages
+                .push(LogEntry::new(LogLevel::Warning, "Port not open"));
+            return;
+        };
+        let Some(far_name) = self
+            .bridge_port
+            .clone()
+            .filter(|name| Some(name) != self.selected_port.as_ref())
+        else {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Warning,
+                "Choose a different port to bridge to",
+            ));
+            return;
+        };
+        let read_size = tuning(&self.read_size, reader::READ_SIZE as u64) as usize;
+        let bridge = self
+            .connect_to(&far_name)
+            .and_then(|(far, _)| Bridge::spawn(port.as_ref(), far, read_size, self.tap));
+        match bridge {
+            Ok(bridge) => {
+                if self.reader.take().is_some() {
+                    self.flush_frame();
+                    self.recv_state = RecvState::Idle;
+                    self.log_messages
+                        .push(LogEntry::new(LogLevel::Info, "Listener stopped"));
+                }
+                self.bridge = Some(bridge);
+                let near = self.selected_port.as_deref().unwrap_or_default();
+                let started = match self.tap {
+                    true => format!("Tapping A → B on '{near}' and B → A on '{far_name}'"),
+                    false => format!("Bridging '{near}' and '{far_name}'"),
+                };
+                self.log_messages
+                    .push(LogEntry::new(LogLevel::Info, started));
+            }
+            Err(e) => self.log_messages.push(LogEntry::new(
+                LogLevel::Error,
+                format!("Failed to bridge to '{far_name}': {e}"),
+            )),
+        }
+    }
+    // Log forwarded or tapped traffic in the order it was read, labelled with the way it went
+    fn drain_bridge(&mut self) {
+        let Some(bridge) = self.bridge.as_ref() else {
+            return;
+        };
+        let (near, far) = match self.tap {
+            true => (String::from("A"), String::from("B")),
+            false => (
+                self.selected_port.clone().unwrap_or_default(),
+                self.bridge_port.clone().unwrap_or_default(),
+            ),
+        };
+        let mut failed = false;
+        for event in bridge.drain() {
+            match event {
+                BridgeEvent::Data { way, data, at } => {
+                    self.line_timeline.record_activity(at);
+                    let (label, direction) = match way {
+                        Way::Out => (format!("{near} → {far}"), Direction::Tx),
+                        Way::In => (format!("{far} → {near}"), Direction::Rx),
+                    };
+                    if let Some(capture) = self.capture.as_ref() {
+                        let text = self.selected_encoding.unwrap().decode(&data).into_owned();
+                        capture.record(self.session_stats.wall_time(at), direction, &data, text);
+                    }
+                    let time = self.session_stats.since_open(at);
+                    self.log_messages.push_folded(LogEntry {
+                        text: label,
+                        ..LogEntry::received(data, time)
+                    });
+                }
+                BridgeEvent::Error(e) => {
+                    self.log_messages.push(LogEntry::new(LogLevel::Error, e));
+                    failed = true;
+                }
+            }
+        }
+        if failed {
+            self.bridge = None;
+            let mode = if self.tap { "Tap" } else { "Bridge" };
+            self.log_messages
+                .push(LogEntry::new(LogLevel::Info, format!("{mode} stopped")));
+        }
+    }
+    // Reconnect: retried every second with the same settings until the device is back
+    fn retry_connect(&mut self) -> Task<Message> {
+        let Some(reconnect) = self.reconnect.as_mut() else {
+            return Task::none();
+        };
+        reconnect.attempts += 1;
+        let attempts = reconnect.attempts;
+        let name = self.selected_port.clone().unwrap_or_default();
+        match self.connect() {
+            Ok((port, break_detector)) => {
+                self.reconnect = None;
+                self.port = Some(port);
+                self.break_detector = break_detector;
+                self.rts = true;
+                self.dtr = true;
+                self.line_timeline.record(Line::Rts, true, Instant::now());
+                self.line_timeline.record(Line::Dtr, true, Instant::now());
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Info,
+                    format!("Reconnected to '{name}' after {attempts} attempts"),
+                ));
+                if self.capture_enabled {
+                    self.start_capture();
+                }
+                self.update(Message::ToggleListener)
+            }
+            Err(e) => {
+                if attempts % 10 == 0 {
+                    self.log_messages.push(LogEntry::new(
+                        LogLevel::Warning,
+                        format!("Still reconnecting to '{name}' ({attempts} attempts): {e}"),
+                    ));
+                }
+                Task::none()
+            }
+        }
+    }
+    // Use a typed baud rate in place of the presets; false when it isn't a usable rate
+    fn set_custom_baud_rate(&mut self) -> bool {
+        let typed = self.custom_baud_rate.trim();
+        if typed.is_empty() {
+            return true;
+        }
+        match typed.parse::<u32>() {
+            Ok(baud_rate) if baud_rate > 0 => {
+                self.selected_baud_rate = Some(baud_rate);
+                self.custom_baud_rate.clear();
+                true
+            }
+            _ => {
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Error,
+                    format!("Invalid baud rate '{typed}': enter a whole number of bits per second"),
+                ));
+                false
+            }
+        }
+    }
+    // Hand a payload to the writer thread, or queue it behind the one going out; `reported`
+    // is the byte count shown once it's sent. The per byte and per line delays from the
+    // advanced settings apply to every send.
+    fn start_write(
+        &mut self,
+        kind: &'static str,
+        label: String,
+        reported: usize,
+        data: Vec<u8>,
+        pacing: Pacing,
+    ) -> Task<Message> {
+        if self.sending.is_some() {
+            self.tx_queue.push_back(Pending {
+                kind,
+                label,
+                reported,
+                data,
+                pacing,
+            });
+            return Task::none();
+        }
+        let clone = match self.port.as_ref().map(|port| port.try_clone()) {
+            Some(Ok(clone)) => clone,
+            Some(Err(e)) => {
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Error,
+                    format!("Error sending {kind} command: {e}"),
+                ));
+                return Task::none();
+            }
+            None => return Task::none(),
+        };
+        let echo = self.verify_echo && self.reader.is_some();
+        if echo {
+            self.echo_check.expect(&data);
+        } else if self.verify_echo {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Warning,
+                "Echo not verified: start the listener to receive it",
+            ));
+        }
+        let data = Bytes::from(data);
+        let control = Arc::new(Control::default());
+        self.write_id += 1;
+        let id = self.write_id;
+        self.sending = Some(Sending {
+            id,
+            kind,
+            label,
+            reported,
+            sent: 0,
+            total: data.len(),
+            draining: false,
+            echo,
+            data: data.clone(),
+            control: Arc::clone(&control),
+        });
+        let pacing = Pacing {
+            char_delay: Duration::from_millis(tuning(&self.char_delay_ms, 0)),
+            line_delay: Duration::from_millis(tuning(&self.line_delay_ms, 0)),
+            cts_gate: self.cts_gate,
+            ..pacing
+        };
+        Task::run(
+            writer::write(clone, data, self.flush_after_send, pacing, control),
+            move |event| Message::Write(id, event),
+        )
+    }
+    // The next queued write, once the one before it is done
+    fn start_queued(&mut self) -> Task<Message> {
+        match self.tx_queue.pop_front() {
+            Some(next) => {
+                self.start_write(next.kind, next.label, next.reported, next.data, next.pacing)
+            }
+            None => Task::none(),
+        }
+    }
+    // Stop the write going out, and with it everything queued or about to be sent
+    fn cancel_sends(&mut self) {
+        let Some(sending) = self.sending.as_ref() else {
+            return;
+        };
+        sending.control.cancel();
+        // Bytes already handed to the OS would still go out, e.g. most of a large file
+        if let Some(port) = self.port.as_ref()
+            && let Err(e) = port.clear(serialport::ClearBuffer::Output)
+        {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Error,
+                format!("Failed to discard queued output: {e}"),
+            ));
+        }
+        let dropped = self.tx_queue.len();
+        self.tx_queue.clear();
+        self.repeat = false;
+        self.editor_queue.clear();
+        self.script = None;
+        self.upload = None;
+        self.xmodem = None;
+        if dropped > 0 {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Warning,
+                format!("Dropped {dropped} queued sends"),
+            ));
+        }
+    }
+    // One tick of repeat mode. A tick is skipped while the previous send is still going out,
+    // and repeating stops once the command can't be sent.
+    fn repeat_send(&mut self) -> Task<Message> {
+        if self.sending.is_some() {
+            return Task::none();
+        }
+        let encoded = match self.port {
+            Some(_) => self.encode_command(),
+            None => {
+                self.log_messages
+                    .push(LogEntry::new(LogLevel::Warning, "Port not open"));
+                None
+            }
+        };
+        let Some((kind, label, reported, data)) = encoded else {
+            self.repeat = false;
+            self.log_messages
+                .push(LogEntry::new(LogLevel::Warning, "Repeat stopped"));
+            return Task::none();
+        };
+        match self.send_to_all {
+            true => Task::done(Message::Broadcast(kind, label, reported, data)),
+            false => self.start_write(kind, label, reported, data, Pacing::default()),
+        }
+    }
+    // Send Later: the command is encoded when it's scheduled, so a mistake shows up right away
+    fn schedule_send(&mut self) {
+        let delay = match schedule::parse(&self.schedule_input, SystemTime::now()) {
+            Ok(delay) => delay,
+            Err(e) => {
+                self.log_messages.push(LogEntry::new(LogLevel::Error, e));
+                return;
+            }
+        };
+        let Some((kind, label, reported, data)) = self.encode_command() else {
+            return;
+        };
+        self.remember_command();
+        // Only one send waits at a time, so a new one takes the place of the last
+        self.cancel_scheduled();
+        self.log_messages.push(LogEntry::new(
+            LogLevel::Info,
+            format!(
+                "Scheduled in {}: {}",
+                schedule::countdown(delay),
+                self.command
+            ),
+        ));
+        self.scheduled = Some(Scheduled {
+            at: Instant::now() + delay,
+            command: self.command.clone(),
+            kind,
+            label,
+            reported,
+            data,
+        });
+    }
+    fn cancel_scheduled(&mut self) {
+        if let Some(scheduled) = self.scheduled.take() {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Info,
+                format!("Cancelled scheduled send: {}", scheduled.command),
+            ));
+        }
+    }
+    fn scheduled_tick(&mut self) -> Task<Message> {
+        if self
+            .scheduled
+            .as_ref()
+            .is_none_or(|scheduled| Instant::now() < scheduled.at)
+        {
+            return Task::none();
+        }
+        let scheduled = self.scheduled.take().unwrap();
+        if self.send_to_all {
+            return Task::done(Message::Broadcast(
+                scheduled.kind,
+                scheduled.label,
+                scheduled.reported,
+                scheduled.data,
+            ));
+        }
+        if self.port.is_none() {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Warning,
+                format!(
+                    "Scheduled send skipped, port not open: {}",
+                    scheduled.command
+                ),
+            ));
+            return Task::none();
+        }
+        self.start_write(
+            scheduled.kind,
+            scheduled.label,
+            scheduled.reported,
+            scheduled.data,
+            Pacing::default(),
+        )
+    }
+    // File Transfer: the whole file goes out as one write, paced in chunks
+    fn send_file(&mut self) -> Task<Message> {
+        let Some((path, data)) = self.read_send_file() else {
+            return Task::none();
+        };
+        if self.file_ack {
+            return self.start_upload(&path, &data);
+        }
+        let pacing = Pacing {
+            chunk: tuning(&self.file_chunk, writer::CHUNK_SIZE as u64) as usize,
+            delay: Duration::from_millis(self.file_delay_ms.trim().parse().unwrap_or(0)),
+            ..Pacing::default()
+        };
+        let label = format!("Sent file '{}' ({} bytes)", path.display(), data.len());
+        self.start_write("file", label, data.len(), data, pacing)
+    }
+    // The file at `file_path`, when the port is open and there's something to send
+    fn read_send_file(&mut self) -> Option<(PathBuf, Vec<u8>)> {
+        if self.port.is_none() {
+            self.log_messages
+                .push(LogEntry::new(LogLevel::Warning, "Port not open"));
+            return None;
+        }
+        let path = PathBuf::from(self.file_path.trim());
+        let data = self.read_file(&path)?;
+        Some((path, data))
+    }
+    fn read_file(&mut self, path: &Path) -> Option<Vec<u8>> {
+        let data = match std::fs::read(path) {
+            Ok(data) => data,
+            Err(e) => {
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Error,
+                    format!("Failed to read '{}': {e}", path.display()),
+                ));
+                return None;
+            }
+        };
+        if data.is_empty() {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Warning,
+                format!("'{}' is empty, nothing sent", path.display()),
+            ));
+            return None;
+        }
+        Some(data)
+    }
+    // Like a macro, the upload needs the listener running to see the ACKs
+    fn start_upload(&mut self, path: &Path, data: &[u8]) -> Task<Message> {
+        if self.upload.is_some() {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Warning,
+                "Still uploading the previous file",
+            ));
+            return Task::none();
+        }
+        let encoding = self.selected_encoding.unwrap();
+        let ack = match encoding.encode_escaped(&self.ack_pattern) {
+            Ok(ack) => ack,
+            Err(e) => {
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Error,
+                    format!("Invalid ACK pattern: {e}"),
+                ));
+                return Task::none();
+            }
+        };
+        let chunk = (!self.ack_by_line)
+            .then(|| tuning(&self.file_chunk, writer::CHUNK_SIZE as u64) as usize);
+        let upload = Upload::new(
+            data,
+            chunk,
+            ack,
+            Duration::from_millis(tuning(&self.ack_timeout_ms, DEFAULT_ACK_TIMEOUT_MS)),
+            self.ack_retries
+                .trim()
+                .parse()
+                .unwrap_or(DEFAULT_ACK_RETRIES),
+        );
+        self.log_messages.push(LogEntry::new(
+            LogLevel::Info,
+            format!(
+                "Uploading '{}' in {} chunks, waiting for '{}' after each",
+                path.display(),
+                upload.len(),
+                self.ack_pattern
+            ),
+        ));
+        self.upload = Some(upload);
+        match self.recv_state {
+            RecvState::Idle => self.update(Message::ToggleListener),
+            RecvState::Listening => Task::none(),
+        }
+    }
+    fn step_upload(&mut self) -> Task<Message> {
+        if self.sending.is_some() {
+            return Task::none();
+        }
+        let Some(upload) = self.upload.as_mut() else {
+            return Task::none();
+        };
+        match upload.step(Instant::now()) {
+            UploadStep::Send(data) => {
+                let label = format!(
+                    "Upload sent chunk {} of {} ({} bytes)",
+                    upload.done() + 1,
+                    upload.len(),
+                    data.len()
+                );
+                let reported = data.len();
+                self.start_write("upload", label, reported, data, Pacing::default())
+            }
+            UploadStep::Wait => Task::none(),
+            UploadStep::Finished => {
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Info,
+                    format!(
+                        "Upload finished: {} chunks, {} retries",
+                        upload.len(),
+                        upload.retried()
+                    ),
+                ));
+                self.upload = None;
+                Task::none()
+            }
+            UploadStep::Failed(e) => {
+                self.upload = None;
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Error,
+                    format!("Upload stopped: {e}"),
+                ));
+                Task::none()
+            }
+        }
+    }
+    // XMODEM Transfers: also driven by what the listener hears. YMODEM sends every file in
+    // `file_path`, separated by ';', and receives into the directory there.
+    fn start_xmodem(&mut self, receive: bool) -> Task<Message> {
+        if self.xmodem.is_some() || self.upload.is_some() {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Warning,
+                "Still transferring the previous file",
+            ));
+            return Task::none();
+        }
+        let variant = self.selected_xmodem.unwrap();
+        let transfer = if receive {
+            if self.port.is_none() {
+                self.log_messages
+                    .push(LogEntry::new(LogLevel::Warning, "Port not open"));
+                return Task::none();
+            }
+            self.xmodem_path = PathBuf::from(self.file_path.trim());
+            if variant.is_batch() && !self.xmodem_path.is_dir() {
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Warning,
+                    format!("Enter the directory to receive {variant} files into"),
+                ));
+                return Task::none();
+            }
+            if self.xmodem_path.as_os_str().is_empty() {
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Warning,
+                    "Enter the path of the file to receive into",
+                ));
+                return Task::none();
+            }
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Info,
+                format!(
+                    "Receiving '{}' over {variant}, start the sender now",
+                    self.xmodem_path.display()
+                ),
+            ));
+            match variant {
+                Variant::Zmodem => Transfer::ZmodemReceive(zmodem::Receiver::new()),
+                _ => Transfer::Receive(Receiver::new(variant)),
+            }
+        } else if variant.is_batch() {
+            if self.port.is_none() {
+                self.log_messages
+                    .push(LogEntry::new(LogLevel::Warning, "Port not open"));
+                return Task::none();
+            }
+            let paths = self
+                .file_path
+                .split(';')
+                .map(str::trim)
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from)
+                .collect::<Vec<_>>();
+            if paths.is_empty() {
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Warning,
+                    "Enter the files to send, separated by ';'",
+                ));
+                return Task::none();
+            }
+            let mut files = Vec::new();
+            for path in &paths {
+                let Some(data) = self.read_file(path) else {
+                    return Task::none();
+                };
+                let name = path
+                    .file_name()
+                    .map_or_else(|| path.to_string_lossy(), |name| name.to_string_lossy());
+                files.push((name.into_owned(), data));
+            }
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Info,
+                format!(
+                    "Sending {} files over {variant}, waiting for the receiver",
+                    files.len()
+                ),
+            ));
+            self.xmodem_path = PathBuf::from(self.file_path.trim());
+            match variant {
+                Variant::Zmodem => Transfer::ZmodemSend(zmodem::Sender::new(files)),
+                _ => Transfer::Send(Sender::new(files, variant)),
+            }
+        } else {
+            let Some((path, data)) = self.read_send_file() else {
+                return Task::none();
+            };
+            let name = path.to_string_lossy().into_owned();
+            let sender = Sender::new(vec![(name, data)], variant);
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Info,
+                format!(
+                    "Sending '{}' over {variant} in {} blocks, waiting for the receiver",
+                    path.display(),
+                    sender.blocks()
+                ),
+            ));
+            self.xmodem_path = path;
+            Transfer::Send(sender)
+        };
+        self.xmodem = Some(transfer);
+        match self.recv_state {
+            RecvState::Idle => self.update(Message::ToggleListener),
+            RecvState::Listening => Task::none(),
+        }
+    }
+    fn step_xmodem(&mut self) -> Task<Message> {
+        if self.sending.is_some() {
+            return Task::none();
+        }
+        let Some(transfer) = self.xmodem.as_mut() else {
+            return Task::none();
+        };
+        let step = transfer.step(Instant::now());
+        let variant = transfer.variant();
+        for note in transfer.take_notes() {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Warning,
+                format!("{variant}: {note}"),
+            ));
+        }
+        for (name, data) in transfer.take_files() {
+            // Only the name is used, so a sender can't write outside the directory
+            let name = Path::new(&name)
+                .file_name()
+                .map_or_else(|| OsString::from("received"), ToOwned::to_owned);
+            let path = self.xmodem_path.join(name);
+            self.log_messages.push(match std::fs::write(&path, &data) {
+                Ok(()) => LogEntry::new(
+                    LogLevel::Info,
+                    format!(
+                        "{variant} received '{}' ({} bytes)",
+                        path.display(),
+                        data.len()
+                    ),
+                ),
+                Err(e) => LogEntry::new(
+                    LogLevel::Error,
+                    format!("Failed to save '{}': {e}", path.display()),
+                ),
+            });
+        }
+        let path = self.xmodem_path.display();
+        match step {
+            XmodemStep::Send(data) => {
+                let label = format!(
+                    "{variant} sent {} bytes ({})",
+                    data.len(),
+                    transfer.progress()
+                );
+                let reported = data.len();
+                self.start_write("XMODEM", label, reported, data, Pacing::default())
+            }
+            XmodemStep::Wait => Task::none(),
+            XmodemStep::Finished => {
+                let entry = match transfer {
+                    Transfer::Send(_) | Transfer::ZmodemSend(_) => LogEntry::new(
+                        LogLevel::Info,
+                        format!(
+                            "{variant} send of '{path}' finished, {} retries",
+                            transfer.retried()
+                        ),
+                    ),
+                    Transfer::Receive(receiver) if !variant.is_batch() => {
+                        match std::fs::write(&self.xmodem_path, receiver.file()) {
+                            Ok(()) => LogEntry::new(
+                                LogLevel::Info,
+                                format!(
+                                    "{variant} received '{path}' ({} bytes, {} retries)",
+                                    receiver.file().len(),
+                                    transfer.retried()
+                                ),
+                            ),
+                            Err(e) => LogEntry::new(
+                                LogLevel::Error,
+                                format!("Failed to save the {variant} download to '{path}': {e}"),
+                            ),
+                        }
+                    }
+                    _ => LogEntry::new(
+                        LogLevel::Info,
+                        format!(
+                            "{variant} batch into '{path}' finished, {} retries",
+                            transfer.retried()
+                        ),
+                    ),
+                };
+                self.log_messages.push(entry);
+                self.xmodem = None;
+                Task::none()
+            }
+            XmodemStep::Failed(e) => {
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Error,
+                    format!("{variant} transfer of '{path}' failed: {e}"),
+                ));
+                self.xmodem = None;
+                Task::none()
+            }
+        }
+    }
+    // Multi-Line Editor: blank lines are skipped when sending line by line
+    fn send_block(&mut self) -> Task<Message> {
+        if self.port.is_none() {
+            self.log_messages
+                .push(LogEntry::new(LogLevel::Warning, "Port not open"));
+            return Task::none();
+        }
+        if !self.editor_queue.is_empty() {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Warning,
+                "Still sending the previous block",
+            ));
+            return Task::none();
+        }
+        let block = self.editor.text();
+        if self.editor_by_line {
+            self.editor_queue = block
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(String::from)
+                .collect();
+            if self.editor_queue.is_empty() {
+                self.log_messages
+                    .push(LogEntry::new(LogLevel::Warning, "Nothing to send"));
+                return Task::none();
+            }
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Info,
+                format!("Sending {} lines", self.editor_queue.len()),
+            ));
+            self.editor_next = Some(Instant::now());
+            return self.send_next_line();
+        }
+        // As one write the lines are joined by the line ending, or by LF when there's none
+        let joiner = match self.selected_tx_ending.unwrap() {
+            TxEnding::None => "\n",
+            ending => ending.text(),
+        };
+        let block = block
+            .trim_end_matches('\n')
+            .lines()
+            .collect::<Vec<_>>()
+            .join(joiner);
+        if block.trim().is_empty() {
+            self.log_messages
+                .push(LogEntry::new(LogLevel::Warning, "Nothing to send"));
+            return Task::none();
+        }
+        match self.encode_input(&block) {
+            Some((kind, label, reported, data)) => {
+                self.start_write(kind, label, reported, data, Pacing::default())
+            }
+            None => Task::none(),
+        }
+    }
+    // One tick while lines are queued: the delay starts once the previous line is out
+    fn send_next_line(&mut self) -> Task<Message> {
+        if self.sending.is_some() {
+            self.editor_next = None;
+            return Task::none();
+        }
+        let now = Instant::now();
+        let delay = Duration::from_millis(tuning(&self.editor_delay_ms, DEFAULT_LINE_DELAY_MS));
+        let next = *self.editor_next.get_or_insert(now + delay);
+        if now < next {
+            return Task::none();
+        }
+        let Some(line) = self.editor_queue.pop_front() else {
+            return Task::none();
+        };
+        self.editor_next = None;
+        let encoded = match self.port {
+            Some(_) => self.encode_input(&line),
+            None => {
+                self.log_messages
+                    .push(LogEntry::new(LogLevel::Warning, "Port not open"));
+                None
+            }
+        };
+        let Some((kind, label, reported, data)) = encoded else {
+            self.editor_queue.clear();
+            self.log_messages
+                .push(LogEntry::new(LogLevel::Warning, "Block stopped"));
+            return Task::none();
+        };
+        self.start_write(kind, label, reported, data, Pacing::default())
+    }
+    // Quick-Send Buttons
+    fn quick_send(&mut self, index: usize) -> Task<Message> {
+        let button = self.quick_buttons[index].clone();
+        let choice = match button.hex {
+            true => RadioChoice::Hex,
+            false => RadioChoice::Utf8,
+        };
+        if self.send_to_all {
+            return match self.encode_as(&button.payload, choice, button.ending) {
+                Some((kind, label, reported, data)) => {
+                    Task::done(Message::Broadcast(kind, label, reported, data))
+                }
+                None => Task::none(),
+            };
+        }
+        if self.port.is_none() {
+            self.log_messages
+                .push(LogEntry::new(LogLevel::Warning, "Port not open"));
+            return Task::none();
+        }
+        match self.encode_as(&button.payload, choice, button.ending) {
+            Some((kind, label, reported, data)) => {
+                self.start_write(kind, label, reported, data, Pacing::default())
+            }
+            None => Task::none(),
+        }
+    }
+    fn save_button(&mut self) {
+        let name = self.button_name.trim();
+        if name.is_empty() {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Warning,
+                "Name the button to save it",
+            ));
+            return;
+        }
+        let button = QuickButton {
+            name: name.to_string(),
+            payload: self.button_payload.clone(),
+            hex: self.button_hex,
+            ending: self.selected_button_ending.unwrap(),
+        };
+        // Saving under an existing name replaces that button in place
+        match self
+            .quick_buttons
+            .iter_mut()
+            .find(|saved| saved.name == button.name)
+        {
+            Some(saved) => *saved = button,
+            None => self.quick_buttons.push(button),
+        }
+        self.save_buttons();
+    }
+    fn save_buttons(&mut self) {
+        if let Err(e) = buttons::save(&self.quick_buttons) {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Error,
+                format!("Failed to save quick-send buttons: {e}"),
+            ));
+        }
+    }
+    // Command History
+    fn remember_command(&mut self) {
+        self.history_pos = None;
+        if self.command.is_empty() {
+            return;
+        }
+        // A repeated command moves to the end rather than appearing twice
+        self.history.retain(|cmd| *cmd != self.command);
+        self.history.push(self.command.clone());
+        if self.history.len() > HISTORY_LEN {
+            self.history.remove(0);
+        }
+        self.history_list = combo_box::State::new(self.history.iter().rev().cloned().collect());
+    }
+    // Up (-1) steps to older commands and Down (1) back towards what was being typed
+    fn recall_command(&mut self, step: isize) -> Task<Message> {
+        let newest = match self.history.len() {
+            0 => return Task::none(),
+            len => len - 1,
+        };
+        self.history_pos = match self.history_pos {
+            None if step < 0 => {
+                self.history_draft = self.command.clone();
+                Some(newest)
+            }
+            None => return Task::none(),
+            Some(pos) if step < 0 => Some(pos.saturating_sub(1)),
+            Some(pos) if pos < newest => Some(pos + 1),
+            Some(_) => None,
+        };
+        self.command = match self.history_pos {
+            Some(pos) => self.history[pos].clone(),
+            None => std::mem::take(&mut self.history_draft),
+        };
+        text_input::move_cursor_to_end(self.command_id.clone())
+    }
+    // The command as bytes for the chosen TX mode, with its log label; errors are logged
+    fn encode_command(&mut self) -> Option<(&'static str, String, usize, Vec<u8>)> {
+        let cmd = self.command.clone();
+        self.encode_input(&cmd)
+    }
+    // Like `encode_command`, for text other than the command line, e.g. the editor's
+    fn encode_input(&mut self, cmd: &str) -> Option<(&'static str, String, usize, Vec<u8>)> {
+        let choice = self.radio_choice.unwrap();
+        let ending = self.selected_tx_ending.unwrap();
+        self.encode_as(cmd, choice, ending)
+    }
+    // Like `encode_input`, with its own TX mode and line ending, e.g. a quick-send button's
+    fn encode_as(
+        &mut self,
+        cmd: &str,
+        choice: RadioChoice,
+        ending: TxEnding,
+    ) -> Option<(&'static str, String, usize, Vec<u8>)> {
+        let (kind, data, ending) = match self.input_bytes(cmd, choice, ending) {
+            Ok(encoded) => encoded,
+            Err(e) => {
+                self.log_messages.push(LogEntry::new(LogLevel::Error, e));
+                return None;
+            }
+        };
+        let bytes_sent = data.len();
+        let mut label = format!("Sent {bytes_sent} bytes: {cmd}");
+        if ending != TxEnding::None {
+            label.push_str(&format!(" + {ending}"));
+        }
+        let tx_framing = self.selected_tx_framing.unwrap();
+        let data = tx_framing.apply(data);
+        if tx_framing != TxFraming::None {
+            label.push_str(&format!(
+                " ({tx_framing}, {} bytes on the wire)",
+                data.len()
+            ));
+        }
+        Some((kind, label, bytes_sent, data))
+    }
+    // The input's bytes before TX framing, and the line ending included in them
+    fn input_bytes(
+        &self,
+        cmd: &str,
+        choice: RadioChoice,
+        ending: TxEnding,
+    ) -> Result<(&'static str, Vec<u8>, TxEnding), String> {
+        match choice {
+            RadioChoice::Base64 => format::from_base64(cmd)
+                .map(|data| ("Base64", data, TxEnding::None))
+                .map_err(|e| format!("Error decoding Base64: {e}")),
+            RadioChoice::Decimal => format::from_byte_list(cmd)
+                .map(|data| ("decimal", data, TxEnding::None))
+                .map_err(|e| format!("Error reading byte list: {e}")),
+            RadioChoice::Hex => {
+                let hex_string: String = cmd.split_whitespace().collect();
+                if !hex_string.len().is_multiple_of(2) {
+                    return Err(String::from("Invalid hex string"));
+                }
+                hex::decode(&hex_string)
+                    .map(|data| ("hex", data, TxEnding::None))
+                    .map_err(|e| format!("Error decoding hex: {e}"))
+            }
+            // Only commands typed as text get the line ending
+            RadioChoice::Utf8 => {
+                let encoding = self.selected_encoding.unwrap();
+                let text = format!("{cmd}{}", ending.text());
+                let data = match self.tx_escapes {
+                    true => encoding
+                        .encode_escaped(&text)
+                        .map_err(|e| format!("Invalid command: {e}"))?,
+                    false => encoding.encode(&text).ok_or_else(|| {
+                        format!("Command has characters that can't be sent as {encoding}")
+                    })?,
+                };
+                Ok((encoding.name(), data, ending))
+            }
+        }
+    }
+    // Log File: everything logged from now on is appended, whichever tab settings are shown
+    fn toggle_log_file(&mut self) {
+        if let Some(log_file) = self.log_file.take() {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Info,
+                format!("Stopped logging to '{}'", log_file.path.display()),
+            ));
+            self.log_messages.set_sink(None);
+            return;
+        }
+        let path = PathBuf::from(self.log_file_path.trim());
+        if path.as_os_str().is_empty() {
+            self.log_messages
+                .push(LogEntry::new(LogLevel::Warning, "Enter a log file path"));
+            return;
+        }
+        match LogFile::start(&path, self.selected_encoding.unwrap()) {
+            Ok((log_file, sink)) => {
+                self.log_file = Some(log_file);
+                self.log_messages.set_sink(Some(sink));
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Info,
+                    format!("Logging to '{}'", path.display()),
+                ));
+            }
+            Err(e) => self.log_messages.push(LogEntry::new(
+                LogLevel::Error,
+                format!("Failed to open log file '{}': {e}", path.display()),
+            )),
+        }
+    }
+    // TeraTerm Macros: `wait` needs received data, so the listener is started with the macro
+    fn run_macro(&mut self) -> Task<Message> {
+        let path = PathBuf::from(self.macro_path.trim());
+        if self.port.is_none() {
+            self.log_messages
+                .push(LogEntry::new(LogLevel::Warning, "Port not open"));
+            return Task::none();
+        }
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|source| Script::parse(&source));
+        match parsed {
+            Ok(script) => {
+                self.script = Some(script);
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Info,
+                    format!("Running macro '{}'", path.display()),
+                ));
+                match self.recv_state {
+                    RecvState::Idle => self.update(Message::ToggleListener),
+                    RecvState::Listening => Task::none(),
+                }
+            }
+            Err(e) => {
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Error,
+                    format!("Failed to load macro '{}': {e}", path.display()),
+                ));
+                Task::none()
+            }
+        }
+    }
+    // Run the macro until it blocks; sends go one at a time through the writer thread
+    fn step_macro(&mut self) -> Task<Message> {
+        if self.sending.is_some() {
+            return Task::none();
+        }
+        let Some(script) = self.script.as_mut() else {
+            return Task::none();
+        };
+        let step = script.run(Instant::now(), self.selected_encoding.unwrap());
+        for message in script.output.drain(..) {
+            self.log_messages
+                .push(LogEntry::new(LogLevel::Info, format!("Macro: {message}")));
+        }
+        match step {
+            Step::Send(data) => {
+                let label = format!("Macro sent {} bytes: {}", data.len(), format::hex(&data));
+                let reported = data.len();
+                self.start_write("macro", label, reported, data, Pacing::default())
+            }
+            Step::Blocked => Task::none(),
+            Step::Finished => {
+                self.script = None;
+                self.log_messages
+                    .push(LogEntry::new(LogLevel::Info, "Macro finished"));
+                Task::none()
+            }
+            Step::Failed(e) => {
+                self.script = None;
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Error,
+                    format!("Macro stopped at {e}"),
+                ));
+                Task::none()
+            }
+        }
+    }
+    // Background Write Progress
+    fn handle_write(&mut self, id: u64, event: WriteEvent) -> Task<Message> {
+        let Some(sending) = self.sending.as_mut().filter(|sending| sending.id == id) else {
+            return Task::none();
+        };
+        match event {
+            WriteEvent::Progress { sent, total } => {
+                sending.sent = sent;
+                sending.total = total;
+            }
+            WriteEvent::Draining => {
+                sending.sent = sending.total;
+                sending.draining = true;
+            }
+            WriteEvent::Done {
+                sent,
+                retries,
+                cts_wait,
+            } => {
+                let mut sending = self.sending.take().unwrap();
+                if cts_wait.as_millis() > 0 {
+                    self.log_messages.push(LogEntry::new(
+                        LogLevel::Info,
+                        format!("Waited {} ms for CTS", cts_wait.as_millis()),
+                    ));
+                }
+                if retries > 0 {
+                    self.log_messages.push(LogEntry::new(
+                        LogLevel::Warning,
+                        format!(
+                            "Port was slow to accept data: {sent} bytes needed {retries} retries"
+                        ),
+                    ));
+                }
+                let now = Instant::now();
+                self.line_timeline.record_activity(now);
+                self.session_stats.record_tx(sending.reported, now);
+                self.log_messages.push(LogEntry::sent(
+                    std::mem::take(&mut sending.label),
+                    sending.data.clone(),
+                ));
+                if let Some(capture) = self.capture.as_ref() {
+                    let text = self
+                        .selected_encoding
+                        .unwrap()
+                        .decode(&sending.data)
+                        .into_owned();
+                    capture.record(
+                        self.session_stats.wall_time(now),
+                        Direction::Tx,
+                        &sending.data,
+                        text,
+                    );
+                    self.check_capture();
+                }
+                if sending.echo {
+                    self.echo_check.written(now);
+                }
+            }
+            WriteEvent::Failed {
+                sent,
+                error: WriteError::Cancelled,
+            } => {
+                let sending = self.sending.take().unwrap();
+                if sending.echo {
+                    self.echo_check.abandon(sending.total, Instant::now());
+                }
+                if sent > 0 {
+                    self.line_timeline.record_activity(Instant::now());
+                    self.session_stats.record_tx(sent, Instant::now());
+                }
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Warning,
+                    format!(
+                        "Cancelled {} send ({sent} of {} bytes went out)",
+                        sending.kind, sending.total
+                    ),
+                ));
+            }
+            WriteEvent::Failed { sent, error } => {
+                let sending = self.sending.take().unwrap();
+                if sending.echo {
+                    self.echo_check.abandon(sending.total, Instant::now());
+                }
+                if !self.tx_queue.is_empty() {
+                    self.log_messages.push(LogEntry::new(
+                        LogLevel::Warning,
+                        format!("Dropped {} queued sends", self.tx_queue.len()),
+                    ));
+                    self.tx_queue.clear();
+                }
+                if self.script.take().is_some() {
+                    self.log_messages.push(LogEntry::new(
+                        LogLevel::Warning,
+                        "Macro stopped: a send failed",
+                    ));
+                }
+                if !self.editor_queue.is_empty() {
+                    self.editor_queue.clear();
+                    self.log_messages.push(LogEntry::new(
+                        LogLevel::Warning,
+                        "Block stopped: a send failed",
+                    ));
+                }
+                if self.upload.take().is_some() {
+                    self.log_messages.push(LogEntry::new(
+                        LogLevel::Warning,
+                        "Upload stopped: a send failed",
+                    ));
+                }
+                if self.xmodem.take().is_some() {
+                    self.log_messages.push(LogEntry::new(
+                        LogLevel::Warning,
+                        "XMODEM transfer stopped: a send failed",
+                    ));
+                }
+                self.session_stats.record_error();
+                if sent > 0 {
+                    self.line_timeline.record_activity(Instant::now());
+                    self.session_stats.record_tx(sent, Instant::now());
+                }
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Error,
+                    format!(
+                        "Error sending {} command: {error} ({sent} of {} bytes went out)",
+                        sending.kind, sending.total
+                    ),
+                ));
+            }
+        }
+        match self.sending {
+            Some(_) => Task::none(),
+            None => self.start_queued(),
+        }
+    }
+    // Move everything the reader thread has queued into the log
+    fn drain_reader(&mut self) -> Task<Message> {
+        let Some(reader) = self.reader.as_mut() else {
+            return Task::none();
+        };
+        let now = Instant::now();
+        let mut failed = false;
+        let mut alarmed = false;
+        let mut offered = false;
+        let mut exchanges = Vec::new();
+        while let Some(event) = reader.try_recv() {
+            match event {
+                RxEvent::Data { data, at } => {
+                    self.line_timeline.record_activity(at);
+                    self.session_stats.record_rx(data.len(), at);
+                    if let Some(fault) = self.echo_check.feed(&data, at) {
+                        self.session_stats.record_error();
+                        self.log_messages
+                            .push(LogEntry::new(LogLevel::Error, fault.to_string()));
+                    }
+                    let text = self.rx_decoder.decode(&data);
+                    if let Some(capture) = self.capture.as_ref() {
+                        capture.record(
+                            self.session_stats.wall_time(at),
+                            Direction::Rx,
+                            &data,
+                            text.clone(),
+                        );
+                    }
+                    if let Some(script) = self.script.as_mut() {
+                        script.receive(&text);
+                    }
+                    if let Some(upload) = self.upload.as_mut() {
+                        upload.receive(&data);
+                    }
+                    if let Some(transfer) = self.xmodem.as_mut() {
+                        transfer.receive(&data);
+                    } else if let Some(offer) = self.zmodem_detector.feed(&data)
+                        && self.zmodem_offer != Some(offer)
+                    {
+                        self.log_messages.push(LogEntry::new(
+                            LogLevel::Info,
+                            match offer {
+                                Offer::Receive => "The remote side started a ZMODEM send",
+                                Offer::Send => "The remote side is waiting for a ZMODEM upload",
+                            },
+                        ));
+                        self.zmodem_offer = Some(offer);
+                        offered = true;
+                    }
+                    if self.show_nmea {
+                        self.nmea.feed(&text);
+                    }
+                    if self.show_slcan {
+                        let time = self.session_stats.since_open(at);
+                        self.slcan.feed(&data, time);
+                    }
+                    if self.show_mavlink {
+                        for status in self.mavlink.feed(&data, at) {
+                            self.log_messages
+                                .push(LogEntry::new(LogLevel::Info, format!("MAVLink {status}")));
+                        }
+                    }
+                    if self.simulate_modbus {
+                        exchanges.extend(self.modbus.feed(&data, at));
+                    }
+                    if self.show_alarms || !self.monitor.thresholds.is_empty() {
+                        for alarm in self.monitor.feed(&text) {
+                            alarmed = true;
+                            self.log_messages
+                                .push(LogEntry::new(LogLevel::Error, format!("Alarm: {alarm}")));
+                        }
+                    }
+                    for (frame, at) in self.framer.feed(data, at) {
+                        let time = self.session_stats.since_open(at);
+                        self.session_stats.record_line();
+                        self.log_messages
+                            .push_folded(LogEntry::received(frame, time));
+                    }
+                }
+                RxEvent::Error(e) => {
+                    self.session_stats.record_error();
+                    self.log_messages.push(LogEntry::new(LogLevel::Error, e));
+                    failed = true;
+                }
+            }
+        }
+        if let Some((frame, at)) = self.framer.flush_idle(now) {
+            let time = self.session_stats.since_open(at);
+            self.session_stats.record_line();
+            self.log_messages
+                .push_folded(LogEntry::received(frame, time));
+        }
+        let invalid = self.framer.take_invalid();
+        if invalid > 0 {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Warning,
+                format!("{invalid} received frames didn't decode and are shown as received"),
+            ));
+        }
+        if let Some(fault) = self.echo_check.check_timeout(now) {
+            self.session_stats.record_error();
+            self.log_messages
+                .push(LogEntry::new(LogLevel::Error, fault.to_string()));
+        }
+        let (chunks, bytes) = reader.dropped();
+        let mut replies = Vec::new();
+        for exchange in exchanges {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Info,
+                format!("Modbus slave: {}", exchange.summary),
+            ));
+            if let Some(response) = exchange.response {
+                let label = format!(
+                    "Modbus slave sent {} bytes: {}",
+                    response.len(),
+                    format::hex(&response)
+                );
+                let reported = response.len();
+                replies.push(self.start_write(
+                    "Modbus reply",
+                    label,
+                    reported,
+                    response,
+                    Pacing::default(),
+                ));
+            }
+        }
+        if chunks > self.reported_drops {
+            self.reported_drops = chunks;
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Warning,
+                format!(
+                    "Receive buffer overflowed: {chunks} chunks ({bytes} bytes) dropped so far"
+                ),
+            ));
+        }
+        // The reader thread exits after a read error, so the listener can't continue. A read
+        // error usually means the device went away, so the port is reopened once it's back.
+        if failed && self.auto_reconnect {
+            self.release_port();
+            self.reconnect = Some(Reconnect { attempts: 0 });
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Warning,
+                format!(
+                    "Lost connection to '{}', reconnecting",
+                    self.selected_port.as_deref().unwrap_or_default()
+                ),
+            ));
+        } else if failed {
+            self.reader = None;
+            self.flush_frame();
+            self.recv_state = RecvState::Idle;
+            self.log_messages
+                .push(LogEntry::new(LogLevel::Info, "Listener stopped"));
+        }
+        self.check_capture();
+        if (alarmed && self.flash_on_alarm) || offered {
+            replies.push(Task::done(Message::RequestAttention));
+        }
+        Task::batch(replies)
+    }
+    // Modbus Slave Simulator: put a value in the register map, or change the one there
+    fn set_modbus_value(&mut self) {
+        let table = self.selected_modbus_table.unwrap();
+        let Some(address) = modbus::parse_number(&self.modbus_address) else {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Error,
+                format!(
+                    "Invalid Modbus address '{}': enter 0 to 65535, or 0x hex",
+                    self.modbus_address.trim()
+                ),
+            ));
+            return;
+        };
+        let value = match modbus::parse_number(&self.modbus_value) {
+            Some(value) if !table.is_bit() || value <= 1 => value,
+            _ => {
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Error,
+                    match table.is_bit() {
+                        true => format!(
+                            "Invalid {table} value '{}': enter 0 or 1",
+                            self.modbus_value.trim()
+                        ),
+                        false => format!(
+                            "Invalid {table} value '{}': enter 0 to 65535, or 0x hex",
+                            self.modbus_value.trim()
+                        ),
+                    },
+                ));
+                return;
+            }
+        };
+        self.modbus.map.insert((table, address), value);
+        // Ready for the next address
+        self.modbus_address = address.wrapping_add(1).to_string();
+    }
+    fn modbus_view(&self) -> Element<'_, Message> {
+        let inputs = row![
+            text("Unit"),
+            text_input("1", &self.modbus_unit)
+                .on_input(Message::ChangeModbusUnit)
+                .width(50),
+            combo_box(
+                &self.modbus_table_list,
+                "Table",
+                self.selected_modbus_table.as_ref(),
+                Message::SelectModbusTable,
+            )
+            .width(170),
+            text_input("Address", &self.modbus_address)
+                .on_input(Message::ChangeModbusAddress)
+                .on_submit(Message::SetModbusValue)
+                .width(100),
+            text_input("Value", &self.modbus_value)
+                .on_input(Message::ChangeModbusValue)
+                .on_submit(Message::SetModbusValue)
+                .width(100),
+            button("Set").padding(5).on_press(Message::SetModbusValue),
+        ]
+        .spacing(10)
+        .align_y(Center);
+        let values = self.modbus.map.iter().map(|(&(table, address), value)| {
+            row![
+                text(format!("{table} {address}")).width(Fill),
+                text(value.to_string()).width(100),
+                button("Remove")
+                    .padding(2)
+                    .on_press(Message::RemoveModbusValue(table, address)),
+            ]
+            .spacing(10)
+            .align_y(Center)
+            .into()
+        });
+        let status = match self.modbus.map.is_empty() {
+            true => text("Every address gets an exception until it's given a value here"),
+            false => text(format!(
+                "Answering unit {} from {} addresses",
+                self.modbus.unit,
+                self.modbus.map.len()
+            )),
+        };
+        container(
+            column![
+                inputs,
+                status,
+                container(scrollable(column(values).spacing(5))).max_height(200)
+            ]
+            .spacing(10),
+        )
+        .padding(10)
+        .style(container::rounded_box)
+        .into()
+    }
+    // SLCAN: adapter commands go out like any other write
+    fn send_slcan(&mut self, command: Vec<u8>, label: String) -> Task<Message> {
+        if self.port.is_none() {
+            self.log_messages
+                .push(LogEntry::new(LogLevel::Warning, "Port not open"));
+            return Task::none();
+        }
+        let reported = command.len();
+        self.start_write("SLCAN", label, reported, command, Pacing::default())
+    }
+    fn slcan_view(&self) -> Element<'_, Message> {
+        let channel = row![
+            combo_box(
+                &self.slcan_bitrate_list,
+                "Bitrate",
+                self.selected_slcan_bitrate.as_ref(),
+                Message::SelectSlcanBitrate,
+            )
+            .width(130),
+            button("Open Channel")
+                .padding(5)
+                .on_press(Message::OpenSlcan),
+            button("Close Channel")
+                .padding(5)
+                .on_press(Message::CloseSlcan),
+            text(format!(
+                "{} received, {} sent, {} refused",
+                self.slcan.received, self.slcan.sent, self.slcan.errors
+            ))
+            .width(Fill),
+            button("Clear").padding(5).on_press(Message::ClearSlcan),
+        ]
+        .spacing(10)
+        .align_y(Center);
+        let data_hint = match self.slcan_remote {
+            true => "DLC",
+            false => "Data (hex)",
+        };
+        let compose = row![
+            text_input("ID (hex)", &self.slcan_id)
+                .on_input(Message::ChangeSlcanId)
+                .on_submit(Message::SendSlcan)
+                .width(110),
+            text_input(data_hint, &self.slcan_data)
+                .on_input(Message::ChangeSlcanData)
+                .on_submit(Message::SendSlcan)
+                .width(260),
+            checkbox("Extended", self.slcan_extended).on_toggle(Message::CheckBoxSlcanExtended),
+            checkbox("Remote", self.slcan_remote).on_toggle(Message::CheckBoxSlcanRemote),
+            button("Send Frame").padding(5).on_press(Message::SendSlcan),
+        ]
+        .spacing(10)
+        .align_y(Center);
+        let columns = |time: String, id: String, dlc: String, data: String| {
+            row![
+                text(time).width(100),
+                text(id).width(100),
+                text(dlc).width(40),
+                text(data).width(Fill),
+            ]
+            .spacing(10)
+        };
+        let header = columns(
+            String::from("Time"),
+            String::from("ID"),
+            String::from("DLC"),
+            String::from("Data"),
+        );
+        // Newest first
+        let frames = self.slcan.frames.iter().rev().map(|(time, frame)| {
+            let time = match frame.timestamp {
+                Some(ms) => format!("{:.3} ({ms})", time.as_secs_f64()),
+                None => format!("{:.3}", time.as_secs_f64()),
+            };
+            columns(
+                time,
+                frame.id_text(),
+                frame.dlc.to_string(),
+                frame.data_text(),
+            )
+            .into()
+        });
+        container(
+            column![
+                channel,
+                compose,
+                header,
+                container(scrollable(column(frames).spacing(2))).max_height(200)
+            ]
+            .spacing(10),
+        )
+        .padding(10)
+        .style(container::rounded_box)
+        .into()
+    }
+    // Highlight Rules
+    fn add_highlight(&mut self) {
+        let pattern = self.highlight_pattern.clone();
+        if pattern.is_empty() {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Warning,
+                "Enter a pattern to highlight",
+            ));
+            return;
+        }
+        if let Err(e) = Search::new(&pattern, true, false) {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Error,
+                format!("Bad highlight pattern '{pattern}': {e}"),
+            ));
+            return;
+        }
+        let color = self.highlight_color.trim().to_string();
+        if highlights::parse_color(&color).is_none() {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Error,
+                format!(
+                    "Unknown color '{color}', use #rrggbb or one of {}",
+                    highlights::color_names().collect::<Vec<_>>().join(", ")
+                ),
+            ));
+            return;
+        }
+        // Adding a pattern again changes its color
+        let rule = HighlightRule { pattern, color };
+        match self
+            .highlight_rules
+            .iter_mut()
+            .find(|saved| saved.pattern == rule.pattern)
+        {
+            Some(saved) => *saved = rule,
+            None => self.highlight_rules.push(rule),
+        }
+        self.highlight_pattern.clear();
+        self.save_highlights();
+    }
+    fn save_highlights(&mut self) {
+        self.highlighters = compile_highlights(&self.highlight_rules);
+        if let Err(e) = highlights::save(&self.highlight_rules) {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Error,
+                format!("Failed to save highlight rules: {e}"),
+            ));
+        }
+    }
+    // The color of the first rule a received line matches
+    fn highlight(&self, entry: &LogEntry, line: &str) -> Option<Color> {
+        if entry.source() != Source::Rx {
+            return None;
+        }
+        self.highlighters
+            .iter()
+            .find(|(search, _)| search.is_match(line))
+            .map(|(_, color)| *color)
+    }
+    fn highlights_view(&self) -> Element<'_, Message> {
+        let inputs = row![
+            text_input("Pattern, e.g. ERROR or OK\\r\\n", &self.highlight_pattern)
+                .on_input(Message::ChangeHighlightPattern)
+                .on_submit(Message::AddHighlight),
+            text_input("Color", &self.highlight_color)
+                .on_input(Message::ChangeHighlightColor)
+                .on_submit(Message::AddHighlight)
+                .width(120),
+            button("Add").padding(5).on_press(Message::AddHighlight),
+        ]
+        .spacing(10)
+        .align_y(Center);
+        let rules = self.highlight_rules.iter().enumerate().map(|(i, rule)| {
+            let color =
+                highlights::parse_color(&rule.color).map(|[r, g, b]| Color::from_rgb8(r, g, b));
+            row![
+                text(rule.pattern.as_str()).color_maybe(color).width(Fill),
+                text(rule.color.as_str()),
+                button("Remove")
+                    .padding(2)
+                    .on_press(Message::RemoveHighlight(i)),
+            ]
+            .spacing(10)
+            .align_y(Center)
+            .into()
+        });
+        container(column![inputs].extend(rules).spacing(10))
+            .padding(10)
+            .style(container::rounded_box)
+            .into()
+    }
+    // Threshold Alarms
+    fn add_threshold(&mut self) {
+        let channel = self.alarm_channel.trim().to_string();
+        let bound = |input: &str| match input.trim() {
+            "" => Ok(None),
+            value => value.parse::<f64>().map(Some),
+        };
+        match (bound(&self.alarm_min), bound(&self.alarm_max)) {
+            _ if channel.is_empty() => self.log_messages.push(LogEntry::new(
+                LogLevel::Warning,
+                "Enter the channel to watch (a name, or #1 for the first number on a line)",
+            )),
+            (Ok(None), Ok(None)) => self.log_messages.push(LogEntry::new(
+                LogLevel::Warning,
+                "Enter a minimum, a maximum, or both",
+            )),
+            (Ok(min), Ok(max)) => {
+                self.monitor
+                    .thresholds
+                    .push(Threshold { channel, min, max });
+                self.alarm_min.clear();
+                self.alarm_max.clear();
+            }
+            _ => self.log_messages.push(LogEntry::new(
+                LogLevel::Error,
+                "Threshold limits must be numbers",
+            )),
+        }
+    }
+    // NMEA Decoder: fields a sentence left empty show as a dash
+    fn nmea_view(&self) -> Element<'_, Message> {
+        let nmea = &self.nmea;
+        let fix = &nmea.fix;
+        let field = |label: &'static str, value: Option<String>| {
+            row![
+                text(label).width(90),
+                text(value.unwrap_or_else(|| String::from("-")))
+            ]
+            .spacing(10)
+        };
+        let coordinate = |value: Option<f64>, positive: char, negative: char| {
+            value.map(|value| {
+                let side = if value < 0.0 { negative } else { positive };
+                format!("{:.6}° {side}", value.abs())
+            })
+        };
+        let status = match (fix.quality_name(), fix.mode, fix.valid) {
+            (Some(quality), Some(mode @ 2..=3), _) => Some(format!("{quality}, {mode}D")),
+            (Some(quality), _, _) => Some(quality.to_string()),
+            (None, _, Some(valid)) => Some(String::from(if valid { "valid" } else { "no fix" })),
+            (None, _, None) => None,
+        };
+        let time = match (&fix.date, &fix.time) {
+            (Some(date), Some(time)) => Some(format!("{date} {time} UTC")),
+            (None, Some(time)) => Some(format!("{time} UTC")),
+            _ => None,
+        };
+        let in_view = nmea.satellites.len();
+        let satellites = match (fix.used, in_view) {
+            (Some(used), 0) => Some(format!("{used} used")),
+            (used, in_view) if in_view > 0 => Some(format!(
+                "{} used, {in_view} in view",
+                used.map_or(String::from("?"), |used| used.to_string())
+            )),
+            _ => None,
+        };
+        let dop = [("H", fix.hdop), ("P", fix.pdop), ("V", fix.vdop)]
+            .into_iter()
+            .filter_map(|(name, dop)| Some(format!("{name} {}", dop?)))
+            .collect::<Vec<_>>();
+        let speed = fix
+            .speed_knots
+            .map(|knots| format!("{knots:.1} kn ({:.1} km/h)", knots * 1.852));
+        let counts = nmea
+            .counts
+            .iter()
+            .map(|(kind, count)| format!("{kind} {count}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let checks = match (nmea.bad_checksums, nmea.unchecked) {
+            (0, 0) => String::from("All checksums good"),
+            (bad, unchecked) => format!("{bad} bad checksums, {unchecked} without one"),
+        };
+        let sky = nmea.satellites.iter().map(|((talker, prn), satellite)| {
+            let value = |value: Option<u32>, unit: &str| {
+                value.map_or(String::from("-"), |value| format!("{value}{unit}"))
+            };
+            text(format!(
+                "{} {prn}: el {} az {} SNR {}",
+                nmea::constellation(talker),
+                value(satellite.elevation, "°"),
+                value(satellite.azimuth, "°"),
+                value(satellite.snr, " dB"),
+            ))
+            .size(12)
+            .into()
+        });
+        let summary = column![
+            row![
+                text(format!("NMEA: {} sentences", nmea.total())).width(Fill),
+                button("Reset").padding(5).on_press(Message::ResetNmea),
+            ]
+            .align_y(Center),
+            field("Fix", status),
+            field("Time", time),
+            field("Latitude", coordinate(fix.latitude, 'N', 'S')),
+            field("Longitude", coordinate(fix.longitude, 'E', 'W')),
+            field(
+                "Altitude",
+                fix.altitude.map(|altitude| format!("{altitude} m"))
+            ),
+            field("Satellites", satellites),
+            field("DOP", (!dop.is_empty()).then(|| dop.join(", "))),
+            field("Speed", speed),
+            field("Course", fix.course.map(|course| format!("{course}°"))),
+            text(counts).size(12),
+            text(checks).size(12),
+        ]
+        .push_maybe(
+            nmea.last_error
+                .as_ref()
+                .map(|e| text(format!("Last: {e}")).size(12).style(text::danger)),
+        )
+        .spacing(5);
+        container(column![summary, scrollable(column(sky).spacing(2))].spacing(10))
+            .padding(10)
+            .width(320)
+            .height(Fill)
+            .style(container::rounded_box)
+            .into()
+    }
+    // MAVLink Dissector: one entry per message kind and sender, newest values only
+    fn mavlink_view(&self) -> Element<'_, Message> {
+        let now = Instant::now();
+        let entries = self.mavlink.latest.values().map(|latest| {
+            let packet = &latest.packet;
+            let age = now.saturating_duration_since(latest.at).as_secs_f32();
+            column![
+                text(format!(
+                    "{} from {}:{} (v{}{}) x{}, {age:.1} s ago",
+                    packet.title(),
+                    packet.system,
+                    packet.component,
+                    packet.version,
+                    if packet.signed { ", signed" } else { "" },
+                    latest.count
+                )),
+                text(packet.summary()).size(12),
+            ]
+            .spacing(2)
+            .into()
+        });
+        let header = row![
+            text(format!("MAVLink: {}", self.mavlink.totals())).width(Fill),
+            button("Reset").padding(5).on_press(Message::ResetMavlink),
+        ]
+        .align_y(Center);
+        container(column![header, scrollable(column(entries).spacing(8))].spacing(10))
+            .padding(10)
+            .width(420)
+            .height(Fill)
+            .style(container::rounded_box)
+            .into()
+    }
+    fn alarms_view(&self) -> Element<'_, Message> {
+        let inputs = row![
+            text_input("Channel", &self.alarm_channel)
+                .on_input(Message::ChangeAlarmChannel)
+                .on_submit(Message::AddThreshold),
+            text_input("Min", &self.alarm_min)
+                .on_input(Message::ChangeAlarmMin)
+                .on_submit(Message::AddThreshold),
+            text_input("Max", &self.alarm_max)
+                .on_input(Message::ChangeAlarmMax)
+                .on_submit(Message::AddThreshold),
+            button("Add").padding(5).on_press(Message::AddThreshold),
+            checkbox("Flash window", self.flash_on_alarm).on_toggle(Message::CheckBoxFlashOnAlarm),
+        ]
+        .spacing(10)
+        .align_y(Center);
+        let thresholds = self
+            .monitor
+            .thresholds
+            .iter()
+            .enumerate()
+            .map(|(i, threshold)| {
+                let bound = |bound: Option<f64>| bound.map_or(String::from("-"), |b| b.to_string());
+                let latest = self
+                    .monitor
+                    .latest
+                    .get(&threshold.channel)
+                    .map_or(String::from("no data"), |value| value.to_string());
+                row![
+                    text(format!(
+                        "{}: {} .. {} (now {latest})",
+                        threshold.channel,
+                        bound(threshold.min),
+                        bound(threshold.max)
+                    ))
+                    .width(Fill),
+                    button("Remove")
+                        .padding(2)
+                        .on_press(Message::RemoveThreshold(i)),
+                ]
+                .spacing(10)
+                .align_y(Center)
+                .into()
+            });
+        let banner = (!self.monitor.alarms.is_empty()).then(|| {
+            let alarms = self.monitor.alarms.values().cloned().collect::<Vec<_>>();
+            container(
+                row![
+                    text(format!("ALARM: {}", alarms.join(", "))).width(Fill),
+                    button("Acknowledge")
+                        .padding(5)
+                        .on_press(Message::AcknowledgeAlarms),
+                ]
+                .spacing(10)
+                .align_y(Center),
+            )
+            .padding(10)
+            .width(Fill)
+            .style(|theme: &Theme| container::Style {
+                background: Some(theme.palette().danger.into()),
+                text_color: Some(theme.palette().background),
+                ..container::Style::default()
+            })
+        });
+        container(
+            column![]
+                .push_maybe(banner)
+                .push(inputs)
+                .extend(thresholds)
+                .spacing(10),
+        )
+        .padding(10)
+        .style(container::rounded_box)
+        .into()
+    }
+    // Advanced Tuning: fast streams want bigger reads and buffers, slow sensors longer waits
+    fn advanced_view(&self) -> Element<'_, Message> {
+        let field = |label, value: &str, on_input: fn(String) -> Message| {
+            row![
+                text(label),
+                text_input("", value)
+                    .on_input(on_input)
+                    .padding(5)
+                    .width(70)
+            ]
+            .spacing(5)
+            .align_y(Center)
+        };
+        container(
+            column![
+                row![
+                    field(
+                        "Read timeout (ms)",
+                        &self.read_timeout_ms,
+                        Message::ChangeReadTimeout
+                    ),
+                    field(
+                        "Min read size (B)",
+                        &self.read_size,
+                        Message::ChangeReadSize
+                    ),
+                    field(
+                        "RX ring (chunks)",
+                        &self.ring_capacity,
+                        Message::ChangeRingCapacity
+                    ),
+                    field(
+                        "Poll every (ms)",
+                        &self.poll_ms,
+                        Message::ChangePollInterval
+                    ),
+                ]
+                .spacing(20),
+                row![
+                    field(
+                        "TX delay per byte (ms)",
+                        &self.char_delay_ms,
+                        Message::ChangeCharDelay
+                    ),
+                    field(
+                        "TX delay per line (ms)",
+                        &self.line_delay_ms,
+                        Message::ChangeLineEndDelay
+                    ),
+                ]
+                .spacing(20),
+                text(
+                    "The timeout applies when a port is opened, read and ring sizes when the \
+                     listener starts, TX delays to every send after they're changed. Blank or \
+                     invalid values use the defaults."
+                )
+                .size(12),
+            ]
+            .spacing(10),
+        )
+        .padding(10)
+        .style(container::rounded_box)
+        .into()
+    }
+    // Capture Database
+    fn start_capture(&mut self) {
+        let port = self.selected_port.clone().unwrap_or_default();
+        let opened = match capture::database_path() {
+            Some(path) => Capture::open(&path, &port)
+                .map(|capture| (capture, path))
+                .map_err(|e| e.to_string()),
+            None => Err(String::from("no config directory")),
+        };
+        match opened {
+            Ok((capture, path)) => {
+                self.capture = Some(capture);
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Info,
+                    format!("Capturing frames to '{}'", path.display()),
+                ));
+            }
+            Err(e) => {
+                self.capture_enabled = false;
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Error,
+                    format!("Failed to open capture database: {e}"),
+                ));
+            }
+        }
+    }
+    // Switch framing, logging anything held back under the old one first. A delimiter that
+    // doesn't parse leaves the current framing in place.
+    fn set_framing(&mut self, framing: Framing) {
+        let delimiter = match framing {
+            Framing::Delimiter => match framing::parse_delimiter(&self.frame_delimiter) {
+                Ok(delimiter) => delimiter,
+                Err(e) => {
+                    self.log_messages.push(LogEntry::new(
+                        LogLevel::Error,
+                        format!("Invalid frame delimiter '{}': {e}", self.frame_delimiter),
+                    ));
+                    return;
+                }
+            },
+            _ => Vec::new(),
+        };
+        let options = FrameOptions {
+            delimiter,
+            gap: Duration::from_millis(tuning(&self.frame_gap_ms, DEFAULT_FRAME_GAP_MS)),
+            length: tuning(&self.frame_length, DEFAULT_FRAME_LENGTH) as usize,
+            encoding: self.selected_encoding.unwrap(),
+        };
+        self.flush_frame();
+        self.selected_framing = Some(framing);
+        self.framer = Framer::new(framing, options);
+    }
+    // Log a partly received frame, e.g. a line still waiting for its line ending
+    fn flush_frame(&mut self) {
+        if let Some((frame, at)) = self.framer.flush() {
+            let time = self.session_stats.since_open(at);
+            self.session_stats.record_line();
+            self.log_messages
+                .push_folded(LogEntry::received(frame, time));
+        }
+        // A character cut off here won't be continued by whatever arrives next
+        self.rx_decoder = StreamDecoder::new(self.selected_encoding.unwrap());
+    }
+    // Raw reads keep characters in the encoding whole, so the framer is rebuilt too
+    fn set_encoding(&mut self, encoding: TextEncoding) {
+        self.selected_encoding = Some(encoding);
+        self.set_framing(self.selected_framing.unwrap());
+    }
+    // The capture thread stops when a write fails, and says why
+    fn check_capture(&mut self) {
+        if let Some(e) = self.capture.as_ref().and_then(Capture::failed) {
+            self.capture = None;
+            self.capture_enabled = false;
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Error,
+                format!("Capture stopped, failed to write frames: {e}"),
+            ));
+        }
+    }
+    fn run_query(&mut self) {
+        let bytes = match hex::decode(self.query_bytes.replace(' ', "")) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Error,
+                    format!("Invalid byte pattern: {e}"),
+                ));
+                return;
+            }
+        };
+        let query = capture::Query {
+            bytes,
+            text: self.query_text.clone(),
+            port: self.query_port.trim().to_string(),
+            since: self.selected_query_since.unwrap().start(SystemTime::now()),
+        };
+        let results = capture::database_path()
+            .ok_or_else(|| String::from("no config directory"))
+            .and_then(|path| capture::query(&path, &query).map_err(|e| e.to_string()));
+        match results {
+            Ok(results) => self.query_results = Some(results),
+            Err(e) => self.log_messages.push(LogEntry::new(
+                LogLevel::Error,
+                format!("Capture query failed: {e}"),
+            )),
+        }
+    }
+    // Capture Query Panel
+    fn query_view(&self) -> Element<'_, Message> {
+        let filters = row![
+            text_input("Bytes (hex)", &self.query_bytes)
+                .on_input(Message::ChangeQueryBytes)
+                .on_submit(Message::RunQuery),
+            text_input("Text contains", &self.query_text)
+                .on_input(Message::ChangeQueryText)
+                .on_submit(Message::RunQuery),
+            text_input("Port", &self.query_port)
+                .on_input(Message::ChangeQueryPort)
+                .on_submit(Message::RunQuery),
+            combo_box(
+                &self.query_since_list,
+                "Since...",
+                self.selected_query_since.as_ref(),
+                Message::SelectQuerySince,
+            )
+            .width(120),
+            button("Search").padding(5).on_press(Message::RunQuery),
+        ]
+        .spacing(10)
+        .align_y(Center);
+        let results = self.query_results.as_deref().unwrap_or_default();
+        let rows = results.iter().map(|m| {
+            text(format!(
+                "{} {} {} {} bytes: {} {:?}",
+                capture::format_time(m.time_us),
+                m.port,
+                m.direction.to_uppercase(),
+                m.data.len(),
+                format::hex(&m.data),
+                m.text
+            ))
+            .into()
+        });
+        let summary = match &self.query_results {
+            Some(results) => format!("{} matching frames (newest first)", results.len()),
+            None => String::from("Search frames captured in every session"),
+        };
+        container(
+            column![
+                filters,
+                text(summary),
+                scrollable(column(rows)).width(Fill).height(150)
+            ]
+            .spacing(10),
+        )
+        .padding(10)
+        .style(container::rounded_box)
+        .into()
+    }
+    fn set_value_layout(&mut self) {
+        if self.value_layout.trim().is_empty() {
+            self.layout = None;
+            return;
+        }
+        match Layout::parse(&self.value_layout) {
+            Ok(layout) => self.layout = Some(layout),
+            Err(e) => self.log_messages.push(LogEntry::new(
+                LogLevel::Error,
+                format!("Invalid value layout '{}': {e}", self.value_layout),
+            )),
+        }
+    }
+    // Decoded Values Panel
+    // Queued writes, each of which can be dropped before it starts
+    fn queue_view(&self) -> Element<'_, Message> {
+        let rows = self.tx_queue.iter().enumerate().map(|(index, pending)| {
+            let label = pending
+                .label
+                .strip_prefix("Sent ")
+                .unwrap_or(&pending.label);
+            row![
+                button(text("x").size(12))
+                    .padding([2, 6])
+                    .style(button::text)
+                    .on_press(Message::DropQueued(index)),
+                text(label.to_string()).size(12)
+            ]
+            .spacing(10)
+            .align_y(Center)
+            .into()
+        });
+        column![
+            text(format!("{} queued sends", self.tx_queue.len())),
+            container(scrollable(column(rows).spacing(2))).max_height(120)
+        ]
+        .spacing(5)
+        .into()
+    }
+    // While editing, a click loads the button into the form instead of sending it
+    fn buttons_view(&self) -> Element<'_, Message> {
+        let grid = row(self.quick_buttons.iter().enumerate().map(|(index, quick)| {
+            button(text(quick.name.as_str()))
+                .padding(10)
+                .style(match self.edit_buttons {
+                    true => button::secondary,
+                    false => button::primary,
+                })
+                .on_press(Message::QuickSend(index))
+                .into()
+        }))
+        .spacing(10)
+        .wrap();
+        if !self.edit_buttons {
+            return grid.into();
+        }
+        let name = text_input("Button name", &self.button_name)
+            .on_input(Message::ChangeButtonName)
+            .padding(10)
+            .width(150);
+        let payload = text_input(
+            match self.button_hex {
+                true => "Hex bytes, e.g. 01 03 00 00",
+                false => "Text, \\ escapes allowed",
+            },
+            &self.button_payload,
+        )
+        .on_input(Message::ChangeButtonPayload)
+        .on_submit(Message::SaveButton)
+        .padding(10);
+        let hex = checkbox("Hex", self.button_hex).on_toggle(Message::CheckBoxButtonHex);
+        let ending = combo_box(
+            &self.button_ending_list,
+            "Line ending",
+            self.selected_button_ending.as_ref(),
+            Message::SelectButtonEnding,
+        )
+        .padding(10)
+        .width(140);
+        let save = button("Save Button")
+            .padding(10)
+            .on_press(Message::SaveButton);
+        let exists = self
+            .quick_buttons
+            .iter()
+            .any(|quick| quick.name == self.button_name.trim());
+        let remove = button("Remove")
+            .padding(10)
+            .style(button::danger)
+            .on_press_maybe(exists.then_some(Message::RemoveButton));
+        let form = row![name, payload, hex]
+            .push_maybe((!self.button_hex).then_some(ending))
+            .push(save)
+            .push(remove)
+            .spacing(20)
+            .align_y(Center);
+        column![grid, form].spacing(10).into()
+    }
+    fn editor_view(&self) -> Element<'_, Message> {
+        let editor = text_editor(&self.editor)
+            .placeholder("One command per line")
+            .on_action(Message::EditBlock)
+            .padding(10)
+            .height(150);
+        let by_line =
+            checkbox("Line by line", self.editor_by_line).on_toggle(Message::CheckBoxByLine);
+        let delay = text_input("ms", &self.editor_delay_ms)
+            .on_input(Message::ChangeLineDelay)
+            .padding(10)
+            .width(60);
+        let send = match self.editor_queue.len() {
+            0 => button("Send Block")
+                .padding(10)
+                .style(button::success)
+                .on_press(Message::SendBlock),
+            left => button(text(format!("Stop ({left} lines left)")))
+                .padding(10)
+                .style(button::danger)
+                .on_press(Message::StopBlock),
+        };
+        let mut controls = row![by_line].spacing(20).align_y(Center);
+        if self.editor_by_line {
+            controls = controls.push(text("Delay:")).push(delay);
+        }
+        column![editor, controls.push(send)].spacing(10).into()
+    }
+    fn values_view(&self) -> Element<'_, Message> {
+        let editor = row![
+            text_input("e.g. id:u8 x1 temp:i16be volts:f32", &self.value_layout)
+                .on_input(Message::ChangeValueLayout)
+                .on_submit(Message::SetValueLayout),
+            button("Apply").padding(5).on_press(Message::SetValueLayout),
+            button("Clear")
+                .padding(5)
+                .on_press_maybe(self.layout.is_some().then_some(Message::ClearValueLayout)),
+        ]
+        .spacing(10)
+        .align_y(Center);
+        let help = text(
+            "Types u8 i8 u16 i16 u32 i32 u64 i64 f32 f64, little endian unless followed by be. \
+             xN skips N bytes and name:type labels a field.",
+        )
+        .size(12);
+        let latest = self
+            .log_messages
+            .entries()
+            .iter()
+            .rev()
+            .find_map(|entry| entry.data.as_ref());
+        let status = match (&self.layout, latest) {
+            (Some(layout), Some(data)) => format!("Latest frame: {}", layout.decode(data)),
+            (Some(_), None) => String::from("Waiting for a received frame"),
+            (None, _) => String::from("Received frames are decoded once a layout is applied"),
+        };
+        container(column![editor, help, text(status)].spacing(10))
+            .padding(10)
+            .style(container::rounded_box)
+            .into()
+    }
+    // Session Summary Export
+    fn export_stats(&mut self) {
+        let exported = std::env::current_dir()
+            .and_then(|dir| self.session_stats.export(&dir, &self.session_name));
+        match exported {
+            Ok(path) => self.log_messages.push(LogEntry::new(
+                LogLevel::Info,
+                format!(
+                    "Session statistics written to '{}' (.csv/.json)",
+                    path.display()
+                ),
+            )),
+            Err(e) => self.log_messages.push(LogEntry::new(
+                LogLevel::Error,
+                format!("Failed to export session statistics: {e}"),
+            )),
+        }
+    }
+    // Entries still in memory only; ones spilled to disk are left out
+    fn export_log(&mut self) {
+        let exported = std::env::current_dir().and_then(|dir| {
+            export::export(
+                self.log_messages.entries(),
+                self.selected_encoding.unwrap(),
+                &dir,
+                &self.session_name,
+            )
+        });
+        match exported {
+            Ok(path) => self.log_messages.push(LogEntry::new(
+                LogLevel::Info,
+                format!("Log written to '{}' (.csv/.json)", path.display()),
+            )),
+            Err(e) => self.log_messages.push(LogEntry::new(
+                LogLevel::Error,
+                format!("Failed to export the log: {e}"),
+            )),
+        }
+    }
+    // Scrollback Paging
+    fn page_in(&mut self, start: usize) {
+        if let Err(e) = self.log_messages.page_in(start) {
+            self.log_messages.push(LogEntry::new(
+                LogLevel::Error,
+                format!("Failed to read older log entries: {e}"),
+            ));
+        }
+    }
+    // Global indices of the entries currently shown: a paged-in window, then everything in memory
+    fn visible_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        let page = self
+            .log_messages
+            .page()
+            .map_or(0..0, |page| page.start..page.start + page.entries.len());
+        page.chain(self.log_messages.first_in_memory()..self.log_messages.len())
+    }
+    // Error Navigation
+    fn jump_to_error(&mut self, forward: bool) -> Task<Message> {
+        match self.log_messages.find_error(self.selected_entry, forward) {
+            Some(index) => self.scroll_to_entry(index),
+            None => Task::none(),
+        }
+    }
+    // Markers: a labelled line put in the log and the capture by hand, to line sections of
+    // the log up with the steps of a test
+    fn add_marker(&mut self) {
+        self.markers_added += 1;
+        let label = match self.marker_label.trim() {
+            "" => format!("Marker {}", self.markers_added),
+            label => label.to_string(),
+        };
+        let text = format!("=== {label} ===");
+        if let Some(capture) = self.capture.as_ref() {
+            let now = self.session_stats.wall_time(Instant::now());
+            capture.record(now, Direction::Marker, &[], text.clone());
+        }
+        self.log_messages
+            .push(LogEntry::new(LogLevel::Marker, text));
+        self.marker_label.clear();
+    }
+    fn jump_to_marker(&mut self, forward: bool) -> Task<Message> {
+        match self.log_messages.find_marker(self.selected_entry, forward) {
+            Some(index) => self.scroll_to_entry(index),
+            None => Task::none(),
+        }
+    }
+    // Log Search and Filter
+    fn compile_search(&mut self) {
+        (self.search, self.search_error) = compile_pattern(
+            &self.search_query,
+            self.search_regex,
+            self.search_ignore_case,
+        );
+    }
+    fn compile_filter(&mut self) {
+        (self.filter, self.filter_error) = compile_pattern(
+            &self.filter_query,
+            self.filter_regex,
+            self.filter_ignore_case,
+        );
+    }
+    // Hits in one display line, with ANSI sequences left out while they're shown as colors
+    fn find_in_line(&self, search: &Search, line: &str) -> Vec<Range<usize>> {
+        match self.selected_ansi_mode == Some(AnsiMode::Color) && line.contains('\x1b') {
+            true => search.find_all(&ansi::strip(line)),
+            false => search.find_all(line),
+        }
+    }
+    fn line_hits(&self, line: &str) -> Vec<Range<usize>> {
+        match &self.search {
+            Some(search) => self.find_in_line(search, line),
+            None => Vec::new(),
+        }
+    }
+    fn shows_category(&self, entry: &LogEntry) -> bool {
+        match entry.source() {
+            Source::Tx | Source::Rx => self.show_traffic,
+            Source::System => self.show_system,
+        }
+    }
+    // Everything an entry's lines and the matches in them depend on
+    fn match_key(&self) -> MatchKey {
+        MatchKey {
+            formats: [
+                self.rx_hex_checked,
+                self.rx_dump_checked,
+                self.rx_base64_checked,
+                self.rx_binary_checked,
+                self.rx_utf8_checked,
+            ],
+            encoding: self.selected_encoding,
+            ansi_mode: self.selected_ansi_mode,
+            layout: self.layout.clone(),
+            search: (
+                self.search_query.clone(),
+                self.search_regex,
+                self.search_ignore_case,
+            ),
+            filter: (
+                self.filter_query.clone(),
+                self.filter_regex,
+                self.filter_ignore_case,
+                self.filter_invert,
+            ),
+        }
+    }
+    // Whether an entry passes the filter, its line count and search hits, scanned only when
+    // it's new or has changed since
+    fn matched(&self, cache: &mut MatchCache, entry: &LogEntry, index: usize) -> Matched {
+        let size = entry.text.len() + entry.data.as_ref().map_or(0, |data| data.len());
+        if let Some(matched) = cache.entries.get(&index)
+            && matched.expanded == entry.expanded
+            && matched.size == size
+        {
+            return *matched;
+        }
+        let lines = self.entry_lines(entry);
+        let shown = self.passes_filter(&lines);
+        let hits = match shown && self.search.is_some() {
+            true => lines.iter().map(|line| self.line_hits(line).len()).sum(),
+            false => 0,
+        };
+        let matched = Matched {
+            expanded: entry.expanded,
+            size,
+            lines: lines.len(),
+            shown,
+            hits,
+        };
+        cache.entries.insert(index, matched);
+        matched
+    }
+    // An entry is shown when one of its lines matches the filter, or none does when inverted
+    fn passes_filter(&self, lines: &[Cow<'_, str>]) -> bool {
+        let Some(filter) = &self.filter else {
+            return true;
+        };
+        let matched = lines
+            .iter()
+            .any(|line| !self.find_in_line(filter, line).is_empty());
+        matched != self.filter_invert
+    }
+    // Timestamp and Δt shown ahead of an entry's lines; `previous` is the time of the last
+    // received entry, carried from one entry to the next
+    fn line_prefix(
+        &self,
+        entry: &LogEntry,
+        previous: &mut Duration,
+        opened: Option<SystemTime>,
+    ) -> String {
+        let mut prefix = String::new();
+        if let Some(stamp) = self
+            .selected_timestamp
+            .unwrap()
+            .format(entry.at, entry.time, opened)
+        {
+            prefix = format!("[{stamp}]  ");
+        }
+        if let Some(time) = entry.time
+            && self.show_deltas
+        {
+            let delta = time.saturating_sub(*previous).as_secs_f64() * 1000.0;
+            *previous = time;
+            prefix = format!("{prefix}+{delta:.3} ms  ");
+        }
+        prefix
+    }
+    // Copying: the log lines as they're shown, within `range` or all of them, with ANSI
+    // sequences left out while they're shown as colors
+    fn log_text(&self, range: Option<RangeInclusive<usize>>) -> String {
+        let page_entries = self
+            .log_messages
+            .page()
+            .map(|page| page.entries.as_slice())
+            .unwrap_or_default();
+        let colors = self.selected_ansi_mode == Some(AnsiMode::Color);
+        let opened = self.port.is_some().then(|| self.session_stats.opened_at());
+        let mut previous = Duration::ZERO;
+        let mut text = String::new();
+        for (entry, i) in page_entries
+            .iter()
+            .chain(self.log_messages.entries())
+            .zip(self.visible_indices())
+            .take_while(|(_, i)| self.frozen.is_none_or(|frozen| *i < frozen))
+        {
+            let prefix = self.line_prefix(entry, &mut previous, opened);
+            let lines = self.entry_lines(entry);
+            if range.as_ref().is_some_and(|range| !range.contains(&i))
+                || !self.shows_category(entry)
+                || !self.passes_filter(&lines)
+            {
+                continue;
+            }
+            let (copies, suffix) = match (entry.repeats, entry.expanded) {
+                (1, _) => (1, String::new()),
+                (repeats, true) => (repeats, String::new()),
+                (repeats, false) => (1, format!(" (x{repeats})")),
+            };
+            for _ in 0..copies {
+                for line in &lines {
+                    let line = match colors {
+                        true => Cow::Owned(ansi::strip(line)),
+                        false => Cow::Borrowed(line.as_ref()),
+                    };
+                    let _ = writeln!(text, "{prefix}{line}{suffix}");
+                }
+            }
+        }
+        text
+    }
+    // Scroll Pause
+    fn resume_scroll(&mut self) -> Task<Message> {
+        self.frozen = None;
+        self.frozen_by_scroll = false;
+        scrollable::snap_to(
+            self.log_id.clone(),
+            scrollable::RelativeOffset { x: 0.0, y: 0.0 },
+        )
+    }
+    // The visible entries with their global indices, less any the filter hides and any logged
+    // since the scroll was paused
+    fn shown_entries(&self) -> impl Iterator<Item = (&LogEntry, usize)> + '_ {
+        let page_entries = self
+            .log_messages
+            .page()
+            .map(|page| page.entries.as_slice())
+            .unwrap_or_default();
+        page_entries
+            .iter()
+            .chain(self.log_messages.entries())
+            .zip(self.visible_indices())
+            .take_while(|(_, i)| self.frozen.is_none_or(|frozen| *i < frozen))
+            .filter(|(entry, _)| {
+                self.shows_category(entry) && self.passes_filter(&self.entry_lines(entry))
+            })
+    }
+    // Only the entries shown are searched: those in memory, and any page read back from disk
+    fn jump_to_match(&mut self, forward: bool) -> Task<Message> {
+        let matches = self
+            .shown_entries()
+            .filter(|(entry, _)| {
+                self.entry_lines(entry)
+                    .iter()
+                    .any(|line| !self.line_hits(line).is_empty())
+            })
+            .map(|(_, i)| i)
+            .collect::<Vec<_>>();
+        // Wraps around at either end
+        let next = match (forward, self.selected_entry) {
+            (true, Some(selected)) => matches.iter().find(|&&i| i > selected),
+            (false, Some(selected)) => matches.iter().rev().find(|&&i| i < selected),
+            (_, None) => None,
+        };
+        let next = next.or(match forward {
+            true => matches.first(),
+            false => matches.last(),
+        });
+        match next {
+            Some(&index) => self.scroll_to_entry(index),
+            None => Task::none(),
+        }
+    }
+    // Go To: a 1-based line number, or `@` and a received byte offset (decimal or 0x hex)
+    fn go_to(&mut self) -> Task<Message> {
+        let target = self.go_to.trim().to_string();
+        let index = match target.strip_prefix('@') {
+            Some(offset) => {
+                let offset = match offset.strip_prefix("0x") {
+                    Some(hex) => u64::from_str_radix(hex, 16).ok(),
+                    None => offset.parse().ok(),
+                };
+                offset.and_then(|offset| self.log_messages.find_offset(offset))
+            }
+            None => target
+                .parse::<usize>()
+                .ok()
+                .and_then(|line| line.checked_sub(1))
+                .filter(|index| *index < self.log_messages.len()),
+        };
+        match index {
+            Some(index) => self.scroll_to_entry(index),
+            None => {
+                self.log_messages.push(LogEntry::new(
+                    LogLevel::Warning,
+                    format!("No log line or received offset '{target}'"),
+                ));
+                Task::none()
+            }
+        }
+    }
+    // Select an entry, paging it in from disk if needed, and scroll it into view
+    fn scroll_to_entry(&mut self, index: usize) -> Task<Message> {
+        self.selected_entry = Some(index);
+        if !self.visible_indices().any(|i| i == index) {
+            self.page_in(index.saturating_sub(log::PAGE_SIZE / 2));
+        }
+        // An entry the filter hides stays selected, but there's nowhere to scroll to
+        let Some(pane) = self
+            .shown_entries()
+            .find(|(_, i)| *i == index)
+            .map(|(entry, _)| self.pane_for(entry))
+        else {
+            return Task::none();
+        };
+        let in_pane = || self.shown_entries().filter(|(entry, _)| pane.shows(entry));
+        let Some(row) = in_pane().position(|(_, i)| i == index) else {
+            return Task::none();
+        };
+        // The log is anchored to the bottom, so offsets count up from the newest entry
+        let last = in_pane().count().saturating_sub(1).max(1);
+        let y = 1.0 - row as f32 / last as f32;
+        let id = match pane {
+            Pane::Tx => self.tx_log_id.clone(),
+            _ => self.log_id.clone(),
+        };
+        scrollable::snap_to(id, scrollable::RelativeOffset { x: 0.0, y })
+    }
     // Listener
     fn subscription(&self) -> Subscription<Message> {
-        match self.recv_state {
+        let listener = match self.recv_state {
             RecvState::Idle => Subscription::none(),
-            RecvState::Listening => every(Duration::from_millis(10)).map(|_| Message::Recv),
+            RecvState::Listening => every(Duration::from_millis(tuning(
+                &self.poll_ms,
+                DEFAULT_POLL_MS,
+            )))
+            .map(|_| Message::Recv),
+        };
+        let lines = match self.port {
+            Some(_) => every(Duration::from_millis(50)).map(|_| Message::PollLines),
+            None => Subscription::none(),
+        };
+        let script = match self.script {
+            Some(_) => every(Duration::from_millis(10)).map(|_| Message::StepMacro),
+            None => Subscription::none(),
+        };
+        let hotplug = every(Duration::from_secs(2)).map(|_| Message::PollPorts);
+        let reconnect = match self.reconnect {
+            Some(_) => every(Duration::from_secs(1)).map(|_| Message::RetryConnect),
+            None => Subscription::none(),
+        };
+        let bridge = match self.bridge {
+            Some(_) => every(Duration::from_millis(tuning(
+                &self.poll_ms,
+                DEFAULT_POLL_MS,
+            )))
+            .map(|_| Message::DrainBridge),
+            None => Subscription::none(),
+        };
+        let repeat = match self.repeat {
+            true => every(Duration::from_millis(tuning(
+                &self.repeat_ms,
+                DEFAULT_REPEAT_MS,
+            )))
+            .map(|_| Message::RepeatSend),
+            false => Subscription::none(),
+        };
+        let log_file = match self.log_file {
+            Some(_) => every(Duration::from_secs(1)).map(|_| Message::CheckLogFile),
+            None => Subscription::none(),
+        };
+        let upload = match self.upload {
+            Some(_) => every(Duration::from_millis(10)).map(|_| Message::StepUpload),
+            None => Subscription::none(),
+        };
+        let xmodem = match self.xmodem {
+            Some(_) => every(Duration::from_millis(10)).map(|_| Message::StepXmodem),
+            None => Subscription::none(),
+        };
+        let scheduled = match self.scheduled {
+            Some(_) => every(Duration::from_millis(250)).map(|_| Message::ScheduledTick),
+            None => Subscription::none(),
+        };
+        let block = match self.editor_queue.is_empty() {
+            false => every(Duration::from_millis(10)).map(|_| Message::SendNextLine),
+            true => Subscription::none(),
+        };
+        Subscription::batch([
+            listener, lines, script, hotplug, reconnect, bridge, repeat, block, scheduled, upload,
+            xmodem, log_file,
+        ])
+    }
+    // Keyboard Shortcuts
+    // The palette keys also work while a text input has focus
+    fn shortcut(key: Key, modifiers: Modifiers, status: event::Status) -> Option<Message> {
+        match key {
+            Key::Character(c)
+                if c.eq_ignore_ascii_case("p") && modifiers.command() && modifiers.shift() =>
+            {
+                Some(Message::TogglePalette)
+            }
+            Key::Character(c) if c.eq_ignore_ascii_case("t") && modifiers.command() => {
+                Some(Message::NewTab)
+            }
+            Key::Character(c) if c.eq_ignore_ascii_case("w") && modifiers.command() => {
+                Some(Message::CloseTab)
+            }
+            Key::Character(c) if c.eq_ignore_ascii_case("f") && modifiers.command() => {
+                Some(Message::FindInLog)
+            }
+            Key::Character(c) if c.eq_ignore_ascii_case("l") && modifiers.command() => {
+                Some(Message::ClearLog)
+            }
+            Key::Character(c) if c.eq_ignore_ascii_case("m") && modifiers.command() => {
+                Some(Message::AddMarker)
+            }
+            Key::Named(Named::Tab) if modifiers.control() => Some(Message::NextTab),
+            Key::Named(Named::Escape) => Some(Message::ClosePalette),
+            Key::Named(Named::ArrowUp) => Some(Message::MovePalette(-1)),
+            Key::Named(Named::ArrowDown) => Some(Message::MovePalette(1)),
+            _ if status == event::Status::Captured => None,
+            Key::Character(c) if c.eq_ignore_ascii_case("c") && modifiers.command() => {
+                Some(Message::CopySelection)
+            }
+            Key::Named(Named::F8) if modifiers.shift() => Some(Message::PrevError),
+            Key::Named(Named::F8) => Some(Message::NextError),
+            Key::Named(Named::F3) if modifiers.shift() => Some(Message::PrevMatch),
+            Key::Named(Named::F3) => Some(Message::NextMatch),
+            Key::Named(Named::F2) if modifiers.shift() => Some(Message::PrevMarker),
+            Key::Named(Named::F2) => Some(Message::NextMarker),
+            _ => None,
         }
     }
+    // Every action the command palette offers, named for the current state
+    fn commands(&self) -> Vec<(&'static str, Message)> {
+        let (listener, toggle_listener) = match self.recv_state {
+            RecvState::Idle => ("Start Listener", Message::ToggleListener),
+            RecvState::Listening => ("Stop Listener", Message::ToggleListener),
+        };
+        let bridge = match (&self.bridge, self.tap) {
+            (Some(_), true) => "Stop Tap",
+            (Some(_), false) => "Stop Bridge",
+            (None, true) => "Start Tap",
+            (None, false) => "Start Bridge",
+        };
+        vec![
+            ("Open Port", Message::OpenPort),
+            ("Rescan Ports", Message::RescanPorts),
+            ("Close Port", Message::ClosePort),
+            (listener, toggle_listener),
+            ("Send Command", Message::Send),
+            (
+                match self.repeat {
+                    true => "Stop Repeat",
+                    false => "Repeat Command",
+                },
+                Message::ToggleRepeat,
+            ),
+            ("Send Break", Message::SendBreak),
+            ("Toggle RTS", Message::CheckBoxRts(!self.rts)),
+            ("Toggle DTR", Message::CheckBoxDtr(!self.dtr)),
+            ("Run Macro", Message::RunMacro),
+            ("Stop Macro", Message::StopMacro),
+            (bridge, Message::ToggleBridge),
+            ("Clear Log", Message::ClearLog),
+            ("Undo Clear Log", Message::UndoClear),
+            ("Copy Selected Log Lines", Message::CopySelection),
+            ("Copy All Log Lines", Message::CopyAll),
+            (
+                match self.frozen {
+                    Some(_) => "Resume Scroll",
+                    None => "Pause Scroll",
+                },
+                Message::ToggleScrollPause,
+            ),
+            ("Next Error", Message::NextError),
+            ("Previous Error", Message::PrevError),
+            ("Add Marker", Message::AddMarker),
+            ("Next Marker", Message::NextMarker),
+            ("Previous Marker", Message::PrevMarker),
+            ("Find in Log", Message::FindInLog),
+            ("Next Match", Message::NextMatch),
+            ("Previous Match", Message::PrevMatch),
+            ("Show Older Log Entries", Message::PageOlder),
+            ("Back to Live Log", Message::ClosePage),
+            ("Export Session Statistics", Message::ExportStats),
+            ("Export Log as CSV/JSON", Message::ExportLog),
+            ("New Window", Message::NewWindow),
+            ("New Tab", Message::NewTab),
+            ("Next Tab", Message::NextTab),
+            ("Close Tab", Message::CloseTab),
+            ("Toggle Split View", Message::SplitView),
+            ("Compare Split Tabs", Message::CompareView),
+            ("Detach Tab Into New Window", Message::DetachTab),
+            ("Save Workspace", Message::SaveWorkspace),
+            ("Restore Workspace", Message::RestoreWorkspace),
+            ("Send as Text", Message::SelectRadio(RadioChoice::Utf8)),
+            ("Send as HEX", Message::SelectRadio(RadioChoice::Hex)),
+            ("Send as Base64", Message::SelectRadio(RadioChoice::Base64)),
+            (
+                "Send as decimal bytes",
+                Message::SelectRadio(RadioChoice::Decimal),
+            ),
+            (
+                "Toggle Receive as HEX",
+                Message::CheckBoxHEX(!self.rx_hex_checked),
+            ),
+            (
+                "Toggle Receive as BIN",
+                Message::CheckBoxBIN(!self.rx_binary_checked),
+            ),
+            (
+                "Toggle Receive as Hexdump",
+                Message::CheckBoxDump(!self.rx_dump_checked),
+            ),
+            (
+                "Toggle Receive as Base64",
+                Message::CheckBoxBase64(!self.rx_base64_checked),
+            ),
+            (
+                "Toggle Receive as Text",
+                Message::CheckBoxUTF8(!self.rx_utf8_checked),
+            ),
+            (
+                "Toggle Receive Time Deltas",
+                Message::CheckBoxDeltas(!self.show_deltas),
+            ),
+            (
+                "Toggle Flush After Send",
+                Message::CheckBoxFlush(!self.flush_after_send),
+            ),
+            (
+                "Toggle Echo Verification",
+                Message::CheckBoxEcho(!self.verify_echo),
+            ),
+            (
+                "Toggle Line Timeline",
+                Message::CheckBoxTimeline(!self.show_timeline),
+            ),
+            (
+                "Toggle Export Statistics on Close",
+                Message::CheckBoxStatsOnClose(!self.export_stats_on_close),
+            ),
+        ]
+    }
+    // Commands whose names contain every word of the palette query
+    fn palette_matches(&self) -> Vec<(&'static str, Message)> {
+        let query = self
+            .palette
+            .as_ref()
+            .map(|palette| palette.query.to_lowercase())
+            .unwrap_or_default();
+        self.commands()
+            .into_iter()
+            .filter(|(name, _)| {
+                let name = name.to_lowercase();
+                query.split_whitespace().all(|word| name.contains(word))
+            })
+            .take(PALETTE_ROWS)
+            .collect()
+    }
+    // First-Run Wizard UI
+    fn setup_view<'a>(&'a self, setup: &'a Setup) -> Element<'a, Message> {
+        let (heading, body): (&str, Element<'a, Message>) = match setup.step {
+            SetupStep::Permissions => {
+                let summary = match (setup.ports, setup.problems.is_empty()) {
+                    (0, _) => "No serial ports found. Plug in a device and check again.",
+                    (_, true) => "All detected ports can be opened.",
+                    (_, false) => "Some ports can't be opened by this user:",
+                };
+                let problems = setup.problems.iter().map(|problem| text(problem).into());
+                let body = column![text(summary)]
+                    .extend(problems)
+                    .push(
+                        button("Check Again")
+                            .padding(10)
+                            .on_press(Message::SetupRecheck),
+                    )
+                    .spacing(10);
+                ("Step 1 of 3: Port access", body.into())
+            }
+            SetupStep::Port => {
+                let port_list = combo_box(
+                    &self.port_list,
+                    "Select a port...",
+                    self.selected_port_entry().as_ref(),
+                    |port: PortEntry| Message::SelectPort(port.name),
+                )
+                .padding(10);
+                (
+                    "Step 2 of 3: Default port",
+                    column![text("Port selected when the app starts:"), port_list]
+                        .spacing(10)
+                        .into(),
+                )
+            }
+            SetupStep::Theme => {
+                let theme_list = combo_box(
+                    &self.theme_list,
+                    "Change theme...",
+                    self.selected_theme.as_ref(),
+                    Message::SelectTheme,
+                )
+                .on_option_hovered(Message::HoverTheme)
+                .padding(10);
+                ("Step 3 of 3: Theme", theme_list.into())
+            }
+        };
+        let back = (setup.step != SetupStep::Permissions)
+            .then(|| button("Back").padding(10).on_press(Message::SetupBack));
+        let next = match setup.step {
+            SetupStep::Theme => button("Finish").padding(10).on_press(Message::SetupFinish),
+            _ => button("Next").padding(10).on_press(Message::SetupNext),
+        };
+        container(
+            column![
+                text("Welcome to Serial App").size(24),
+                text(heading).size(18),
+                body,
+                row![]
+                    .push_maybe(back)
+                    .push(horizontal_space())
+                    .push(button("Skip").padding(10).on_press(Message::SetupFinish))
+                    .push(next)
+                    .spacing(20),
+            ]
+            .spacing(20),
+        )
+        .padding(20)
+        .into()
+    }
+    // Command Palette
+    fn palette_view(&self) -> Option<Element<'_, Message>> {
+        let palette = self.palette.as_ref()?;
+        let query = text_input("Type a command...", &palette.query)
+            .id(self.palette_id.clone())
+            .on_input(Message::ChangePaletteQuery)
+            .on_submit(Message::RunCommand(palette.selected));
+        let matches = self
+            .palette_matches()
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, _))| {
+                button(text(name))
+                    .width(Fill)
+                    .style(match i == palette.selected {
+                        true => button::primary,
+                        false => button::text,
+                    })
+                    .on_press(Message::RunCommand(i))
+                    .into()
+            });
+        let list = column![query].extend(matches).spacing(5);
+        Some(
+            container(list)
+                .padding(10)
+                .style(|theme: &Theme| container::Style {
+                    border: Border {
+                        color: theme.palette().primary,
+                        width: 1.0,
+                        radius: Radius::new(3.0),
+                    },
+                    ..container::Style::default()
+                })
+                .into(),
+        )
+    }
+    // ZMODEM Auto-Start: accepting uses the file field like the transfer buttons do
+    fn zmodem_offer_view(&self) -> Option<Element<'_, Message>> {
+        let prompt = match self.zmodem_offer? {
+            Offer::Receive => "The remote side is sending with ZMODEM. Receive into directory:",
+            Offer::Send => "The remote side is waiting for a ZMODEM upload. Send files (a;b):",
+        };
+        let path = text_input("Path", &self.file_path)
+            .on_input(Message::ChangeFilePath)
+            .on_submit(Message::AcceptZmodem)
+            .padding(10);
+        let browse = match self.zmodem_offer? {
+            Offer::Receive => Browse::Folder,
+            Offer::Send => Browse::Files,
+        };
+        Some(
+            container(
+                row![
+                    text(prompt),
+                    path,
+                    button("Browse…")
+                        .padding(10)
+                        .style(button::secondary)
+                        .on_press(Message::BrowseFile(browse)),
+                    button("Accept").padding(10).on_press(Message::AcceptZmodem),
+                    button("Decline")
+                        .padding(10)
+                        .style(button::secondary)
+                        .on_press(Message::DeclineZmodem),
+                ]
+                .spacing(20)
+                .align_y(Center),
+            )
+            .padding(10)
+            .style(|theme: &Theme| container::Style {
+                border: Border {
+                    color: theme.palette().primary,
+                    width: 1.0,
+                    radius: Radius::new(3.0),
+                },
+                ..container::Style::default()
+            })
+            .into(),
+        )
+    }
     // App UI
     fn view(&self) -> Element<'_, Message> {
+        if let Some(path) = &self.crash_report {
+            return crash_view(path);
+        }
+        if let Some(setup) = &self.setup {
+            return self.setup_view(setup);
+        }
         // Inputs
         let port_list = combo_box(
             &self.port_list,
             "Select a port...",
-            self.selected_port.as_ref(),
-            Message::SelectPort,
+            self.selected_port_entry().as_ref(),
+            |port: PortEntry| Message::SelectPort(port.name),
         )
         .padding(10);
+        let rescan = button("Rescan").padding(10).on_press(Message::RescanPorts);
+        // Named pipes and Unix sockets can't be listed, so their path is typed in
+        let endpoint_path = text_input("Pipe, socket, unix:path or @name", &self.endpoint_path)
+            .on_input(Message::ChangeEndpointPath)
+            .on_submit(Message::SelectPort(self.endpoint_path.trim().to_string()))
+            .padding(10);
         let baud_rate = combo_box(
             &self.baud_rate_list,
             "Baud rate",
@@ -316,6 +5889,11 @@ impl SerialApp {
             Message::SelectBaudRate,
         )
         .padding(10);
+        let custom_baud_rate = text_input("Other baud", &self.custom_baud_rate)
+            .on_input(Message::ChangeCustomBaudRate)
+            .on_submit(Message::SetCustomBaudRate)
+            .padding(10)
+            .width(110);
         let parity = combo_box(
             &self.parity_list,
             "Parity",
@@ -337,6 +5915,13 @@ impl SerialApp {
             Message::SelectStopBits,
         )
         .padding(10);
+        let flow_control = combo_box(
+            &self.flow_control_list,
+            "Flow control",
+            self.selected_flow_control.as_ref(),
+            Message::SelectFlowControl,
+        )
+        .padding(10);
         let theme_list = combo_box(
             &self.theme_list,
             "Change theme...",
@@ -347,12 +5932,51 @@ impl SerialApp {
         .padding(10)
         .width(200);
         let command = text_input("Enter command...", &self.command)
+            .id(self.command_id.clone())
             .on_input(Message::ChangeCmd)
             .on_submit(Message::Send)
             .padding(10);
+        let history = combo_box(
+            &self.history_list,
+            "Recent commands",
+            None,
+            Message::SelectHistory,
+        )
+        .padding(10)
+        .width(180);
         let tx_type = text("Command type:");
+        let tx_escapes =
+            checkbox("\\ escapes", self.tx_escapes).on_toggle(Message::CheckBoxEscapes);
+        // Bytes the command turns into, before any TX framing
+        let command_size = text(
+            match self.input_bytes(
+                &self.command,
+                self.radio_choice.unwrap(),
+                self.selected_tx_ending.unwrap(),
+            ) {
+                Ok((_, data, _)) => format!("{} bytes", data.len()),
+                Err(_) => String::from("Invalid"),
+            },
+        )
+        .width(70);
+        let tx_ending = combo_box(
+            &self.tx_ending_list,
+            "Line ending",
+            self.selected_tx_ending.as_ref(),
+            Message::SelectTxEnding,
+        )
+        .padding(10)
+        .width(140);
+        let tx_framing = combo_box(
+            &self.tx_framing_list,
+            "TX framing",
+            self.selected_tx_framing.as_ref(),
+            Message::SelectTxFraming,
+        )
+        .padding(10)
+        .width(140);
         let tx_utf8 = radio(
-            "UTF-8",
+            self.selected_encoding.unwrap().name(),
             RadioChoice::Utf8,
             self.radio_choice,
             Message::SelectRadio,
@@ -363,13 +5987,284 @@ impl SerialApp {
             self.radio_choice,
             Message::SelectRadio,
         );
+        let tx_base64 = radio(
+            "Base64",
+            RadioChoice::Base64,
+            self.radio_choice,
+            Message::SelectRadio,
+        );
+        let tx_decimal = radio(
+            "DEC",
+            RadioChoice::Decimal,
+            self.radio_choice,
+            Message::SelectRadio,
+        );
         let rx_type = text("Receive as:");
-        let rx_utf8 = checkbox("UTF-8", self.rx_utf8_checked).on_toggle(Message::CheckBoxUTF8);
+        let rx_utf8 = checkbox(self.selected_encoding.unwrap().name(), self.rx_utf8_checked)
+            .on_toggle(Message::CheckBoxUTF8);
+        let encoding = combo_box(
+            &self.encoding_list,
+            "Encoding...",
+            self.selected_encoding.as_ref(),
+            Message::SelectEncoding,
+        )
+        .padding(5)
+        .width(140);
+        let ansi_mode = self.rx_utf8_checked.then(|| {
+            combo_box(
+                &self.ansi_mode_list,
+                "Escapes...",
+                self.selected_ansi_mode.as_ref(),
+                Message::SelectAnsiMode,
+            )
+            .padding(5)
+            .width(140)
+        });
+        let rx_deltas = checkbox("Δt", self.show_deltas).on_toggle(Message::CheckBoxDeltas);
+        let timestamps = combo_box(
+            &self.timestamp_list,
+            "Timestamps",
+            self.selected_timestamp.as_ref(),
+            Message::SelectTimestamp,
+        )
+        .padding(10)
+        .width(160);
+        let split_log =
+            checkbox("Split TX/RX", self.split_log).on_toggle(Message::CheckBoxSplitLog);
         let rx_hex = checkbox("HEX", self.rx_hex_checked).on_toggle(Message::CheckBoxHEX);
         let rx_bin = checkbox("BIN", self.rx_binary_checked).on_toggle(Message::CheckBoxBIN);
+        let rx_dump = checkbox("DUMP", self.rx_dump_checked).on_toggle(Message::CheckBoxDump);
+        let rx_base64 = checkbox("B64", self.rx_base64_checked).on_toggle(Message::CheckBoxBase64);
+        let session_name = text_input("Session name", &self.session_name)
+            .on_input(Message::ChangeSessionName)
+            .padding(10)
+            .width(160);
+        let new_window = button("New Window")
+            .padding(10)
+            .on_press(Message::NewWindow);
+        let profile_list = combo_box(
+            &self.profile_list,
+            "Load profile...",
+            self.selected_profile.as_ref(),
+            Message::SelectProfile,
+        )
+        .padding(10);
+        let import_path = text_input(
+            "PuTTY .reg, minicom or TeraTerm .ini path",
+            &self.import_path,
+        )
+        .on_input(Message::ChangeImportPath)
+        .on_submit(Message::ImportProfiles)
+        .padding(10);
+        let import = button("Import")
+            .padding(10)
+            .on_press(Message::ImportProfiles);
+        let macro_path = text_input("TeraTerm macro (.ttl) path", &self.macro_path)
+            .on_input(Message::ChangeMacroPath)
+            .on_submit(Message::RunMacro)
+            .padding(10);
+        let file_path = text_input(
+            "File to send (text or binary) or receive into",
+            &self.file_path,
+        )
+        .on_input(Message::ChangeFilePath)
+        .on_submit(Message::SendFile)
+        .padding(10);
+        let file_chunk = text_input("Bytes", &self.file_chunk)
+            .on_input(Message::ChangeFileChunk)
+            .padding(10)
+            .width(80);
+        let file_delay = text_input("ms", &self.file_delay_ms)
+            .on_input(Message::ChangeFileDelay)
+            .padding(10)
+            .width(60);
+        let browse_file = button("Browse…")
+            .padding(10)
+            .style(button::secondary)
+            .on_press(Message::BrowseFile(Browse::File));
+        let send_file = button("Send File").padding(10).on_press(Message::SendFile);
+        let file_ack = checkbox("Wait for ACK", self.file_ack).on_toggle(Message::CheckBoxFileAck);
+        let ack_options = (self.file_ack || self.upload.is_some()).then(|| {
+            row![
+                text("ACK:"),
+                text_input("e.g. OK or \\x06", &self.ack_pattern)
+                    .on_input(Message::ChangeAckPattern)
+                    .padding(10)
+                    .width(120),
+                text("Timeout (ms):"),
+                text_input("ms", &self.ack_timeout_ms)
+                    .on_input(Message::ChangeAckTimeout)
+                    .padding(10)
+                    .width(70),
+                text("Retries:"),
+                text_input("", &self.ack_retries)
+                    .on_input(Message::ChangeAckRetries)
+                    .padding(10)
+                    .width(50),
+                checkbox("Chunk by line", self.ack_by_line).on_toggle(Message::CheckBoxAckByLine)
+            ]
+            .spacing(20)
+            .align_y(Center)
+        });
+        let xmodem = row![
+            combo_box(
+                &self.xmodem_list,
+                "Protocol",
+                self.selected_xmodem.as_ref(),
+                Message::SelectXmodem,
+            )
+            .padding(10)
+            .width(140),
+            button(text(format!("{} Send", self.selected_xmodem.unwrap())))
+                .padding(10)
+                .on_press(Message::XmodemSend),
+            button(text(format!("{} Receive", self.selected_xmodem.unwrap())))
+                .padding(10)
+                .on_press(Message::XmodemReceive),
+        ]
+        .spacing(20)
+        .align_y(Center)
+        .push_maybe(
+            self.xmodem
+                .as_ref()
+                .map(|transfer| text(transfer.progress())),
+        )
+        .push_maybe(self.xmodem.is_some().then(|| {
+            button("Stop Transfer")
+                .padding(10)
+                .style(button::danger)
+                .on_press(Message::StopXmodem)
+        }));
+        let upload_status = self.upload.as_ref().map(|upload| {
+            row![
+                text(format!("Chunk {} of {}", upload.done() + 1, upload.len())),
+                button("Stop Upload")
+                    .padding(10)
+                    .style(button::danger)
+                    .on_press(Message::StopUpload)
+            ]
+            .spacing(20)
+            .align_y(Center)
+        });
+        let log_file_path = text_input("Log file (appended to)", &self.log_file_path)
+            .on_input(Message::ChangeLogFilePath)
+            .on_submit(Message::ToggleLogFile)
+            .padding(10);
+        let log_file_toggle = match self.log_file {
+            Some(_) => button("Stop Logging to File")
+                .padding(10)
+                .style(button::danger)
+                .on_press(Message::ToggleLogFile),
+            None => button("Start Logging to File")
+                .padding(10)
+                .on_press(Message::ToggleLogFile),
+        };
+        let macro_toggle = match self.script {
+            Some(_) => button("Stop Macro")
+                .padding(10)
+                .style(button::danger)
+                .on_press(Message::StopMacro),
+            None => button("Run Macro").padding(10).on_press(Message::RunMacro),
+        };
+        let bridge_port = self.bridge_port.as_deref().map(|name| {
+            self.ports
+                .iter()
+                .find(|port| port.name == name)
+                .cloned()
+                .unwrap_or_else(|| PortEntry::bare(name))
+        });
+        let bridge_list = combo_box(
+            &self.bridge_list,
+            "Bridge to port...",
+            bridge_port.as_ref(),
+            |port: PortEntry| Message::SelectBridgePort(port.name),
+        )
+        .padding(10);
+        let mode = if self.tap { "Tap" } else { "Bridge" };
+        let bridge_toggle = match self.bridge {
+            Some(_) => button(text(format!("Stop {mode}")))
+                .padding(10)
+                .style(button::danger)
+                .on_press(Message::ToggleBridge),
+            None => button(mode)
+                .padding(10)
+                .on_press_maybe(self.port.is_some().then_some(Message::ToggleBridge)),
+        };
+        let tap = checkbox("Tap only", self.tap)
+            .on_toggle_maybe(self.bridge.is_none().then_some(Message::CheckBoxTap));
+        let workspace_name = text_input("Workspace name", &self.workspace_name)
+            .on_input(Message::ChangeWorkspaceName)
+            .on_submit(Message::SaveWorkspace);
+        let save_workspace = button("Save Workspace")
+            .padding(10)
+            .on_press(Message::SaveWorkspace);
+        let restore_workspace = button("Restore Workspace")
+            .padding(10)
+            .on_press(Message::RestoreWorkspace);
+        let workspace_list = combo_box(
+            &self.workspace_list,
+            "Open workspace...",
+            None,
+            Message::OpenWorkspace,
+        )
+        .padding(10)
+        .width(180);
+        let export_stats = button("Export Stats")
+            .padding(10)
+            .on_press(Message::ExportStats);
+        let export_log = button("Export Log")
+            .padding(10)
+            .on_press(Message::ExportLog);
+        let capture =
+            checkbox("Capture to DB", self.capture_enabled).on_toggle(Message::CheckBoxCapture);
+        let query_toggle =
+            checkbox("Query captures", self.show_query).on_toggle(Message::CheckBoxQuery);
+        let values_toggle =
+            checkbox("Decode values", self.show_values).on_toggle(Message::CheckBoxValues);
+        let alarms_toggle = checkbox(
+            "Alarms",
+            self.show_alarms || !self.monitor.alarms.is_empty(),
+        )
+        .on_toggle(Message::CheckBoxAlarms);
+        let modbus_toggle =
+            checkbox("Modbus slave", self.simulate_modbus).on_toggle(Message::CheckBoxModbus);
+        let nmea_toggle = checkbox("NMEA", self.show_nmea).on_toggle(Message::CheckBoxNmea);
+        let slcan_toggle = checkbox("SLCAN", self.show_slcan).on_toggle(Message::CheckBoxSlcan);
+        let mavlink_toggle =
+            checkbox("MAVLink", self.show_mavlink).on_toggle(Message::CheckBoxMavlink);
+        let highlights_toggle =
+            checkbox("Highlights", self.show_highlights).on_toggle(Message::CheckBoxHighlights);
+        let stats_on_close = checkbox("Export on close", self.export_stats_on_close)
+            .on_toggle(Message::CheckBoxStatsOnClose);
+        let log_budget = text_input("MB", &self.log_budget_mb)
+            .on_input(Message::ChangeLogBudget)
+            .padding(10)
+            .width(60);
+        // Retention: a ring of the newest entries, with older ones on disk or dropped
+        let log_max_lines = text_input("Max lines", &self.log_max_lines)
+            .on_input(Message::ChangeLogMaxLines)
+            .padding(10)
+            .width(100);
+        let keep_on_disk = checkbox("Keep older on disk", self.log_keep_on_disk)
+            .on_toggle(Message::CheckBoxKeepOnDisk);
+        let log_dropped = (self.log_messages.dropped() > 0)
+            .then(|| text(format!("{} dropped", self.log_messages.dropped())));
+        let log_memory = text(format!(
+            "Log memory: {:.1} MB of",
+            self.log_messages.memory_usage() as f32 / (1024.0 * 1024.0)
+        ));
+        let advanced_toggle =
+            checkbox("Advanced", self.show_advanced).on_toggle(Message::CheckBoxAdvanced);
+        let timeline_toggle =
+            checkbox("Line chart", self.show_timeline).on_toggle(Message::CheckBoxTimeline);
 
         // Buttons
-        let port_toggle = if self.port.is_some() {
+        let port_toggle = if self.reconnect.is_some() {
+            button("Reconnecting... (Cancel)")
+                .padding(10)
+                .style(button::danger)
+                .on_press(Message::ClosePort)
+        } else if self.port.is_some() {
             button("Close Port")
                 .padding(10)
                 .style(button::danger)
@@ -381,56 +6276,1181 @@ impl SerialApp {
             .padding(10)
             .style(button::success)
             .on_press(Message::Send);
+        let repeat = match self.repeat {
+            true => button("Stop Repeat")
+                .padding(10)
+                .style(button::danger)
+                .on_press(Message::ToggleRepeat),
+            false => button("Repeat")
+                .padding(10)
+                .on_press_maybe(self.port.is_some().then_some(Message::ToggleRepeat)),
+        };
+        let repeat_ms = text_input("ms", &self.repeat_ms)
+            .on_input(Message::ChangeRepeatInterval)
+            .padding(10)
+            .width(60);
+        let schedule = match &self.scheduled {
+            Some(scheduled) => row![
+                text(format!(
+                    "Sending in {}: {}",
+                    schedule::countdown(scheduled.at.saturating_duration_since(Instant::now())),
+                    scheduled.command
+                )),
+                button("Cancel")
+                    .padding(10)
+                    .style(button::danger)
+                    .on_press(Message::CancelScheduled)
+            ],
+            None => row![
+                text_input(
+                    match timestamp::clock_is_local() {
+                        true => "30s, 5m or 14:05",
+                        false => "30s, 5m or 14:05 UTC",
+                    },
+                    &self.schedule_input
+                )
+                .on_input(Message::ChangeSchedule)
+                .on_submit(Message::ScheduleSend)
+                .padding(10)
+                .width(150),
+                button("Send Later")
+                    .padding(10)
+                    .on_press(Message::ScheduleSend)
+            ],
+        }
+        .spacing(20)
+        .align_y(Center);
+        let send_progress = self.sending.as_ref().map(|sending| {
+            progress_bar(0.0..=sending.total.max(1) as f32, sending.sent as f32)
+                .width(80)
+                .height(10)
+        });
+        let send_controls = self.sending.as_ref().map(|sending| {
+            let pause = match sending.control.is_paused() {
+                true => button("Resume"),
+                false => button("Pause"),
+            };
+            row![
+                pause.padding(10).on_press(Message::TogglePauseSend),
+                button("Cancel")
+                    .padding(10)
+                    .style(button::danger)
+                    .on_press(Message::CancelSend)
+            ]
+            .spacing(10)
+        });
+        let flush = checkbox("Flush", self.flush_after_send).on_toggle(Message::CheckBoxFlush);
+        let echo = checkbox("Echo", self.verify_echo).on_toggle(Message::CheckBoxEcho);
+        let cts_gate = checkbox("Wait for CTS", self.cts_gate).on_toggle(Message::CheckBoxCtsGate);
+        let send_to_all =
+            checkbox("Send to all", self.send_to_all).on_toggle(Message::CheckBoxSendToAll);
+        let editor_toggle =
+            checkbox("Multi-line", self.show_editor).on_toggle(Message::CheckBoxEditor);
+        let buttons_toggle =
+            checkbox("Edit buttons", self.edit_buttons).on_toggle(Message::CheckBoxEditButtons);
+        // Bytes still sitting in the OS output buffer, waiting to go out on the wire
+        let tx_queued = match &self.sending {
+            Some(sending) if sending.draining => {
+                Some(text(format!("Draining {} B", self.tx_queued)))
+            }
+            _ if self.tx_queued > 0 => Some(text(format!("{} B queued", self.tx_queued))),
+            _ => None,
+        };
+        let reconnect =
+            checkbox("Reconnect", self.auto_reconnect).on_toggle(Message::CheckBoxReconnect);
+        // Windows always opens COM ports exclusively; the choice applies the next time it opens
+        let exclusive = cfg!(unix).then(|| {
+            checkbox("Exclusive", self.exclusive)
+                .on_toggle_maybe(self.port.is_none().then_some(Message::CheckBoxExclusive))
+        });
+        let rts = checkbox("RTS", self.port.is_some() && self.rts)
+            .on_toggle_maybe(self.port.is_some().then_some(Message::CheckBoxRts));
+        let dtr = checkbox("DTR", self.port.is_some() && self.dtr)
+            .on_toggle_maybe(self.port.is_some().then_some(Message::CheckBoxDtr));
+        let send_break = button("Break").padding(10).on_press_maybe(
+            (self.port.is_some() && !self.sending_break).then_some(Message::SendBreak),
+        );
+        let break_ms = text_input("ms", &self.break_ms)
+            .on_input(Message::ChangeBreakMs)
+            .on_submit(Message::SendBreak)
+            .padding(10)
+            .width(60);
+        // Input line status while the port is open
+        let modem_lines = self.port.as_ref().map(|_| {
+            row(["CTS", "DSR", "RI", "CD"]
+                .into_iter()
+                .zip(self.modem_lines)
+                .map(|(name, level)| line_indicator(name, level)))
+            .spacing(10)
+            .align_y(Center)
+        });
         let recv_toggle = {
             match &self.recv_state {
                 RecvState::Idle => button("Start Listener")
                     .padding(10)
                     .style(button::success)
-                    .on_press(Message::ToggleListener),
+                    .on_press_maybe(self.bridge.is_none().then_some(Message::ToggleListener)),
                 RecvState::Listening => button("Stop Listener")
                     .padding(10)
                     .style(button::danger)
                     .on_press(Message::ToggleListener),
             }
         };
-        // Log
-        let mut log_column = column![];
-        for i in &self.log_messages {
-            log_column = log_column.push(i.as_str());
-        }
-        let log = container(
-            scrollable(log_column)
-                .anchor_bottom()
-                .width(Fill)
-                .height(Fill),
+        let error_count = self.log_messages.error_count();
+        let prev_error = button("< Error")
+            .padding(5)
+            .on_press_maybe((error_count > 0).then_some(Message::PrevError));
+        let next_error = button("Error >")
+            .padding(5)
+            .on_press_maybe((error_count > 0).then_some(Message::NextError));
+        let errors = text(format!("{error_count} errors"));
+        let marker_label = text_input("Marker label", &self.marker_label)
+            .on_input(Message::ChangeMarkerLabel)
+            .on_submit(Message::AddMarker)
+            .width(150);
+        let add_marker = button("Mark").padding(5).on_press(Message::AddMarker);
+        let marker_count = self.log_messages.marker_count();
+        let prev_marker = button("< Mark")
+            .padding(5)
+            .on_press_maybe((marker_count > 0).then_some(Message::PrevMarker));
+        let next_marker = button("Mark >")
+            .padding(5)
+            .on_press_maybe((marker_count > 0).then_some(Message::NextMarker));
+        let go_to = text_input("Go to line/@offset", &self.go_to)
+            .on_input(Message::ChangeGoTo)
+            .on_submit(Message::GoTo)
+            .width(150);
+        // Freezes the log in place; it also pauses on scrolling up, and resumes at the bottom
+        let (pause_scroll, paused) = match self.frozen {
+            Some(frozen) => (
+                button("Resume Scroll")
+                    .padding(5)
+                    .style(button::success)
+                    .on_press(Message::ToggleScrollPause),
+                Some(text(format!(
+                    "Paused, {} new lines",
+                    self.log_messages.len().saturating_sub(frozen)
+                ))),
+            ),
+            None => (
+                button("Pause Scroll")
+                    .padding(5)
+                    .on_press(Message::ToggleScrollPause),
+                None,
+            ),
+        };
+        // Ctrl+L; the log file keeps everything, and the last clear can be undone
+        let clear_log = button("Clear")
+            .padding(5)
+            .style(button::danger)
+            .on_press(Message::ClearLog);
+        let copy = button("Copy")
+            .padding(5)
+            .on_press_maybe(self.selection.map(|_| Message::CopySelection));
+        let copy_all = button("Copy All").padding(5).on_press(Message::CopyAll);
+        let undo_clear = self
+            .cleared
+            .as_ref()
+            .map(|_| button("Undo Clear").padding(5).on_press(Message::UndoClear));
+        let rx_buffer = self.reader.as_ref().map(|reader| {
+            let (queued, capacity) = reader.backlog();
+            let (dropped, bytes) = reader.dropped();
+            match self.selected_overflow_policy {
+                Some(OverflowPolicy::Backpressure) => text(format!(
+                    "RX buffer {queued}/{capacity}, paused {} times",
+                    reader.stalls()
+                )),
+                _ => text(format!(
+                    "RX buffer {queued}/{capacity}, {dropped} dropped ({bytes} B)"
+                )),
+            }
+        });
+        let framing = combo_box(
+            &self.framing_list,
+            "Framing",
+            self.selected_framing.as_ref(),
+            Message::SelectFraming,
         )
         .padding(10)
-        .style(|theme: &Theme| container::Style {
-            border: Border {
-                color: theme.palette().success,
-                width: 1.0,
-                radius: Radius::new(3.0),
-            },
-            ..container::Style::default()
+        .width(130);
+        // e.g. 7E, ETX or \r\n>; applied on Enter
+        let frame_delimiter = (self.selected_framing == Some(Framing::Delimiter)).then(|| {
+            text_input("7E, ETX or \\r\\n>", &self.frame_delimiter)
+                .on_input(Message::ChangeFrameDelimiter)
+                .on_submit(Message::SetFrameDelimiter)
+                .padding(10)
+                .width(100)
+        });
+        // Applied on Enter
+        let frame_gap = (self.selected_framing == Some(Framing::IdleGap)).then(|| {
+            text_input("Gap ms", &self.frame_gap_ms)
+                .on_input(Message::ChangeFrameGap)
+                .on_submit(Message::SetFrameGap)
+                .padding(10)
+                .width(80)
+        });
+        let frame_length = (self.selected_framing == Some(Framing::FixedLength)).then(|| {
+            text_input("Bytes", &self.frame_length)
+                .on_input(Message::ChangeFrameLength)
+                .on_submit(Message::SetFrameLength)
+                .padding(10)
+                .width(80)
         });
+        let overflow_policy = combo_box(
+            &self.overflow_policy_list,
+            "On overflow",
+            self.selected_overflow_policy.as_ref(),
+            Message::SelectOverflowPolicy,
+        )
+        .padding(10)
+        .width(150);
+        // Log
+        let spilled = self.log_messages.spilled();
+        let mut log_column = column![];
+        if let Some(page) = self.log_messages.page() {
+            log_column = log_column.push(
+                row![
+                    text(format!(
+                        "Entries {}-{} of {spilled} on disk",
+                        page.start + 1,
+                        page.start + page.entries.len()
+                    )),
+                    button("Older").padding(5).on_press_maybe(
+                        (page.start > self.log_messages.dropped()).then_some(Message::PageOlder),
+                    ),
+                    button("Newer").padding(5).on_press(Message::PageNewer),
+                    button("Hide").padding(5).on_press(Message::ClosePage),
+                ]
+                .spacing(10)
+                .align_y(Center),
+            );
+        } else if spilled > 0 {
+            log_column = log_column.push(
+                button(text(format!("Show older entries ({spilled} on disk)")))
+                    .padding(5)
+                    .on_press(Message::PageOlder),
+            );
+        }
+        // Sends get a pane of their own while the log is split
+        let (rows, mut hit_count, mut hit_entries) = self.log_rows(self.main_pane());
+        let mut shown = rows.len();
+        if self.split_log {
+            log_column = log_column.push(text("Received").size(12));
+        }
+        log_column = log_column.extend(self.lay_out(rows, self.log_viewport));
+        let mut tx_column = None;
+        if self.split_log {
+            let (rows, hits, entries) = self.log_rows(Pane::Tx);
+            hit_count += hits;
+            hit_entries += entries;
+            shown += rows.len();
+            tx_column = Some(
+                column![text("Sent").size(12)].extend(self.lay_out(rows, self.tx_log_viewport)),
+            );
+        }
+        // Ctrl+F; Enter or F3 goes to the next match, Shift+F3 the previous one
+        let search = text_input("Search log (Ctrl+F)", &self.search_query)
+            .id(self.search_id.clone())
+            .on_input(Message::ChangeSearch)
+            .on_submit(Message::NextMatch)
+            .width(250);
+        let search_ignore_case = checkbox("Ignore case", self.search_ignore_case)
+            .on_toggle(Message::CheckBoxSearchIgnoreCase);
+        let search_regex =
+            checkbox("Regex", self.search_regex).on_toggle(Message::CheckBoxSearchRegex);
+        let prev_match = button("< Match")
+            .padding(5)
+            .on_press_maybe((hit_count > 0).then_some(Message::PrevMatch));
+        let next_match = button("Match >")
+            .padding(5)
+            .on_press_maybe((hit_count > 0).then_some(Message::NextMatch));
+        let search_status = match (&self.search_error, &self.search) {
+            (Some(e), _) => Some(text(format!("Bad pattern: {e}")).style(text::danger)),
+            (None, Some(_)) => Some(text(format!("{hit_count} matches in {hit_entries} lines"))),
+            (None, None) => None,
+        };
+        // Hides entries without a match, or with one when inverted
+        let filter = text_input("Filter log", &self.filter_query)
+            .on_input(Message::ChangeFilter)
+            .width(200);
+        let filter_ignore_case = checkbox("Ignore case", self.filter_ignore_case)
+            .on_toggle(Message::CheckBoxFilterIgnoreCase);
+        let filter_regex =
+            checkbox("Regex", self.filter_regex).on_toggle(Message::CheckBoxFilterRegex);
+        let filter_invert =
+            checkbox("Invert", self.filter_invert).on_toggle(Message::CheckBoxFilterInvert);
+        // Device traffic, or the app's own notices such as "Listener started"
+        let show_traffic =
+            checkbox("Traffic", self.show_traffic).on_toggle(Message::CheckBoxShowTraffic);
+        let show_system =
+            checkbox("System", self.show_system).on_toggle(Message::CheckBoxShowSystem);
+        let filter_status = match (&self.filter_error, &self.filter) {
+            (Some(e), _) => Some(text(format!("Bad pattern: {e}")).style(text::danger)),
+            (None, Some(_)) => Some(text(format!(
+                "{shown} of {} lines shown",
+                self.visible_indices().count()
+            ))),
+            (None, None) => None,
+        };
+        let search_row = row![
+            search,
+            search_ignore_case,
+            search_regex,
+            prev_match,
+            next_match
+        ]
+        .push_maybe(search_status)
+        .push(horizontal_space())
+        .push(filter)
+        .push(filter_ignore_case)
+        .push(filter_regex)
+        .push(filter_invert)
+        .push(show_traffic)
+        .push(show_system)
+        .push_maybe(filter_status)
+        .spacing(20)
+        .align_y(Center);
+        let log = log_frame(log_column, self.log_id.clone(), Message::LogScrolled);
+        let log = match tx_column {
+            Some(tx_column) => row![
+                log_frame(tx_column, self.tx_log_id.clone(), Message::TxLogScrolled),
+                log
+            ]
+            .spacing(10),
+            None => row![log],
+        }
+        .push_maybe(self.show_nmea.then(|| self.nmea_view()))
+        .push_maybe(self.show_mavlink.then(|| self.mavlink_view()));
         // Layout
         container(
-            column![
-                row![port_list, port_toggle, recv_toggle].spacing(20),
-                row![baud_rate, data_bits, parity, stop_bits].spacing(20),
-                row![rx_type, rx_hex, rx_bin, rx_utf8].spacing(20),
-                row![log],
-                row![tx_type, tx_utf8, tx_hex].spacing(20),
-                row![command, send].spacing(20),
-                row![theme_list].spacing(20),
-            ]
-            .spacing(20),
+            column![]
+                .push_maybe(self.palette_view())
+                .push_maybe(self.zmodem_offer_view())
+                .push(
+                    row![
+                        port_list,
+                        rescan,
+                        endpoint_path,
+                        port_toggle,
+                        recv_toggle,
+                        rts,
+                        dtr,
+                        reconnect
+                    ]
+                    .push_maybe(modem_lines)
+                    .spacing(20)
+                    .align_y(Center),
+                )
+                .push(
+                    row![
+                        baud_rate,
+                        custom_baud_rate,
+                        data_bits,
+                        parity,
+                        stop_bits,
+                        flow_control
+                    ]
+                    .push_maybe(exclusive)
+                    .spacing(20)
+                    .align_y(Center),
+                )
+                .push(
+                    row![rx_type, rx_hex, rx_bin, rx_dump, rx_base64, rx_utf8]
+                        .push_maybe(ansi_mode)
+                        .push(rx_deltas)
+                        .push(timestamps)
+                        .push(split_log)
+                        .push(framing)
+                        .push_maybe(frame_delimiter)
+                        .push_maybe(frame_gap)
+                        .push_maybe(frame_length)
+                        .push(horizontal_space())
+                        .push_maybe(rx_buffer)
+                        .push(errors)
+                        .push(prev_error)
+                        .push(next_error)
+                        .push(go_to)
+                        .push(marker_label)
+                        .push(add_marker)
+                        .push(prev_marker)
+                        .push(next_marker)
+                        .push_maybe(paused)
+                        .push(pause_scroll)
+                        .push(copy)
+                        .push(copy_all)
+                        .push(clear_log)
+                        .push_maybe(undo_clear)
+                        .spacing(20)
+                        .align_y(Center),
+                )
+                .push(search_row)
+                .push(log)
+                .push_maybe(self.show_timeline.then(|| self.timeline_view()))
+                .push_maybe(self.show_query.then(|| self.query_view()))
+                .push_maybe(self.show_values.then(|| self.values_view()))
+                .push_maybe(self.show_highlights.then(|| self.highlights_view()))
+                .push_maybe(self.simulate_modbus.then(|| self.modbus_view()))
+                .push_maybe(self.show_slcan.then(|| self.slcan_view()))
+                .push_maybe(self.show_advanced.then(|| self.advanced_view()))
+                .push_maybe(
+                    (self.show_alarms || !self.monitor.alarms.is_empty())
+                        .then(|| self.alarms_view()),
+                )
+                .push(
+                    row![
+                        tx_type,
+                        tx_utf8,
+                        tx_hex,
+                        tx_base64,
+                        tx_decimal,
+                        text("Text encoding:"),
+                        encoding,
+                        tx_ending,
+                        tx_escapes,
+                        tx_framing,
+                        editor_toggle,
+                        buttons_toggle
+                    ]
+                    .spacing(20)
+                    .align_y(Center),
+                )
+                .push(
+                    row![
+                        command,
+                        command_size,
+                        history,
+                        send,
+                        repeat,
+                        repeat_ms,
+                        flush,
+                        echo,
+                        cts_gate,
+                        send_to_all,
+                        send_break,
+                        break_ms
+                    ]
+                    .push_maybe(send_progress)
+                    .push_maybe(send_controls)
+                    .push_maybe(tx_queued)
+                    .spacing(20)
+                    .align_y(Center),
+                )
+                .push(schedule)
+                .push_maybe((!self.tx_queue.is_empty()).then(|| self.queue_view()))
+                .push_maybe(self.show_editor.then(|| self.editor_view()))
+                .push_maybe(
+                    (self.edit_buttons || !self.quick_buttons.is_empty())
+                        .then(|| self.buttons_view()),
+                )
+                .push(
+                    row![
+                        session_name,
+                        new_window,
+                        theme_list,
+                        overflow_policy,
+                        timeline_toggle,
+                        export_stats,
+                        export_log,
+                        stats_on_close,
+                        capture,
+                        query_toggle,
+                        values_toggle,
+                        alarms_toggle,
+                        highlights_toggle,
+                        modbus_toggle,
+                        nmea_toggle,
+                        mavlink_toggle,
+                        slcan_toggle,
+                        advanced_toggle,
+                        log_memory,
+                        log_budget,
+                        log_max_lines,
+                        keep_on_disk
+                    ]
+                    .push_maybe(log_dropped)
+                    .spacing(20)
+                    .align_y(Center),
+                )
+                .push(
+                    row![
+                        workspace_name,
+                        save_workspace,
+                        restore_workspace,
+                        workspace_list
+                    ]
+                    .spacing(20)
+                    .align_y(Center),
+                )
+                .push(
+                    row![profile_list, import_path, import]
+                        .spacing(20)
+                        .align_y(Center),
+                )
+                .push(row![macro_path, macro_toggle].spacing(20).align_y(Center))
+                .push(
+                    row![log_file_path, log_file_toggle]
+                        .spacing(20)
+                        .align_y(Center),
+                )
+                .push(
+                    row![
+                        file_path,
+                        browse_file,
+                        text("Chunk:"),
+                        file_chunk,
+                        text("Delay:"),
+                        file_delay,
+                        send_file,
+                        file_ack
+                    ]
+                    .spacing(20)
+                    .align_y(Center),
+                )
+                .push_maybe(ack_options.map(|options| options.push_maybe(upload_status)))
+                .push(xmodem)
+                .push(
+                    row![bridge_list, bridge_toggle, tap]
+                        .spacing(20)
+                        .align_y(Center),
+                )
+                .push(self.status_bar())
+                .spacing(20),
         )
         .padding(20)
         .into()
     }
+    // Display Lines for an Entry, formatting received data in every checked representation
+    fn entry_lines<'a>(&self, entry: &'a LogEntry) -> Vec<Cow<'a, str>> {
+        let Some(data) = &entry.data else {
+            return vec![Cow::Borrowed(entry.text.as_str())];
+        };
+        let b = data.len();
+        // Bridged traffic is labelled with the way it went
+        let label = match entry.text.is_empty() {
+            true => format!("Received {b} bytes"),
+            false => format!("{} ({b} bytes)", entry.text),
+        };
+        // Long bursts show a preview until clicked; the entry itself keeps every byte
+        let (shown, more) = match entry.expanded || b <= PREVIEW_BYTES {
+            true => (&data[..], String::new()),
+            false => (
+                &data[..PREVIEW_BYTES],
+                format!(" … {} more bytes (click to expand)", b - PREVIEW_BYTES),
+            ),
+        };
+        let mut lines = Vec::new();
+        if self.rx_hex_checked {
+            lines.push(Cow::Owned(format!("{label}: {}{more}", format::hex(shown))));
+        }
+        // The rows only line up in a monospace font, which the log switches to while it's on
+        if self.rx_dump_checked {
+            lines.push(Cow::Owned(format!("{label}:{more}")));
+            lines.extend(format::hexdump(shown).into_iter().map(Cow::Owned));
+        }
+        if self.rx_base64_checked {
+            lines.push(Cow::Owned(format!(
+                "{label}: {}{more}",
+                format::base64(shown)
+            )));
+        }
+        if self.rx_binary_checked {
+            lines.push(Cow::Owned(format!(
+                "{label}: {}{more}",
+                format::binary(shown)
+            )));
+        }
+        if self.rx_utf8_checked {
+            let decoded = self.selected_encoding.unwrap().decode(shown);
+            let decoded = match self.selected_ansi_mode {
+                Some(AnsiMode::Strip) => Cow::Owned(ansi::strip(&decoded)),
+                _ => decoded,
+            };
+            lines.push(Cow::Owned(format!("{label}: {decoded}{more}")));
+        }
+        // Values come from the whole frame, even while only a preview is shown
+        if let Some(layout) = &self.layout {
+            lines.push(Cow::Owned(format!(
+                "{label} as values: {}",
+                layout.decode(data)
+            )));
+        }
+        lines
+    }
+    // Log Panes
+    fn main_pane(&self) -> Pane {
+        match self.split_log {
+            true => Pane::Rx,
+            false => Pane::All,
+        }
+    }
+    fn pane_for(&self, entry: &LogEntry) -> Pane {
+        match entry.source() {
+            Source::Tx if self.split_log => Pane::Tx,
+            _ => self.main_pane(),
+        }
+    }
+    // Lazy Rendering: every entry shown is measured, but only those near the viewport are
+    // laid out, with blank space standing in for the rest. Entries are only formatted up
+    // front while a search or filter needs their text. Search hits are counted on the way,
+    // along with the entries holding one.
+    fn log_rows(&self, pane: Pane) -> (Vec<LogRow<'_>>, usize, usize) {
+        let page_entries = self
+            .log_messages
+            .page()
+            .map(|page| page.entries.as_slice())
+            .unwrap_or_default();
+        let entries = page_entries
+            .iter()
+            .chain(self.log_messages.entries())
+            .zip(self.visible_indices());
+        let searching = self.search.is_some() || self.filter.is_some();
+        let mut cache = self.match_cache.borrow_mut();
+        if searching {
+            let key = self.match_key();
+            if cache.key.as_ref() != Some(&key) {
+                cache.entries.clear();
+                cache.key = Some(key);
+            }
+        }
+        let mut previous = Duration::ZERO;
+        let (mut hit_count, mut hit_entries) = (0, 0);
+        let mut rows = Vec::new();
+        for (entry, index) in entries {
+            if self.frozen.is_some_and(|frozen| index >= frozen) {
+                break;
+            }
+            // Received entries show the time since the previous one when deltas are on
+            let before = previous;
+            if let Some(time) = entry.time
+                && self.show_deltas
+            {
+                previous = time;
+            }
+            if !pane.shows(entry) || !self.shows_category(entry) {
+                continue;
+            }
+            let height = match searching {
+                true => {
+                    let matched = self.matched(&mut cache, entry, index);
+                    if !matched.shown {
+                        continue;
+                    }
+                    hit_count += matched.hits;
+                    hit_entries += usize::from(matched.hits > 0);
+                    matched.lines * copies(entry)
+                }
+                false => self.entry_rows(entry),
+            };
+            if height == 0 {
+                continue;
+            }
+            rows.push(LogRow {
+                entry,
+                index,
+                previous: before,
+                height,
+            });
+        }
+        // Entries dropped from memory, or paged back out, are forgotten
+        let first = self.log_messages.first_in_memory();
+        let page = self
+            .log_messages
+            .page()
+            .map_or(0..0, |page| page.start..page.start + page.entries.len());
+        if cache.entries.len() > 2 * (self.log_messages.len() - first + page.len()) {
+            cache
+                .entries
+                .retain(|index, _| *index >= first || page.contains(index));
+        }
+        (rows, hit_count, hit_entries)
+    }
+    // The rows near the viewport with space above and below for the rest. The log is anchored
+    // to the bottom, so the offset is the distance up from it; a screen either side is laid
+    // out as well.
+    fn lay_out<'a>(
+        &'a self,
+        rows: Vec<LogRow<'a>>,
+        viewport: (f32, f32),
+    ) -> Vec<Element<'a, Message>> {
+        let opened = self.port.is_some().then(|| self.session_stats.opened_at());
+        let total = rows.iter().map(|row| row.height).sum::<usize>();
+        let (from_bottom, height) = viewport;
+        let screen = (height / LOG_ROW_HEIGHT).ceil() as usize;
+        let (top, bottom) = match rows.len() > LAZY_ENTRIES {
+            true => {
+                let bottom = total.saturating_sub((from_bottom / LOG_ROW_HEIGHT) as usize) + screen;
+                (bottom.saturating_sub(3 * screen), bottom)
+            }
+            false => (0, usize::MAX),
+        };
+        let (mut above, mut below, mut at) = (0, 0, 0);
+        let mut laid_out = Vec::new();
+        for row in rows {
+            let end = at + row.height;
+            if end <= top {
+                above += row.height;
+            } else if at >= bottom {
+                below += row.height;
+            } else {
+                laid_out.push(self.log_row(row, opened));
+            }
+            at = end;
+        }
+        let mut elements = vec![
+            vertical_space()
+                .height(above as f32 * LOG_ROW_HEIGHT)
+                .into(),
+        ];
+        elements.extend(laid_out);
+        elements.push(
+            vertical_space()
+                .height(below as f32 * LOG_ROW_HEIGHT)
+                .into(),
+        );
+        elements
+    }
+    // Display lines an entry takes up, worked out without formatting it
+    fn entry_rows(&self, entry: &LogEntry) -> usize {
+        let Some(data) = &entry.data else {
+            return copies(entry);
+        };
+        let shown = match entry.expanded || data.len() <= PREVIEW_BYTES {
+            true => &data[..],
+            false => &data[..PREVIEW_BYTES],
+        };
+        let mut rows = [
+            self.rx_hex_checked,
+            self.rx_base64_checked,
+            self.rx_binary_checked,
+            self.layout.is_some(),
+        ]
+        .into_iter()
+        .filter(|checked| *checked)
+        .count();
+        if self.rx_dump_checked {
+            rows += 1 + shown.len().div_ceil(16);
+        }
+        // Decoded text breaks wherever it holds a line feed
+        if self.rx_utf8_checked {
+            rows += 1 + shown.iter().filter(|&&byte| byte == b'\n').count();
+        }
+        rows * copies(entry)
+    }
+    // One entry of the log, its lines formatted now unless a search or filter already did
+    fn log_row<'a>(&self, row: LogRow<'a>, opened: Option<SystemTime>) -> Element<'a, Message> {
+        let LogRow {
+            entry,
+            index: i,
+            mut previous,
+            ..
+        } = row;
+        let colors = self.selected_ansi_mode == Some(AnsiMode::Color);
+        let font = match self.rx_dump_checked {
+            true => Font::MONOSPACE,
+            false => Font::DEFAULT,
+        };
+        // Only the rows being laid out have their hits found again, for the highlights
+        let lines = self
+            .entry_lines(entry)
+            .into_iter()
+            .map(|line| {
+                let hits = self.line_hits(&line);
+                (line, hits)
+            })
+            .collect::<Vec<_>>();
+        // The time prefixes aren't searched
+        let prefix = self.line_prefix(entry, &mut previous, opened);
+        // Sends, received data, errors and markers each in their own color
+        let tint: fn(&Theme) -> text::Style = match (entry.level, entry.source()) {
+            (LogLevel::Error, _) => text::danger,
+            (LogLevel::Marker, _) => text::secondary,
+            (_, Source::Tx) => text::primary,
+            (_, Source::Rx) => text::success,
+            (_, Source::System) => text::default,
+        };
+        let long = entry
+            .data
+            .as_ref()
+            .is_some_and(|data| data.len() > PREVIEW_BYTES);
+        let line: Element<'_, Message> = if entry.repeats == 1 && !long {
+            column(lines.into_iter().map(|(line, hits)| {
+                let highlight = self.highlight(entry, &line);
+                log_line(prefix.clone(), line, &hits, tint, highlight, colors, font)
+            }))
+            .into()
+        } else if entry.repeats == 1 {
+            mouse_area(column(lines.into_iter().map(|(line, hits)| {
+                let highlight = self.highlight(entry, &line);
+                log_line(prefix.clone(), line, &hits, tint, highlight, colors, font)
+            })))
+            .on_press(Message::ToggleFold(i))
+            .into()
+        } else if entry.expanded {
+            mouse_area(column((0..entry.repeats).flat_map(|_| {
+                lines.iter().map(|(line, hits)| {
+                    let highlight = self.highlight(entry, line);
+                    log_line(
+                        prefix.clone(),
+                        line.clone(),
+                        hits,
+                        tint,
+                        highlight,
+                        colors,
+                        font,
+                    )
+                })
+            })))
+            .on_press(Message::ToggleFold(i))
+            .into()
+        } else {
+            let repeats = entry.repeats;
+            mouse_area(column(lines.into_iter().map(|(line, hits)| {
+                let highlight = self.highlight(entry, &line);
+                let line = Cow::Owned(format!("{line} (x{repeats})"));
+                log_line(prefix.clone(), line, &hits, tint, highlight, colors, font)
+            })))
+            .on_press(Message::ToggleFold(i))
+            .into()
+        };
+        // Clicking the line number selects it for copying
+        let number = mouse_area(text(i + 1).width(60)).on_press(Message::SelectLine(i));
+        let line = row![number, line];
+        let selected = self
+            .selection
+            .is_some_and(|(anchor, end)| (anchor.min(end)..=anchor.max(end)).contains(&i));
+        match selected || self.selected_entry == Some(i) {
+            true => container(line)
+                .width(Fill)
+                .style(container::rounded_box)
+                .into(),
+            false => line.into(),
+        }
+    }
+    // Status Bar: the counters of the current connection, or the last one until the port is
+    // opened again
+    fn status_bar(&self) -> Element<'_, Message> {
+        let stats = &self.session_stats;
+        let connected = match self.port {
+            Some(_) => {
+                let seconds = stats.elapsed().as_secs();
+                format!(
+                    "Connected {}:{:02}:{:02}",
+                    seconds / 3600,
+                    seconds / 60 % 60,
+                    seconds % 60
+                )
+            }
+            None => String::from("Not connected"),
+        };
+        let errors = text(format!("Errors: {}", stats.errors)).style(match stats.errors {
+            0 => text::default,
+            _ => text::danger,
+        });
+        row![
+            text(connected),
+            text(format!("TX: {}", byte_count(stats.bytes_tx))),
+            text(format!("RX: {}", byte_count(stats.bytes_rx))),
+            text(format!("Frames: {}", stats.lines_rx)),
+            errors
+        ]
+        .spacing(20)
+        .align_y(Center)
+        .into()
+    }
+    // Control Line Chart
+    fn timeline_view(&self) -> Element<'_, Message> {
+        const COLUMNS: usize = 100;
+        let now = Instant::now();
+        let mut lanes = column![
+            text(format!(
+                "Last {} s (t = {:.1} s)",
+                WINDOW.as_secs(),
+                self.line_timeline.elapsed(now).as_secs_f32()
+            ))
+            .size(12)
+        ]
+        .spacing(4);
+        for line in Line::ALL {
+            let samples = self.line_timeline.samples(line, now, WINDOW, COLUMNS);
+            lanes = lanes.push(timeline_lane(line.to_string(), samples, |theme| {
+                theme.palette().primary
+            }));
+        }
+        let activity = self.line_timeline.activity_samples(now, WINDOW, COLUMNS);
+        lanes = lanes.push(timeline_lane("DATA".to_string(), activity, |theme| {
+            theme.palette().success
+        }));
+        container(lanes)
+            .padding(10)
+            .width(Fill)
+            .style(container::bordered_box)
+            .into()
+    }
     // Initial Theme
     fn theme(&self) -> Theme {
         self.selected_theme.as_ref().unwrap().clone()
     }
 }
+// Status Lamp for an Input Line: lit while high, hollow while low, dim when unknown
+fn line_indicator<'a>(name: &'a str, level: Option<bool>) -> Element<'a, Message> {
+    let lamp = container(horizontal_space())
+        .width(10)
+        .height(10)
+        .style(move |theme: &Theme| {
+            let palette = theme.palette();
+            let color = match level {
+                Some(_) => palette.success,
+                None => palette.text.scale_alpha(0.3),
+            };
+            container::Style {
+                background: (level == Some(true)).then(|| color.into()),
+                border: Border {
+                    color,
+                    width: 1.0,
+                    radius: Radius::new(5.0),
+                },
+                ..container::Style::default()
+            }
+        });
+    row![lamp, text(name).size(12)]
+        .spacing(4)
+        .align_y(Center)
+        .into()
+}
+// Which entries a log pane shows: everything, or sends and the rest apart
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Pane {
+    All,
+    Tx,
+    Rx,
+}
+
+impl Pane {
+    fn shows(self, entry: &LogEntry) -> bool {
+        match self {
+            Pane::All => true,
+            Pane::Tx => entry.source() == Source::Tx,
+            Pane::Rx => entry.source() != Source::Tx,
+        }
+    }
+}
+// The native file picker, opened where `current` points; the chosen path, several joined by
+// ';' like they're typed, or nothing when it's cancelled
+async fn browse_file(browse: Browse, current: String) -> Option<String> {
+    let current = PathBuf::from(current.split(';').next().unwrap_or_default().trim());
+    let mut dialog = rfd::AsyncFileDialog::new();
+    if let Some(directory) = [Some(current.as_path()), current.parent()]
+        .into_iter()
+        .flatten()
+        .find(|path| path.is_dir())
+    {
+        dialog = dialog.set_directory(directory);
+    }
+    let paths = match browse {
+        Browse::File => vec![dialog.set_title("File to send").pick_file().await?],
+        Browse::Files => dialog.set_title("Files to send").pick_files().await?,
+        Browse::Folder => vec![dialog.set_title("Receive into").pick_folder().await?],
+    };
+    Some(
+        paths
+            .iter()
+            .map(|file| file.path().display().to_string())
+            .collect::<Vec<_>>()
+            .join(";"),
+    )
+}
+// Saved highlight rules ready to match, any that no longer parse left out
+fn compile_highlights(rules: &[HighlightRule]) -> Vec<(Search, Color)> {
+    rules
+        .iter()
+        .filter_map(|rule| {
+            let search = Search::new(&rule.pattern, true, false).ok()?;
+            let [r, g, b] = highlights::parse_color(&rule.color)?;
+            Some((search, Color::from_rgb8(r, g, b)))
+        })
+        .collect()
+}
+// A byte count in B, KB or MB, e.g. 1536 to "1.5 KB"
+fn byte_count(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{bytes} B"),
+        1024..1_048_576 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+// A bordered log pane, anchored to the bottom so it follows new entries
+fn log_frame<'a>(
+    content: Column<'a, Message>,
+    id: scrollable::Id,
+    on_scroll: fn(scrollable::Viewport) -> Message,
+) -> Element<'a, Message> {
+    container(
+        scrollable(content)
+            .id(id)
+            .anchor_bottom()
+            .on_scroll(on_scroll)
+            .width(Fill)
+            .height(Fill),
+    )
+    .padding(10)
+    .style(|theme: &Theme| container::Style {
+        border: Border {
+            color: theme.palette().success,
+            width: 1.0,
+            radius: Radius::new(3.0),
+        },
+        ..container::Style::default()
+    })
+    .into()
+}
+// A log entry that's shown, measured in display lines
+struct LogRow<'a> {
+    entry: &'a LogEntry,
+    index: usize,
+    // Time of the received entry before it, for Δt
+    previous: Duration,
+    height: usize,
+}
+// Search and Filter Results per Entry, by index, valid while the key matches
+#[derive(Default)]
+struct MatchCache {
+    key: Option<MatchKey>,
+    entries: HashMap<usize, Matched>,
+}
+#[derive(PartialEq)]
+struct MatchKey {
+    formats: [bool; 5],
+    encoding: Option<TextEncoding>,
+    ansi_mode: Option<AnsiMode>,
+    layout: Option<Layout>,
+    search: (String, bool, bool),
+    filter: (String, bool, bool, bool),
+}
+#[derive(Clone, Copy)]
+struct Matched {
+    // What the entry was like when it was scanned, so a change shows
+    expanded: bool,
+    size: usize,
+    lines: usize,
+    shown: bool,
+    hits: usize,
+}
+// Times a folded entry's lines are shown: once, or every repeat while it's expanded
+fn copies(entry: &LogEntry) -> usize {
+    match entry.expanded {
+        true => entry.repeats,
+        false => 1,
+    }
+}
+// A search or filter pattern, or why it doesn't compile; nothing while it's empty
+fn compile_pattern(
+    query: &str,
+    regex: bool,
+    ignore_case: bool,
+) -> (Option<Search>, Option<String>) {
+    if query.is_empty() {
+        return (None, None);
+    }
+    match Search::new(query, regex, ignore_case) {
+        Ok(search) => (Some(search), None),
+        Err(e) => (None, Some(e)),
+    }
+}
+// Background of search hits in the log
+const HIGHLIGHT: Color = Color::from_rgba(1.0, 0.8, 0.0, 0.45);
+
+// One line of the log after its time `prefix`, in the entry's `tint` unless ANSI SGR sequences
+// in it say otherwise while `colors` is on, with search `hits` (byte ranges of the line as
+// displayed) highlighted
+fn log_line<'a>(
+    prefix: String,
+    line: Cow<'a, str>,
+    hits: &[Range<usize>],
+    tint: fn(&Theme) -> text::Style,
+    highlight: Option<Color>,
+    colors: bool,
+    font: Font,
+) -> Element<'a, Message> {
+    let colored = colors && line.contains('\x1b');
+    if !colored && hits.is_empty() {
+        return match prefix.is_empty() {
+            true => text(line)
+                .font(font)
+                .style(tint)
+                .color_maybe(highlight)
+                .into(),
+            false => text(format!("{prefix}{line}"))
+                .font(font)
+                .style(tint)
+                .color_maybe(highlight)
+                .into(),
+        };
+    }
+    let runs = match colored {
+        true => ansi::runs(&line),
+        false => vec![(line.into_owned(), ansi::Style::default())],
+    };
+    let mut spans = vec![span(prefix).font(font)];
+    let mut offset = 0;
+    for (run, style) in runs {
+        let font = match style.bold {
+            true => Font {
+                weight: iced::font::Weight::Bold,
+                ..font
+            },
+            false => font,
+        };
+        let styled = |piece: &str, hit: bool| {
+            span(piece.to_string())
+                .color_maybe(style.color)
+                .background_maybe(if hit {
+                    Some(HIGHLIGHT)
+                } else {
+                    style.background
+                })
+                .font(font)
+                .underline(style.underline)
+        };
+        // Split the run wherever a hit starts or ends
+        let end = offset + run.len();
+        let mut cut = offset;
+        for hit in hits
+            .iter()
+            .filter(|hit| hit.start < end && hit.end > offset)
+        {
+            let start = hit.start.max(cut);
+            if start > cut {
+                spans.push(styled(&run[cut - offset..start - offset], false));
+            }
+            cut = hit.end.min(end);
+            spans.push(styled(&run[start - offset..cut - offset], true));
+        }
+        if cut < end {
+            spans.push(styled(&run[cut - offset..], false));
+        }
+        offset = end;
+    }
+    rich_text(spans).style(tint).color_maybe(highlight).into()
+}
+// Logic-Analyzer Style Lane: tall blocks while the signal is high, a thin trace while low
+fn timeline_lane<'a>(
+    label: String,
+    samples: Vec<bool>,
+    color: fn(&Theme) -> iced::Color,
+) -> Element<'a, Message> {
+    let trace = row(samples.into_iter().map(|high| {
+        container(horizontal_space())
+            .width(Fill)
+            .height(if high { 12 } else { 2 })
+            .style(move |theme: &Theme| container::Style {
+                background: Some(color(theme).into()),
+                ..container::Style::default()
+            })
+            .into()
+    }))
+    .height(12)
+    .align_y(Bottom);
+    row![text(label).size(12).width(40), trace]
+        .align_y(Center)
+        .into()
+}
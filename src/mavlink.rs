@@ -0,0 +1,755 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::time::Instant;
+
+const MAGIC_V1: u8 = 0xFE;
+const MAGIC_V2: u8 = 0xFD;
+// v2 incompatibility flag: a 13-byte signature follows the CRC
+const SIGNED: u8 = 0x01;
+const SIGNATURE_LEN: usize = 13;
+
+// How a field is laid out on the wire, little endian
+#[derive(Debug, Clone, Copy)]
+enum Field {
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    F32,
+    // A NUL-padded string of this many bytes
+    Text(usize),
+    U16s(usize),
+    F32s(usize),
+}
+
+impl Field {
+    fn size(self) -> usize {
+        match self {
+            Field::U8 | Field::I8 => 1,
+            Field::U16 | Field::I16 => 2,
+            Field::U32 | Field::I32 | Field::F32 => 4,
+            Field::U64 | Field::I64 => 8,
+            Field::Text(len) => len,
+            Field::U16s(len) => 2 * len,
+            Field::F32s(len) => 4 * len,
+        }
+    }
+    fn decode(self, bytes: &[u8]) -> String {
+        let mut word = [0; 8];
+        let len = bytes.len().min(8);
+        word[..len].copy_from_slice(&bytes[..len]);
+        match self {
+            Field::U8 => bytes[0].to_string(),
+            Field::I8 => (bytes[0] as i8).to_string(),
+            Field::U16 => u16::from_le_bytes([word[0], word[1]]).to_string(),
+            Field::I16 => i16::from_le_bytes([word[0], word[1]]).to_string(),
+            Field::U32 => u32::from_le_bytes([word[0], word[1], word[2], word[3]]).to_string(),
+            Field::I32 => i32::from_le_bytes([word[0], word[1], word[2], word[3]]).to_string(),
+            Field::U64 => u64::from_le_bytes(word).to_string(),
+            Field::I64 => i64::from_le_bytes(word).to_string(),
+            Field::F32 => f32::from_le_bytes([word[0], word[1], word[2], word[3]]).to_string(),
+            Field::Text(_) => {
+                let end = bytes
+                    .iter()
+                    .position(|&byte| byte == 0)
+                    .unwrap_or(bytes.len());
+                format!("\"{}\"", String::from_utf8_lossy(&bytes[..end]))
+            }
+            Field::U16s(_) => {
+                let values = bytes
+                    .chunks(2)
+                    .map(|pair| u16::from_le_bytes([pair[0], pair[1]]).to_string())
+                    .collect::<Vec<_>>();
+                format!("[{}]", values.join(", "))
+            }
+            Field::F32s(_) => {
+                let values = bytes
+                    .chunks(4)
+                    .map(|quad| {
+                        f32::from_le_bytes([quad[0], quad[1], quad[2], quad[3]]).to_string()
+                    })
+                    .collect::<Vec<_>>();
+                format!("[{}]", values.join(", "))
+            }
+        }
+    }
+}
+
+// A message definition: fields in wire order, which is largest type first, and the
+// CRC_EXTRA seed that ties packets to this layout
+struct Definition {
+    id: u32,
+    name: &'static str,
+    crc_extra: u8,
+    fields: &'static [(&'static str, Field)],
+}
+
+use Field::*;
+
+// Common Messages, from the MAVLink common.xml; extension fields are left out
+const DEFINITIONS: &[Definition] = &[
+    Definition {
+        id: 0,
+        name: "HEARTBEAT",
+        crc_extra: 50,
+        fields: &[
+            ("custom_mode", U32),
+            ("type", U8),
+            ("autopilot", U8),
+            ("base_mode", U8),
+            ("system_status", U8),
+            ("mavlink_version", U8),
+        ],
+    },
+    Definition {
+        id: 1,
+        name: "SYS_STATUS",
+        crc_extra: 124,
+        fields: &[
+            ("onboard_control_sensors_present", U32),
+            ("onboard_control_sensors_enabled", U32),
+            ("onboard_control_sensors_health", U32),
+            ("load", U16),
+            ("voltage_battery", U16),
+            ("current_battery", I16),
+            ("drop_rate_comm", U16),
+            ("errors_comm", U16),
+            ("errors_count1", U16),
+            ("errors_count2", U16),
+            ("errors_count3", U16),
+            ("errors_count4", U16),
+            ("battery_remaining", I8),
+        ],
+    },
+    Definition {
+        id: 2,
+        name: "SYSTEM_TIME",
+        crc_extra: 137,
+        fields: &[("time_unix_usec", U64), ("time_boot_ms", U32)],
+    },
+    Definition {
+        id: 4,
+        name: "PING",
+        crc_extra: 237,
+        fields: &[
+            ("time_usec", U64),
+            ("seq", U32),
+            ("target_system", U8),
+            ("target_component", U8),
+        ],
+    },
+    Definition {
+        id: 11,
+        name: "SET_MODE",
+        crc_extra: 89,
+        fields: &[
+            ("custom_mode", U32),
+            ("target_system", U8),
+            ("base_mode", U8),
+        ],
+    },
+    Definition {
+        id: 21,
+        name: "PARAM_REQUEST_LIST",
+        crc_extra: 159,
+        fields: &[("target_system", U8), ("target_component", U8)],
+    },
+    Definition {
+        id: 22,
+        name: "PARAM_VALUE",
+        crc_extra: 220,
+        fields: &[
+            ("param_value", F32),
+            ("param_count", U16),
+            ("param_index", U16),
+            ("param_id", Text(16)),
+            ("param_type", U8),
+        ],
+    },
+    Definition {
+        id: 23,
+        name: "PARAM_SET",
+        crc_extra: 168,
+        fields: &[
+            ("param_value", F32),
+            ("target_system", U8),
+            ("target_component", U8),
+            ("param_id", Text(16)),
+            ("param_type", U8),
+        ],
+    },
+    Definition {
+        id: 24,
+        name: "GPS_RAW_INT",
+        crc_extra: 24,
+        fields: &[
+            ("time_usec", U64),
+            ("lat", I32),
+            ("lon", I32),
+            ("alt", I32),
+            ("eph", U16),
+            ("epv", U16),
+            ("vel", U16),
+            ("cog", U16),
+            ("fix_type", U8),
+            ("satellites_visible", U8),
+        ],
+    },
+    Definition {
+        id: 26,
+        name: "SCALED_IMU",
+        crc_extra: 170,
+        fields: &[
+            ("time_boot_ms", U32),
+            ("xacc", I16),
+            ("yacc", I16),
+            ("zacc", I16),
+            ("xgyro", I16),
+            ("ygyro", I16),
+            ("zgyro", I16),
+            ("xmag", I16),
+            ("ymag", I16),
+            ("zmag", I16),
+        ],
+    },
+    Definition {
+        id: 27,
+        name: "RAW_IMU",
+        crc_extra: 144,
+        fields: &[
+            ("time_usec", U64),
+            ("xacc", I16),
+            ("yacc", I16),
+            ("zacc", I16),
+            ("xgyro", I16),
+            ("ygyro", I16),
+            ("zgyro", I16),
+            ("xmag", I16),
+            ("ymag", I16),
+            ("zmag", I16),
+        ],
+    },
+    Definition {
+        id: 29,
+        name: "SCALED_PRESSURE",
+        crc_extra: 115,
+        fields: &[
+            ("time_boot_ms", U32),
+            ("press_abs", F32),
+            ("press_diff", F32),
+            ("temperature", I16),
+        ],
+    },
+    Definition {
+        id: 30,
+        name: "ATTITUDE",
+        crc_extra: 39,
+        fields: &[
+            ("time_boot_ms", U32),
+            ("roll", F32),
+            ("pitch", F32),
+            ("yaw", F32),
+            ("rollspeed", F32),
+            ("pitchspeed", F32),
+            ("yawspeed", F32),
+        ],
+    },
+    Definition {
+        id: 31,
+        name: "ATTITUDE_QUATERNION",
+        crc_extra: 246,
+        fields: &[
+            ("time_boot_ms", U32),
+            ("q1", F32),
+            ("q2", F32),
+            ("q3", F32),
+            ("q4", F32),
+            ("rollspeed", F32),
+            ("pitchspeed", F32),
+            ("yawspeed", F32),
+        ],
+    },
+    Definition {
+        id: 32,
+        name: "LOCAL_POSITION_NED",
+        crc_extra: 185,
+        fields: &[
+            ("time_boot_ms", U32),
+            ("x", F32),
+            ("y", F32),
+            ("z", F32),
+            ("vx", F32),
+            ("vy", F32),
+            ("vz", F32),
+        ],
+    },
+    Definition {
+        id: 33,
+        name: "GLOBAL_POSITION_INT",
+        crc_extra: 104,
+        fields: &[
+            ("time_boot_ms", U32),
+            ("lat", I32),
+            ("lon", I32),
+            ("alt", I32),
+            ("relative_alt", I32),
+            ("vx", I16),
+            ("vy", I16),
+            ("vz", I16),
+            ("hdg", U16),
+        ],
+    },
+    Definition {
+        id: 35,
+        name: "RC_CHANNELS_RAW",
+        crc_extra: 244,
+        fields: &[
+            ("time_boot_ms", U32),
+            ("chan1_raw", U16),
+            ("chan2_raw", U16),
+            ("chan3_raw", U16),
+            ("chan4_raw", U16),
+            ("chan5_raw", U16),
+            ("chan6_raw", U16),
+            ("chan7_raw", U16),
+            ("chan8_raw", U16),
+            ("port", U8),
+            ("rssi", U8),
+        ],
+    },
+    Definition {
+        id: 36,
+        name: "SERVO_OUTPUT_RAW",
+        crc_extra: 222,
+        fields: &[
+            ("time_usec", U32),
+            ("servo1_raw", U16),
+            ("servo2_raw", U16),
+            ("servo3_raw", U16),
+            ("servo4_raw", U16),
+            ("servo5_raw", U16),
+            ("servo6_raw", U16),
+            ("servo7_raw", U16),
+            ("servo8_raw", U16),
+            ("port", U8),
+        ],
+    },
+    Definition {
+        id: 42,
+        name: "MISSION_CURRENT",
+        crc_extra: 28,
+        fields: &[("seq", U16)],
+    },
+    Definition {
+        id: 44,
+        name: "MISSION_COUNT",
+        crc_extra: 221,
+        fields: &[
+            ("count", U16),
+            ("target_system", U8),
+            ("target_component", U8),
+        ],
+    },
+    Definition {
+        id: 46,
+        name: "MISSION_ITEM_REACHED",
+        crc_extra: 11,
+        fields: &[("seq", U16)],
+    },
+    Definition {
+        id: 47,
+        name: "MISSION_ACK",
+        crc_extra: 153,
+        fields: &[
+            ("target_system", U8),
+            ("target_component", U8),
+            ("type", U8),
+        ],
+    },
+    Definition {
+        id: 51,
+        name: "MISSION_REQUEST_INT",
+        crc_extra: 196,
+        fields: &[
+            ("seq", U16),
+            ("target_system", U8),
+            ("target_component", U8),
+        ],
+    },
+    Definition {
+        id: 62,
+        name: "NAV_CONTROLLER_OUTPUT",
+        crc_extra: 183,
+        fields: &[
+            ("nav_roll", F32),
+            ("nav_pitch", F32),
+            ("alt_error", F32),
+            ("aspd_error", F32),
+            ("xtrack_error", F32),
+            ("nav_bearing", I16),
+            ("target_bearing", I16),
+            ("wp_dist", U16),
+        ],
+    },
+    Definition {
+        id: 65,
+        name: "RC_CHANNELS",
+        crc_extra: 118,
+        fields: &[
+            ("time_boot_ms", U32),
+            ("chan_raw", U16s(18)),
+            ("chancount", U8),
+            ("rssi", U8),
+        ],
+    },
+    Definition {
+        id: 66,
+        name: "REQUEST_DATA_STREAM",
+        crc_extra: 148,
+        fields: &[
+            ("req_message_rate", U16),
+            ("target_system", U8),
+            ("target_component", U8),
+            ("req_stream_id", U8),
+            ("start_stop", U8),
+        ],
+    },
+    Definition {
+        id: 74,
+        name: "VFR_HUD",
+        crc_extra: 20,
+        fields: &[
+            ("airspeed", F32),
+            ("groundspeed", F32),
+            ("alt", F32),
+            ("climb", F32),
+            ("heading", I16),
+            ("throttle", U16),
+        ],
+    },
+    Definition {
+        id: 76,
+        name: "COMMAND_LONG",
+        crc_extra: 152,
+        fields: &[
+            ("param", F32s(7)),
+            ("command", U16),
+            ("target_system", U8),
+            ("target_component", U8),
+            ("confirmation", U8),
+        ],
+    },
+    Definition {
+        id: 77,
+        name: "COMMAND_ACK",
+        crc_extra: 143,
+        fields: &[("command", U16), ("result", U8)],
+    },
+    Definition {
+        id: 109,
+        name: "RADIO_STATUS",
+        crc_extra: 185,
+        fields: &[
+            ("rxerrors", U16),
+            ("fixed", U16),
+            ("rssi", U8),
+            ("remrssi", U8),
+            ("txbuf", U8),
+            ("noise", U8),
+            ("remnoise", U8),
+        ],
+    },
+    Definition {
+        id: 111,
+        name: "TIMESYNC",
+        crc_extra: 34,
+        fields: &[("tc1", I64), ("ts1", I64)],
+    },
+    Definition {
+        id: 125,
+        name: "POWER_STATUS",
+        crc_extra: 203,
+        fields: &[("Vcc", U16), ("Vservo", U16), ("flags", U16)],
+    },
+    Definition {
+        id: 147,
+        name: "BATTERY_STATUS",
+        crc_extra: 154,
+        fields: &[
+            ("current_consumed", I32),
+            ("energy_consumed", I32),
+            ("temperature", I16),
+            ("voltages", U16s(10)),
+            ("current_battery", I16),
+            ("id", U8),
+            ("battery_function", U8),
+            ("type", U8),
+            ("battery_remaining", I8),
+        ],
+    },
+    Definition {
+        id: 241,
+        name: "VIBRATION",
+        crc_extra: 90,
+        fields: &[
+            ("time_usec", U64),
+            ("vibration_x", F32),
+            ("vibration_y", F32),
+            ("vibration_z", F32),
+            ("clipping_0", U32),
+            ("clipping_1", U32),
+            ("clipping_2", U32),
+        ],
+    },
+    Definition {
+        id: 242,
+        name: "HOME_POSITION",
+        crc_extra: 104,
+        fields: &[
+            ("latitude", I32),
+            ("longitude", I32),
+            ("altitude", I32),
+            ("x", F32),
+            ("y", F32),
+            ("z", F32),
+            ("q", F32s(4)),
+            ("approach_x", F32),
+            ("approach_y", F32),
+            ("approach_z", F32),
+        ],
+    },
+    Definition {
+        id: 245,
+        name: "EXTENDED_SYS_STATE",
+        crc_extra: 130,
+        fields: &[("vtol_state", U8), ("landed_state", U8)],
+    },
+    Definition {
+        id: 253,
+        name: "STATUSTEXT",
+        crc_extra: 83,
+        fields: &[("severity", U8), ("text", Text(50))],
+    },
+];
+
+fn definition(id: u32) -> Option<&'static Definition> {
+    DEFINITIONS.iter().find(|definition| definition.id == id)
+}
+
+// CRC-16/MCRF4XX, as MAVLink calls X.25
+fn crc_accumulate(crc: u16, byte: u8) -> u16 {
+    let mut tmp = byte ^ (crc & 0xFF) as u8;
+    tmp ^= tmp << 4;
+    let tmp = u16::from(tmp);
+    (crc >> 8) ^ (tmp << 8) ^ (tmp << 3) ^ (tmp >> 4)
+}
+
+fn crc(data: &[u8], extra: u8) -> u16 {
+    let crc = data
+        .iter()
+        .fold(0xFFFF, |crc, &byte| crc_accumulate(crc, byte));
+    crc_accumulate(crc, extra)
+}
+
+// One Framed Packet
+pub struct Packet {
+    pub version: u8,
+    pub sequence: u8,
+    pub system: u8,
+    pub component: u8,
+    pub id: u32,
+    // None when the message isn't known, so its CRC couldn't be checked
+    pub name: Option<&'static str>,
+    pub fields: Vec<(&'static str, String)>,
+    pub payload: Vec<u8>,
+    pub signed: bool,
+}
+
+impl Packet {
+    pub fn title(&self) -> String {
+        match self.name {
+            Some(name) => name.to_string(),
+            None => format!("message {} (CRC not checked)", self.id),
+        }
+    }
+    pub fn summary(&self) -> String {
+        match self.name {
+            Some(_) => self
+                .fields
+                .iter()
+                .map(|(name, value)| format!("{name}={value}"))
+                .collect::<Vec<_>>()
+                .join(" "),
+            None => hex::encode_upper(&self.payload),
+        }
+    }
+    // The text of a STATUSTEXT, with its severity
+    fn status_text(&self) -> Option<String> {
+        (self.name == Some("STATUSTEXT")).then(|| {
+            let value = |name: &str| {
+                self.fields
+                    .iter()
+                    .find(|(field, _)| *field == name)
+                    .map_or("", |(_, value)| value.as_str())
+            };
+            let text = value("text").trim_matches('"');
+            format!("severity {}: {text}", value("severity"))
+        })
+    }
+}
+
+// The latest packet of one kind from one system and component
+pub struct Latest {
+    pub packet: Packet,
+    pub count: u64,
+    pub at: Instant,
+}
+
+// MAVLink Dissector: received bytes in, the latest packet of each kind kept, and the text of
+// STATUSTEXT messages out for the log
+#[derive(Default)]
+pub struct Dissector {
+    buffer: Vec<u8>,
+    // By system, component and message id
+    pub latest: BTreeMap<(u8, u8, u32), Latest>,
+    pub packets: u64,
+    pub crc_errors: u64,
+    // Sequence gaps per system and component, and the sequence number seen last
+    pub lost: u64,
+    sequences: BTreeMap<(u8, u8), u8>,
+}
+
+impl Dissector {
+    pub fn feed(&mut self, data: &[u8], at: Instant) -> Vec<String> {
+        self.buffer.extend_from_slice(data);
+        let mut texts = Vec::new();
+        while let Some(packet) = self.take_packet() {
+            self.packets += 1;
+            let stream = (packet.system, packet.component);
+            if let Some(last) = self.sequences.insert(stream, packet.sequence) {
+                self.lost += u64::from(packet.sequence.wrapping_sub(last).wrapping_sub(1));
+            }
+            let key = (packet.system, packet.component, packet.id);
+            let count = self.latest.get(&key).map_or(0, |latest| latest.count) + 1;
+            if let Some(text) = packet.status_text() {
+                texts.push(format!("{}:{} {text}", packet.system, packet.component));
+            }
+            self.latest.insert(key, Latest { packet, count, at });
+        }
+        texts
+    }
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+    // e.g. "12 packets, 3 CRC errors, 1 lost"
+    pub fn totals(&self) -> String {
+        let mut totals = format!("{} packets", self.packets);
+        if self.crc_errors > 0 {
+            let _ = write!(totals, ", {} CRC errors", self.crc_errors);
+        }
+        if self.lost > 0 {
+            let _ = write!(totals, ", {} lost", self.lost);
+        }
+        totals
+    }
+    // The next whole packet, skipping a byte at a time past noise and bad CRCs
+    fn take_packet(&mut self) -> Option<Packet> {
+        loop {
+            let start = self
+                .buffer
+                .iter()
+                .position(|&byte| byte == MAGIC_V1 || byte == MAGIC_V2);
+            let Some(start) = start else {
+                self.buffer.clear();
+                return None;
+            };
+            self.buffer.drain(..start);
+            let v2 = self.buffer[0] == MAGIC_V2;
+            let header = if v2 { 10 } else { 6 };
+            let len = usize::from(*self.buffer.get(1)?);
+            if self.buffer.len() < header {
+                return None;
+            }
+            let signed = v2 && self.buffer[2] & SIGNED != 0;
+            let total = header + len + 2 + if signed { SIGNATURE_LEN } else { 0 };
+            if self.buffer.len() < total {
+                return None;
+            }
+            let (sequence, system, component, id) = match v2 {
+                true => (
+                    self.buffer[4],
+                    self.buffer[5],
+                    self.buffer[6],
+                    u32::from_le_bytes([self.buffer[7], self.buffer[8], self.buffer[9], 0]),
+                ),
+                false => (
+                    self.buffer[2],
+                    self.buffer[3],
+                    self.buffer[4],
+                    u32::from(self.buffer[5]),
+                ),
+            };
+            let payload = self.buffer[header..header + len].to_vec();
+            let sent =
+                u16::from_le_bytes([self.buffer[header + len], self.buffer[header + len + 1]]);
+            let known = definition(id);
+            match known {
+                Some(definition)
+                    if crc(&self.buffer[1..header + len], definition.crc_extra) != sent =>
+                {
+                    self.crc_errors += 1;
+                    self.buffer.remove(0);
+                    continue;
+                }
+                Some(_) => {}
+                // Without its CRC_EXTRA an unknown message can't be checked, so a stray magic
+                // byte in noise could pass for one; it's only taken when the next packet starts
+                // right after it
+                None => match self.buffer.get(total) {
+                    None => return None,
+                    Some(&MAGIC_V1 | &MAGIC_V2) => {}
+                    Some(_) => {
+                        self.buffer.remove(0);
+                        continue;
+                    }
+                },
+            }
+            self.buffer.drain(..total);
+            let fields = known.map_or_else(Vec::new, |definition| {
+                // v2 drops trailing zeros from the payload, so they're put back
+                let size = definition
+                    .fields
+                    .iter()
+                    .map(|(_, field)| field.size())
+                    .sum();
+                let mut bytes = payload.clone();
+                bytes.resize(bytes.len().max(size), 0);
+                let mut at = 0;
+                definition
+                    .fields
+                    .iter()
+                    .map(|&(name, field)| {
+                        let value = field.decode(&bytes[at..at + field.size()]);
+                        at += field.size();
+                        (name, value)
+                    })
+                    .collect()
+            });
+            return Some(Packet {
+                version: if v2 { 2 } else { 1 },
+                sequence,
+                system,
+                component,
+                id,
+                name: known.map(|definition| definition.name),
+                fields,
+                payload,
+                signed,
+            });
+        }
+    }
+}
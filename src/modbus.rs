@@ -0,0 +1,284 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+// Bytes of a request further apart than this belong to different frames, so whatever was
+// left over from the last one is dropped
+const FRAME_GAP: Duration = Duration::from_millis(50);
+
+// Exception Codes
+const ILLEGAL_FUNCTION: u8 = 1;
+const ILLEGAL_ADDRESS: u8 = 2;
+const ILLEGAL_VALUE: u8 = 3;
+
+// The Four Modbus Data Tables
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Table {
+    Coil,
+    DiscreteInput,
+    InputRegister,
+    HoldingRegister,
+}
+
+impl Table {
+    pub const ALL: [Table; 4] = [
+        Table::Coil,
+        Table::DiscreteInput,
+        Table::InputRegister,
+        Table::HoldingRegister,
+    ];
+    pub fn is_bit(self) -> bool {
+        matches!(self, Table::Coil | Table::DiscreteInput)
+    }
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Table::Coil => "Coil",
+                Table::DiscreteInput => "Discrete input",
+                Table::InputRegister => "Input register",
+                Table::HoldingRegister => "Holding register",
+            }
+        )
+    }
+}
+
+// A request that was answered, or taken without an answer when broadcast
+pub struct Exchange {
+    pub summary: String,
+    pub response: Option<Vec<u8>>,
+}
+
+// Modbus RTU Slave Simulator
+// Answers requests to one unit id from a register map; addresses missing from the map get
+// an illegal data address exception, as a real device's would. Coils and discrete inputs are
+// stored as 0 or 1.
+pub struct Slave {
+    pub unit: u8,
+    pub map: BTreeMap<(Table, u16), u16>,
+    buffer: Vec<u8>,
+    last: Option<Instant>,
+}
+
+impl Slave {
+    pub fn new() -> Self {
+        Self {
+            unit: 1,
+            map: BTreeMap::new(),
+            buffer: Vec::new(),
+            last: None,
+        }
+    }
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.last = None;
+    }
+    // Received bytes in, the requests they complete out
+    pub fn feed(&mut self, data: &[u8], at: Instant) -> Vec<Exchange> {
+        if self
+            .last
+            .is_some_and(|last| at.saturating_duration_since(last) > FRAME_GAP)
+        {
+            self.buffer.clear();
+        }
+        self.last = Some(at);
+        self.buffer.extend_from_slice(data);
+        let mut exchanges = Vec::new();
+        while let Some(request) = self.take_request() {
+            if let Some(exchange) = self.answer(&request) {
+                exchanges.push(exchange);
+            }
+        }
+        exchanges
+    }
+    // The next whole request with a good CRC, skipping a byte at a time past anything else
+    fn take_request(&mut self) -> Option<Vec<u8>> {
+        loop {
+            let function = *self.buffer.get(1)?;
+            let len = match function {
+                1..=6 => 8,
+                15 | 16 => 9 + usize::from(*self.buffer.get(6)?),
+                // Other functions have no length to go by, so the whole buffer is tried
+                _ => self.buffer.len().max(4),
+            };
+            if self.buffer.len() < len {
+                return None;
+            }
+            let crc = u16::from_le_bytes([self.buffer[len - 2], self.buffer[len - 1]]);
+            if crc16(&self.buffer[..len - 2]) == crc {
+                return Some(self.buffer.drain(..len).collect());
+            }
+            if !(1..=6).contains(&function) && function != 15 && function != 16 {
+                // Maybe more of it is on the way; a gap clears it otherwise
+                return None;
+            }
+            self.buffer.remove(0);
+        }
+    }
+    fn answer(&mut self, request: &[u8]) -> Option<Exchange> {
+        let unit = request[0];
+        // Unit 0 is a broadcast: writes are carried out but never answered
+        if unit != self.unit && unit != 0 {
+            return None;
+        }
+        let function = request[1];
+        let body = &request[2..request.len() - 2];
+        let word = |at: usize| u16::from_be_bytes([body[at], body[at + 1]]);
+        let (summary, reply) = match function {
+            1..=4 => {
+                let table = match function {
+                    1 => Table::Coil,
+                    2 => Table::DiscreteInput,
+                    3 => Table::HoldingRegister,
+                    _ => Table::InputRegister,
+                };
+                let (start, count) = (word(0), word(2));
+                let summary = format!("read {count} from {table} {start}");
+                (summary, self.read(table, start, count))
+            }
+            5 => {
+                let (address, value) = (word(0), word(2));
+                let summary = format!("write Coil {address} = {}", u8::from(value == 0xFF00));
+                let reply = match value {
+                    0xFF00 | 0x0000 => self
+                        .write(Table::Coil, address, &[u16::from(value == 0xFF00)])
+                        .map(|()| body.to_vec()),
+                    _ => Err(ILLEGAL_VALUE),
+                };
+                (summary, reply)
+            }
+            6 => {
+                let (address, value) = (word(0), word(2));
+                let summary = format!("write Holding register {address} = {value}");
+                let reply = self
+                    .write(Table::HoldingRegister, address, &[value])
+                    .map(|()| body.to_vec());
+                (summary, reply)
+            }
+            15 | 16 => {
+                let (start, count) = (word(0), word(2));
+                let bytes = &body[5..];
+                let values = match function {
+                    15 => (0..usize::from(count))
+                        .map(|i| bytes.get(i / 8).map(|byte| u16::from(byte >> (i % 8) & 1)))
+                        .collect::<Option<Vec<_>>>(),
+                    _ => (bytes.len() == usize::from(count) * 2).then(|| {
+                        bytes
+                            .chunks(2)
+                            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                            .collect()
+                    }),
+                };
+                let table = match function {
+                    15 => Table::Coil,
+                    _ => Table::HoldingRegister,
+                };
+                let summary = format!("write {count} to {table} {start}");
+                let reply = match values {
+                    Some(values) if !values.is_empty() => self
+                        .write(table, start, &values)
+                        .map(|()| body[..4].to_vec()),
+                    _ => Err(ILLEGAL_VALUE),
+                };
+                (summary, reply)
+            }
+            _ => (format!("function {function}"), Err(ILLEGAL_FUNCTION)),
+        };
+        let summary = match &reply {
+            Ok(_) => summary,
+            Err(code) => format!("{summary}, answered with exception {code}"),
+        };
+        let response = (unit != 0).then(|| {
+            let mut response = vec![unit];
+            match reply {
+                Ok(body) => {
+                    response.push(function);
+                    response.extend(body);
+                }
+                Err(code) => response.extend([function | 0x80, code]),
+            }
+            response.extend(crc16(&response).to_le_bytes());
+            response
+        });
+        Some(Exchange { summary, response })
+    }
+    // The body of a read response: a byte count and the values
+    fn read(&self, table: Table, start: u16, count: u16) -> Result<Vec<u8>, u8> {
+        let limit = match table.is_bit() {
+            true => 2000,
+            false => 125,
+        };
+        if count == 0 || count > limit {
+            return Err(ILLEGAL_VALUE);
+        }
+        let values = (0..count)
+            .map(|i| {
+                let address = start.checked_add(i).ok_or(ILLEGAL_ADDRESS)?;
+                self.map
+                    .get(&(table, address))
+                    .copied()
+                    .ok_or(ILLEGAL_ADDRESS)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let data = match table.is_bit() {
+            // Bits are packed eight to a byte, lowest address in the lowest bit
+            true => values
+                .chunks(8)
+                .map(|bits| {
+                    bits.iter()
+                        .enumerate()
+                        .fold(0, |byte, (i, bit)| byte | (u8::from(*bit != 0) << i))
+                })
+                .collect::<Vec<_>>(),
+            false => values
+                .iter()
+                .flat_map(|value| value.to_be_bytes())
+                .collect(),
+        };
+        Ok([vec![data.len() as u8], data].concat())
+    }
+    // Every address has to be mapped before any of them is written
+    fn write(&mut self, table: Table, start: u16, values: &[u16]) -> Result<(), u8> {
+        let addresses = (0..values.len())
+            .map(|i| {
+                u16::try_from(i)
+                    .ok()
+                    .and_then(|i| start.checked_add(i))
+                    .filter(|address| self.map.contains_key(&(table, *address)))
+                    .ok_or(ILLEGAL_ADDRESS)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for (address, value) in addresses.into_iter().zip(values) {
+            self.map.insert((table, address), *value);
+        }
+        Ok(())
+    }
+}
+
+// CRC-16/MODBUS, sent low byte first
+pub fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0xFFFF;
+    for byte in data {
+        crc ^= u16::from(*byte);
+        for _ in 0..8 {
+            crc = match crc & 1 {
+                1 => (crc >> 1) ^ 0xA001,
+                _ => crc >> 1,
+            };
+        }
+    }
+    crc
+}
+
+// A register address or value, in decimal or as 0x hex
+pub fn parse_number(text: &str) -> Option<u16> {
+    let text = text.trim();
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u16::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+
+// Longest partial line kept while waiting for its line break; sentences are at most 82
+const MAX_LINE: usize = 1024;
+
+// One NMEA 0183 Sentence, e.g. $GPGGA,...*47: talker GP, kind GGA
+pub struct Sentence {
+    pub talker: String,
+    pub kind: String,
+    pub fields: Vec<String>,
+    // False when the sentence came without a checksum
+    pub checked: bool,
+}
+
+// The sentence in a received line, if there is one; an error when its checksum is wrong
+pub fn parse(line: &str) -> Option<Result<Sentence, String>> {
+    let start = line.find(['$', '!'])?;
+    let line = line[start + 1..].trim_end();
+    let (body, checked) = match line.rsplit_once('*') {
+        Some((body, sum)) => {
+            let expected = u8::from_str_radix(sum.get(..2)?, 16).ok()?;
+            let actual = body.bytes().fold(0, |sum, byte| sum ^ byte);
+            if actual != expected {
+                return Some(Err(format!(
+                    "checksum {actual:02X} instead of {expected:02X} in {}",
+                    line.chars().take(6).collect::<String>()
+                )));
+            }
+            (body, true)
+        }
+        None => (line, false),
+    };
+    let mut fields = body.split(',');
+    let address = fields.next()?;
+    // Two letter talker and three letter kind; proprietary $P... sentences are skipped
+    if address.len() != 5 || !address.bytes().all(|byte| byte.is_ascii_alphanumeric()) {
+        return None;
+    }
+    Some(Ok(Sentence {
+        talker: address[..2].to_string(),
+        kind: address[2..].to_string(),
+        fields: fields.map(str::to_string).collect(),
+        checked,
+    }))
+}
+
+// ddmm.mmmm with N/S, or dddmm.mmmm with E/W, in signed degrees
+fn coordinate(value: &str, hemisphere: &str) -> Option<f64> {
+    let dot = value.find('.').unwrap_or(value.len());
+    let degrees: f64 = value.get(..dot.checked_sub(2)?)?.parse().ok()?;
+    let minutes: f64 = value.get(dot - 2..)?.parse().ok()?;
+    let degrees = degrees + minutes / 60.0;
+    match hemisphere {
+        "N" | "E" => Some(degrees),
+        "S" | "W" => Some(-degrees),
+        _ => None,
+    }
+}
+
+// hhmmss.ss as hh:mm:ss
+fn time(value: &str) -> Option<String> {
+    let digits = value.get(..6)?;
+    digits
+        .bytes()
+        .all(|byte| byte.is_ascii_digit())
+        .then(|| format!("{}:{}:{}", &digits[..2], &digits[2..4], &digits[4..6]))
+}
+
+// ddmmyy as 20yy-mm-dd
+fn date(value: &str) -> Option<String> {
+    (value.len() == 6 && value.bytes().all(|byte| byte.is_ascii_digit()))
+        .then(|| format!("20{}-{}-{}", &value[4..6], &value[2..4], &value[..2]))
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Satellite {
+    pub elevation: Option<u32>,
+    pub azimuth: Option<u32>,
+    pub snr: Option<u32>,
+}
+
+// The Latest Fix, from whichever sentences last carried each part
+#[derive(Debug, Clone, Default)]
+pub struct Fix {
+    pub time: Option<String>,
+    pub date: Option<String>,
+    // RMC's A (valid) or V (warning)
+    pub valid: Option<bool>,
+    // GGA's fix quality, 0 for none
+    pub quality: Option<u32>,
+    // GSA's 1 (none), 2 (2D) or 3 (3D)
+    pub mode: Option<u32>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub altitude: Option<f64>,
+    pub used: Option<u32>,
+    pub hdop: Option<f64>,
+    pub pdop: Option<f64>,
+    pub vdop: Option<f64>,
+    pub speed_knots: Option<f64>,
+    pub course: Option<f64>,
+}
+
+impl Fix {
+    pub fn quality_name(&self) -> Option<&'static str> {
+        Some(match self.quality? {
+            0 => "no fix",
+            1 => "GPS fix",
+            2 => "DGPS fix",
+            3 => "PPS fix",
+            4 => "RTK fixed",
+            5 => "RTK float",
+            6 => "dead reckoning",
+            7 => "manual input",
+            8 => "simulation",
+            _ => "unknown fix",
+        })
+    }
+}
+
+// NMEA Decoder: received text in, the latest fix and satellites out
+#[derive(Default)]
+pub struct Decoder {
+    pub fix: Fix,
+    // Satellites in view by talker and PRN, refreshed by each full GSV cycle
+    pub satellites: BTreeMap<(String, u32), Satellite>,
+    // Sentences decoded per kind
+    pub counts: BTreeMap<String, u64>,
+    pub bad_checksums: u64,
+    pub unchecked: u64,
+    pub last_error: Option<String>,
+    partial: String,
+}
+
+impl Decoder {
+    pub fn feed(&mut self, text: &str) {
+        self.partial.push_str(text);
+        while let Some(end) = self.partial.find('\n') {
+            let line = self.partial[..end].to_string();
+            self.partial.drain(..=end);
+            match parse(&line) {
+                Some(Ok(sentence)) => self.apply(&sentence),
+                Some(Err(e)) => {
+                    self.bad_checksums += 1;
+                    self.last_error = Some(e);
+                }
+                None => {}
+            }
+        }
+        if self.partial.len() > MAX_LINE {
+            self.partial.clear();
+        }
+    }
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+    pub fn total(&self) -> u64 {
+        self.counts.values().sum()
+    }
+    fn apply(&mut self, sentence: &Sentence) {
+        *self.counts.entry(sentence.kind.clone()).or_default() += 1;
+        if !sentence.checked {
+            self.unchecked += 1;
+        }
+        let field = |i: usize| sentence.fields.get(i).map_or("", String::as_str);
+        let number = |i: usize| field(i).parse::<f64>().ok();
+        let integer = |i: usize| field(i).parse::<u32>().ok();
+        let fix = &mut self.fix;
+        match sentence.kind.as_str() {
+            "GGA" => {
+                fix.time = time(field(0)).or(fix.time.take());
+                fix.latitude = coordinate(field(1), field(2));
+                fix.longitude = coordinate(field(3), field(4));
+                fix.quality = integer(5);
+                fix.used = integer(6);
+                fix.hdop = number(7);
+                fix.altitude = number(8);
+            }
+            "RMC" => {
+                fix.time = time(field(0)).or(fix.time.take());
+                fix.valid = Some(field(1) == "A");
+                fix.latitude = coordinate(field(2), field(3));
+                fix.longitude = coordinate(field(4), field(5));
+                fix.speed_knots = number(6);
+                fix.course = number(7);
+                fix.date = date(field(8)).or(fix.date.take());
+            }
+            "GLL" => {
+                fix.latitude = coordinate(field(0), field(1));
+                fix.longitude = coordinate(field(2), field(3));
+                fix.time = time(field(4)).or(fix.time.take());
+                fix.valid = Some(field(5) == "A");
+            }
+            "VTG" => {
+                fix.course = number(0);
+                fix.speed_knots = number(4);
+            }
+            "GSA" => {
+                fix.mode = integer(1);
+                fix.pdop = number(14);
+                fix.hdop = number(15);
+                fix.vdop = number(16);
+            }
+            // Up to four satellites each, in numbered parts
+            "GSV" => {
+                if integer(1) == Some(1) {
+                    self.satellites
+                        .retain(|(talker, _), _| *talker != sentence.talker);
+                }
+                // NMEA 4.1 adds a signal id after the last group, which chunks_exact leaves out
+                let groups = sentence.fields.get(3..).unwrap_or_default();
+                for group in groups.chunks_exact(4) {
+                    let value = |i: usize| group[i].parse::<u32>().ok();
+                    let Some(prn) = value(0) else {
+                        continue;
+                    };
+                    self.satellites.insert(
+                        (sentence.talker.clone(), prn),
+                        Satellite {
+                            elevation: value(1),
+                            azimuth: value(2),
+                            snr: value(3),
+                        },
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// Talker ids of the common constellations
+pub fn constellation(talker: &str) -> &str {
+    match talker {
+        "GP" => "GPS",
+        "GL" => "GLONASS",
+        "GA" => "Galileo",
+        "GB" | "BD" => "BeiDou",
+        "GQ" | "QZ" => "QZSS",
+        "GI" => "NavIC",
+        "GN" => "Multi",
+        _ => talker,
+    }
+}
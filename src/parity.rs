@@ -0,0 +1,87 @@
+use serialport::Parity;
+
+// Parity Choices, including the mark and space ("sticky") parity used for 9-bit addressing
+// on RS-485 buses. The serial driver only knows none/odd/even, so mark and space are set up
+// on the open port separately.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum LineParity {
+    None,
+    Odd,
+    Even,
+    Mark,
+    Space,
+}
+
+impl LineParity {
+    pub const ALL: [LineParity; 5] = [
+        LineParity::None,
+        LineParity::Odd,
+        LineParity::Even,
+        LineParity::Mark,
+        LineParity::Space,
+    ];
+
+    // What to give the port builder; mark and space start out as no parity
+    pub fn serial(self) -> Parity {
+        match self {
+            LineParity::Odd => Parity::Odd,
+            LineParity::Even => Parity::Even,
+            _ => Parity::None,
+        }
+    }
+    // The choice matching what a port reports, which never says mark or space
+    pub fn from_serial(parity: Parity) -> Self {
+        match parity {
+            Parity::Odd => LineParity::Odd,
+            Parity::Even => LineParity::Even,
+            Parity::None => LineParity::None,
+        }
+    }
+    // Letter used in "8N1" style summaries
+    pub fn letter(self) -> char {
+        match self {
+            LineParity::None => 'N',
+            LineParity::Odd => 'O',
+            LineParity::Even => 'E',
+            LineParity::Mark => 'M',
+            LineParity::Space => 'S',
+        }
+    }
+    pub fn sticky(self) -> bool {
+        matches!(self, LineParity::Mark | LineParity::Space)
+    }
+}
+
+impl std::fmt::Display for LineParity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            LineParity::None => "None",
+            LineParity::Odd => "Odd",
+            LineParity::Even => "Even",
+            LineParity::Mark => "Mark",
+            LineParity::Space => "Space",
+        };
+        write!(f, "{name}")
+    }
+}
+
+// Turn on mark or space parity: a parity bit that is always 1 (mark) or always 0 (space)
+#[cfg(target_os = "linux")]
+pub fn set_sticky(fd: std::os::fd::RawFd, parity: LineParity) -> std::io::Result<()> {
+    // SAFETY: termios2 is plain data, filled in by the kernel before it's changed
+    let mut termios: libc::termios2 = unsafe { std::mem::zeroed() };
+    // SAFETY: the fd belongs to the open port and the struct matches what the ioctl expects
+    if unsafe { libc::ioctl(fd, libc::TCGETS2, &mut termios) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    termios.c_cflag |= libc::PARENB | libc::CMSPAR;
+    match parity {
+        LineParity::Mark => termios.c_cflag |= libc::PARODD,
+        _ => termios.c_cflag &= !libc::PARODD,
+    }
+    // SAFETY: as above
+    if unsafe { libc::ioctl(fd, libc::TCSETS2, &termios) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
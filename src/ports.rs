@@ -0,0 +1,55 @@
+use serialport::{SerialPortType, UsbPortInfo};
+
+// A Port as Listed in the Selector, with what the OS knows about a USB adapter behind it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortEntry {
+    pub name: String,
+    // e.g. "FTDI FT232R (0403:6001)"
+    pub usb: Option<String>,
+}
+
+impl PortEntry {
+    // A port that isn't in the list, such as a typed pipe or socket path
+    pub fn bare(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            usb: None,
+        }
+    }
+}
+
+impl std::fmt::Display for PortEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.usb {
+            Some(usb) => write!(f, "{} — {usb}", self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+// Serial ports currently present
+pub fn available() -> Vec<PortEntry> {
+    serialport::available_ports()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|port| PortEntry {
+            usb: match &port.port_type {
+                SerialPortType::UsbPort(info) => Some(describe(info)),
+                _ => None,
+            },
+            name: port.port_name,
+        })
+        .collect()
+}
+
+// Product strings often repeat the manufacturer ("FTDI" + "FTDI FT232R"), so it's only
+// prefixed when it adds something
+fn describe(info: &UsbPortInfo) -> String {
+    let ids = format!("({:04x}:{:04x})", info.vid, info.pid);
+    match (info.manufacturer.as_deref(), info.product.as_deref()) {
+        (Some(maker), Some(product)) if product.starts_with(maker) => format!("{product} {ids}"),
+        (Some(maker), Some(product)) => format!("{maker} {product} {ids}"),
+        (Some(name), None) | (None, Some(name)) => format!("{name} {ids}"),
+        (None, None) => format!("USB {ids}"),
+    }
+}
@@ -0,0 +1,325 @@
+use crate::parity::LineParity;
+use serialport::{DataBits, FlowControl, StopBits};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Saved Connection Settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    pub port: String,
+    pub baud_rate: u32,
+    pub data_bits: DataBits,
+    pub parity: LineParity,
+    pub stop_bits: StopBits,
+    pub flow_control: FlowControl,
+}
+
+impl Profile {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            port: String::new(),
+            baud_rate: 9600,
+            data_bits: DataBits::Eight,
+            parity: LineParity::None,
+            stop_bits: StopBits::One,
+            flow_control: FlowControl::None,
+        }
+    }
+    // e.g. "COM3, 115200 8N1, hardware flow control"
+    pub fn summary(&self) -> String {
+        let parity = self.parity.letter();
+        format!(
+            "{}, {} {}{parity}{}, {} flow control",
+            self.port,
+            self.baud_rate,
+            u8::from(self.data_bits),
+            u8::from(self.stop_bits),
+            self.flow_control.to_string().to_lowercase()
+        )
+    }
+    fn to_text(&self) -> String {
+        format!(
+            "[profile]\nname={}\nport={}\nbaud_rate={}\ndata_bits={}\nparity={}\nstop_bits={}\nflow_control={}\n",
+            self.name,
+            self.port,
+            self.baud_rate,
+            u8::from(self.data_bits),
+            self.parity,
+            u8::from(self.stop_bits),
+            self.flow_control
+        )
+    }
+    fn set(&mut self, key: &str, value: &str) {
+        match key {
+            "name" => self.name = value.to_string(),
+            "port" => self.port = value.to_string(),
+            "baud_rate" => self.baud_rate = value.parse().unwrap_or(self.baud_rate),
+            "data_bits" => self.data_bits = data_bits(value.parse().unwrap_or(8)),
+            "parity" => {
+                self.parity = LineParity::ALL
+                    .into_iter()
+                    .find(|parity| parity.to_string() == value)
+                    .unwrap_or(self.parity)
+            }
+            "stop_bits" => self.stop_bits = stop_bits(value),
+            "flow_control" => {
+                self.flow_control = [
+                    FlowControl::None,
+                    FlowControl::Software,
+                    FlowControl::Hardware,
+                ]
+                .into_iter()
+                .find(|flow| flow.to_string() == value)
+                .unwrap_or(self.flow_control)
+            }
+            _ => {}
+        }
+    }
+}
+
+impl std::fmt::Display for Profile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+fn data_bits(bits: u8) -> DataBits {
+    DataBits::try_from(bits).unwrap_or(DataBits::Eight)
+}
+
+// 1.5 stop bits isn't available, so it rounds up to 2
+fn stop_bits(value: &str) -> StopBits {
+    match value {
+        "1" => StopBits::One,
+        _ => StopBits::Two,
+    }
+}
+
+// Letters (minicom), names (TeraTerm), or PuTTY's numbering
+fn parity(value: &str) -> LineParity {
+    match value.to_ascii_lowercase().as_str() {
+        "o" | "odd" | "1" => LineParity::Odd,
+        "e" | "even" | "2" => LineParity::Even,
+        "m" | "mark" | "3" => LineParity::Mark,
+        "s" | "space" | "4" => LineParity::Space,
+        _ => LineParity::None,
+    }
+}
+
+fn profiles_path() -> io::Result<PathBuf> {
+    crate::workspace::config_dir()
+        .map(|dir| dir.join("profiles"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))
+}
+
+pub fn load() -> Vec<Profile> {
+    let Ok(text) = profiles_path().and_then(fs::read_to_string) else {
+        return Vec::new();
+    };
+    let mut profiles = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line == "[profile]" {
+            profiles.push(Profile::new(String::new()));
+        } else if let Some((key, value)) = line.split_once('=')
+            && let Some(profile) = profiles.last_mut()
+        {
+            profile.set(key.trim(), value.trim());
+        }
+    }
+    profiles
+}
+
+pub fn save(profiles: &[Profile]) -> io::Result<()> {
+    let path = profiles_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let text = profiles
+        .iter()
+        .map(Profile::to_text)
+        .collect::<Vec<_>>()
+        .join("\n");
+    fs::write(path, text)
+}
+
+// Read another terminal's saved sessions: a PuTTY registry export (.reg), a minicom
+// configuration (minirc.* / .dfl), or a TeraTerm .ini
+pub fn import(path: &Path) -> io::Result<Vec<Profile>> {
+    let bytes = fs::read(path)?;
+    // regedit writes UTF-16LE exports with a byte order mark
+    let text = match bytes.strip_prefix(&[0xFF, 0xFE]) {
+        Some(utf16) => String::from_utf16_lossy(
+            &utf16
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                .collect::<Vec<_>>(),
+        ),
+        None => String::from_utf8_lossy(&bytes).into_owned(),
+    };
+    let stem = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let profiles = if text.contains("SimonTatham\\PuTTY\\Sessions") {
+        import_putty(&text)
+    } else if text
+        .lines()
+        .any(|line| line.trim_start().starts_with("pu "))
+    {
+        vec![import_minicom(&text, &stem)]
+    } else if text.contains("[Tera Term]") {
+        vec![import_teraterm(&text, &stem)]
+    } else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a PuTTY registry export, minicom configuration, or TeraTerm .ini",
+        ));
+    };
+    Ok(profiles)
+}
+
+// Serial sessions only; PuTTY keeps SSH/telnet sessions in the same key
+fn import_putty(text: &str) -> Vec<Profile> {
+    let mut profiles = Vec::new();
+    let mut current: Option<(Profile, bool)> = None;
+    for line in text.lines().map(str::trim) {
+        if let Some(key) = line.strip_prefix('[').and_then(|key| key.strip_suffix(']')) {
+            profiles.extend(current.take().filter(|(_, serial)| *serial).map(|(p, _)| p));
+            if let Some((_, name)) = key.split_once("PuTTY\\Sessions\\") {
+                current = Some((
+                    Profile::new(format!("PuTTY: {}", unescape_putty(name))),
+                    false,
+                ));
+            }
+            continue;
+        }
+        let Some((profile, serial)) = current.as_mut() else {
+            continue;
+        };
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim_matches('"');
+        let number = value
+            .strip_prefix("dword:")
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .unwrap_or_default();
+        match key {
+            "Protocol" => *serial = value.trim_matches('"') == "serial",
+            "SerialLine" => profile.port = value.trim_matches('"').to_string(),
+            "SerialSpeed" => profile.baud_rate = number,
+            "SerialDataBits" => profile.data_bits = data_bits(number as u8),
+            "SerialStopHalfbits" => {
+                profile.stop_bits = match number {
+                    2 => StopBits::One,
+                    _ => StopBits::Two,
+                }
+            }
+            "SerialParity" => profile.parity = parity(&number.to_string()),
+            "SerialFlowControl" => {
+                profile.flow_control = match number {
+                    1 => FlowControl::Software,
+                    2 => FlowControl::Hardware,
+                    _ => FlowControl::None,
+                }
+            }
+            _ => {}
+        }
+    }
+    profiles.extend(current.filter(|(_, serial)| *serial).map(|(p, _)| p));
+    profiles
+}
+
+// PuTTY session names are percent-encoded in the registry
+fn unescape_putty(name: &str) -> String {
+    let mut bytes = Vec::new();
+    let mut rest = name;
+    while let Some(c) = rest.chars().next() {
+        let decoded = rest
+            .strip_prefix('%')
+            .and_then(|hex| hex.get(..2))
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match decoded {
+            Some(byte) => {
+                bytes.push(byte);
+                rest = &rest[3..];
+            }
+            None => {
+                bytes.extend_from_slice(c.to_string().as_bytes());
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn import_minicom(text: &str, file_name: &str) -> Profile {
+    let name = file_name.strip_prefix("minirc.").unwrap_or(file_name);
+    let mut profile = Profile::new(format!("minicom: {name}"));
+    let (mut rtscts, mut xonxoff) = (false, false);
+    for line in text.lines() {
+        let mut fields = line.split_whitespace();
+        if fields.next() != Some("pu") {
+            continue;
+        }
+        let (Some(key), Some(value)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        match key {
+            "port" => profile.port = value.to_string(),
+            "baudrate" => profile.baud_rate = value.parse().unwrap_or(profile.baud_rate),
+            "bits" => profile.data_bits = data_bits(value.parse().unwrap_or(8)),
+            "parity" => profile.parity = parity(value),
+            "stopbits" => profile.stop_bits = stop_bits(value),
+            "rtscts" => rtscts = value.eq_ignore_ascii_case("yes"),
+            "xonxoff" => xonxoff = value.eq_ignore_ascii_case("yes"),
+            _ => {}
+        }
+    }
+    profile.flow_control = match (rtscts, xonxoff) {
+        (true, _) => FlowControl::Hardware,
+        (_, true) => FlowControl::Software,
+        _ => FlowControl::None,
+    };
+    profile
+}
+
+fn import_teraterm(text: &str, file_name: &str) -> Profile {
+    let name = file_name
+        .rsplit_once('.')
+        .map_or(file_name, |(stem, _)| stem);
+    let mut profile = Profile::new(format!("TeraTerm: {name}"));
+    let mut section = "";
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') {
+            section = line;
+            continue;
+        }
+        if section != "[Tera Term]" {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "ComPort" => profile.port = format!("COM{value}"),
+            "BaudRate" => profile.baud_rate = value.parse().unwrap_or(profile.baud_rate),
+            "DataBit" => profile.data_bits = data_bits(value.parse().unwrap_or(8)),
+            "Parity" => profile.parity = parity(value),
+            "StopBit" => profile.stop_bits = stop_bits(value),
+            "FlowCtrl" => {
+                profile.flow_control = match value {
+                    "x" => FlowControl::Software,
+                    "hard" => FlowControl::Hardware,
+                    _ => FlowControl::None,
+                }
+            }
+            _ => {}
+        }
+    }
+    profile
+}
@@ -0,0 +1,499 @@
+use crate::ring::{self, Consumer, Producer};
+use bytes::{Bytes, BytesMut};
+use serialport::SerialPort;
+use std::collections::VecDeque;
+use std::io::ErrorKind;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU64, AtomicUsize, Ordering};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+// Bytes requested per read call when fewer are already waiting
+pub const READ_SIZE: usize = 16;
+// Cap on a read sized to everything the OS has buffered
+pub const MAX_READ_SIZE: usize = 64 * 1024;
+// Chunks the ring holds before the overflow policy kicks in
+pub const RING_CAPACITY: usize = 4096;
+
+// Events Passed From the Reader Thread to the UI
+pub enum RxEvent {
+    // Stamped in the reader thread the moment the read returned
+    Data { data: Bytes, at: Instant },
+    Error(String),
+}
+
+// What the Reader Does When the UI Falls Behind
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum OverflowPolicy {
+    DropOldest,
+    DropNewest,
+    Backpressure,
+}
+
+impl OverflowPolicy {
+    pub const ALL: [OverflowPolicy; 3] = [
+        OverflowPolicy::DropOldest,
+        OverflowPolicy::DropNewest,
+        OverflowPolicy::Backpressure,
+    ];
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => OverflowPolicy::DropOldest,
+            1 => OverflowPolicy::DropNewest,
+            _ => OverflowPolicy::Backpressure,
+        }
+    }
+    fn to_u8(self) -> u8 {
+        match self {
+            OverflowPolicy::DropOldest => 0,
+            OverflowPolicy::DropNewest => 1,
+            OverflowPolicy::Backpressure => 2,
+        }
+    }
+}
+
+impl std::fmt::Display for OverflowPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            OverflowPolicy::DropOldest => "Drop oldest",
+            OverflowPolicy::DropNewest => "Drop newest",
+            OverflowPolicy::Backpressure => "Pause reading",
+        };
+        write!(f, "{name}")
+    }
+}
+
+// Counters and Controls Shared With the Reader Thread
+struct Shared {
+    stop: AtomicBool,
+    policy: AtomicU8,
+    dropped_chunks: AtomicUsize,
+    dropped_bytes: AtomicU64,
+    // Times reading was paused because the ring was full
+    stalls: AtomicUsize,
+    // Oldest chunks the consumer should discard to make room for newer ones
+    skip: AtomicUsize,
+}
+
+impl Shared {
+    fn count_drop(&self, bytes: usize) {
+        self.dropped_chunks.fetch_add(1, Ordering::Relaxed);
+        self.dropped_bytes
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+}
+
+// Background Receive Thread
+//
+// Reads a clone of the open port continuously and queues what it gets in a ring buffer that
+// the UI drains on its own schedule, so a fast stream never floods the iced runtime.
+pub struct Reader {
+    shared: Arc<Shared>,
+    handle: Option<JoinHandle<()>>,
+    events: Consumer<RxEvent>,
+}
+
+impl Reader {
+    // At least `read_size` bytes are requested per read; the ring holds `capacity` chunks
+    pub fn spawn(
+        mut port: Box<dyn SerialPort>,
+        policy: OverflowPolicy,
+        read_size: usize,
+        capacity: usize,
+    ) -> Self {
+        let (producer, events) = ring::ring(capacity);
+        let shared = Arc::new(Shared {
+            stop: AtomicBool::new(false),
+            policy: AtomicU8::new(policy.to_u8()),
+            dropped_chunks: AtomicUsize::new(0),
+            dropped_bytes: AtomicU64::new(0),
+            stalls: AtomicUsize::new(0),
+            skip: AtomicUsize::new(0),
+        });
+        let handle = {
+            let shared = shared.clone();
+            std::thread::spawn(move || {
+                let mut queue = Queue {
+                    producer,
+                    stash: VecDeque::new(),
+                    capacity,
+                    shared: &shared,
+                };
+                let mut buffer = BytesMut::new();
+                while !shared.stop.load(Ordering::Relaxed) {
+                    queue.flush_stash();
+                    buffer.resize(read_len(port.as_ref(), read_size), 0);
+                    match port.read(&mut buffer) {
+                        Ok(0) => {}
+                        Ok(b) => {
+                            let at = Instant::now();
+                            buffer.truncate(b);
+                            queue.deliver(RxEvent::Data {
+                                data: buffer.split().freeze(),
+                                at,
+                            });
+                        }
+                        Err(e) if e.kind() == ErrorKind::TimedOut => {}
+                        Err(e) => {
+                            queue.deliver(RxEvent::Error(e.to_string()));
+                            break;
+                        }
+                    }
+                }
+                queue.finish();
+            })
+        };
+        Self {
+            shared,
+            handle: Some(handle),
+            events,
+        }
+    }
+    pub fn try_recv(&mut self) -> Option<RxEvent> {
+        // Make room the reader asked for under the drop-oldest policy
+        let skip = self.shared.skip.swap(0, Ordering::AcqRel);
+        for _ in 0..skip {
+            match self.events.pop() {
+                Some(RxEvent::Data { data, .. }) => self.shared.count_drop(data.len()),
+                event => return event,
+            }
+        }
+        self.events.pop()
+    }
+    pub fn set_policy(&self, policy: OverflowPolicy) {
+        self.shared.policy.store(policy.to_u8(), Ordering::Relaxed);
+    }
+    // Chunks waiting to be drained, and the ring's capacity
+    pub fn backlog(&self) -> (usize, usize) {
+        (self.events.len(), self.events.capacity())
+    }
+    // Chunks and bytes lost to overflow
+    pub fn dropped(&self) -> (usize, u64) {
+        (
+            self.shared.dropped_chunks.load(Ordering::Relaxed),
+            self.shared.dropped_bytes.load(Ordering::Relaxed),
+        )
+    }
+    pub fn stalls(&self) -> usize {
+        self.shared.stalls.load(Ordering::Relaxed)
+    }
+}
+
+// Everything waiting in the OS buffer is taken in one read, so a fast stream at high baud
+// isn't read a few bytes at a time and left to pile up
+pub fn read_len(port: &dyn SerialPort, read_size: usize) -> usize {
+    let waiting = port.bytes_to_read().map_or(0, |waiting| waiting as usize);
+    waiting.min(MAX_READ_SIZE).max(read_size).max(1)
+}
+
+impl Drop for Reader {
+    fn drop(&mut self) {
+        self.shared.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+// Reader-Side End of the Ring, applying the overflow policy when it is full
+struct Queue<'a> {
+    producer: Producer<RxEvent>,
+    // Newest chunks held back while the consumer discards the oldest ones in the ring
+    stash: VecDeque<RxEvent>,
+    capacity: usize,
+    shared: &'a Shared,
+}
+
+impl Queue<'_> {
+    fn flush_stash(&mut self) {
+        while let Some(event) = self.stash.pop_front() {
+            if let Err(event) = self.producer.push(event) {
+                self.stash.push_front(event);
+                break;
+            }
+        }
+    }
+    // Hand over everything held back before the thread exits, so the UI always sees the error
+    // that stopped it
+    fn finish(&mut self) {
+        self.flush_stash();
+        while !self.stash.is_empty() && !self.shared.stop.load(Ordering::Relaxed) {
+            std::thread::sleep(Duration::from_millis(1));
+            self.flush_stash();
+        }
+    }
+    fn deliver(&mut self, event: RxEvent) {
+        self.flush_stash();
+        let event = match self.stash.is_empty() {
+            true => match self.producer.push(event) {
+                Ok(()) => return,
+                Err(event) => event,
+            },
+            false => event,
+        };
+        match OverflowPolicy::from_u8(self.shared.policy.load(Ordering::Relaxed)) {
+            OverflowPolicy::DropNewest => match event {
+                RxEvent::Data { data, .. } => self.shared.count_drop(data.len()),
+                // An error ends the reader, so it's held for `finish` rather than dropped
+                event => self.stash.push_back(event),
+            },
+            OverflowPolicy::DropOldest => {
+                if self.stash.len() < self.capacity {
+                    self.shared.skip.fetch_add(1, Ordering::AcqRel);
+                } else if let Some(RxEvent::Data { data, .. }) = self.stash.pop_front() {
+                    self.shared.count_drop(data.len());
+                }
+                self.stash.push_back(event);
+            }
+            OverflowPolicy::Backpressure => {
+                // Stop reading until there's room; the OS buffer (and the device) take the strain
+                self.shared.stalls.fetch_add(1, Ordering::Relaxed);
+                self.stash.push_back(event);
+                while !self.stash.is_empty() && !self.shared.stop.load(Ordering::Relaxed) {
+                    std::thread::sleep(Duration::from_millis(1));
+                    self.flush_stash();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serialport::{ClearBuffer, DataBits, FlowControl, Parity, StopBits};
+    use std::io::{self, Read, Write};
+    use std::sync::Mutex;
+
+    // A port with `waiting` bytes in its OS buffer, numbered so their order can be checked.
+    // Once they're read it times out, or fails when `fail` is set; `broken` makes it unable to
+    // say how much is waiting. The size of every read that got data goes in `reads`.
+    #[derive(Default)]
+    struct FakePort {
+        waiting: usize,
+        read: usize,
+        fail: bool,
+        broken: bool,
+        reads: Arc<Mutex<Vec<usize>>>,
+    }
+
+    impl FakePort {
+        fn new(waiting: usize) -> Self {
+            Self {
+                waiting,
+                ..Self::default()
+            }
+        }
+    }
+
+    fn byte(at: usize) -> u8 {
+        (at % 251) as u8
+    }
+
+    impl Read for FakePort {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            if self.waiting == 0 {
+                if self.fail {
+                    return Err(io::Error::new(ErrorKind::BrokenPipe, "unplugged"));
+                }
+                std::thread::sleep(Duration::from_millis(1));
+                return Err(io::Error::new(ErrorKind::TimedOut, "timed out"));
+            }
+            self.reads.lock().unwrap().push(buffer.len());
+            let len = buffer.len().min(self.waiting);
+            for (i, slot) in buffer[..len].iter_mut().enumerate() {
+                *slot = byte(self.read + i);
+            }
+            self.read += len;
+            self.waiting -= len;
+            Ok(len)
+        }
+    }
+
+    impl Write for FakePort {
+        fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+            Ok(buffer.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SerialPort for FakePort {
+        fn name(&self) -> Option<String> {
+            Some(String::from("fake"))
+        }
+        fn baud_rate(&self) -> serialport::Result<u32> {
+            Ok(3_000_000)
+        }
+        fn data_bits(&self) -> serialport::Result<DataBits> {
+            Ok(DataBits::Eight)
+        }
+        fn flow_control(&self) -> serialport::Result<FlowControl> {
+            Ok(FlowControl::None)
+        }
+        fn parity(&self) -> serialport::Result<Parity> {
+            Ok(Parity::None)
+        }
+        fn stop_bits(&self) -> serialport::Result<StopBits> {
+            Ok(StopBits::One)
+        }
+        fn timeout(&self) -> Duration {
+            Duration::ZERO
+        }
+        fn set_baud_rate(&mut self, _: u32) -> serialport::Result<()> {
+            Ok(())
+        }
+        fn set_data_bits(&mut self, _: DataBits) -> serialport::Result<()> {
+            Ok(())
+        }
+        fn set_flow_control(&mut self, _: FlowControl) -> serialport::Result<()> {
+            Ok(())
+        }
+        fn set_parity(&mut self, _: Parity) -> serialport::Result<()> {
+            Ok(())
+        }
+        fn set_stop_bits(&mut self, _: StopBits) -> serialport::Result<()> {
+            Ok(())
+        }
+        fn set_timeout(&mut self, _: Duration) -> serialport::Result<()> {
+            Ok(())
+        }
+        fn write_request_to_send(&mut self, _: bool) -> serialport::Result<()> {
+            Ok(())
+        }
+        fn write_data_terminal_ready(&mut self, _: bool) -> serialport::Result<()> {
+            Ok(())
+        }
+        fn read_clear_to_send(&mut self) -> serialport::Result<bool> {
+            Ok(false)
+        }
+        fn read_data_set_ready(&mut self) -> serialport::Result<bool> {
+            Ok(false)
+        }
+        fn read_ring_indicator(&mut self) -> serialport::Result<bool> {
+            Ok(false)
+        }
+        fn read_carrier_detect(&mut self) -> serialport::Result<bool> {
+            Ok(false)
+        }
+        fn bytes_to_read(&self) -> serialport::Result<u32> {
+            match self.broken {
+                true => Err(serialport::Error::new(
+                    serialport::ErrorKind::NoDevice,
+                    "unplugged",
+                )),
+                false => Ok(self.waiting as u32),
+            }
+        }
+        fn bytes_to_write(&self) -> serialport::Result<u32> {
+            Ok(0)
+        }
+        fn clear(&self, _: ClearBuffer) -> serialport::Result<()> {
+            Ok(())
+        }
+        fn try_clone(&self) -> serialport::Result<Box<dyn SerialPort>> {
+            Err(serialport::Error::new(
+                serialport::ErrorKind::Unknown,
+                "not cloneable",
+            ))
+        }
+        fn set_break(&self) -> serialport::Result<()> {
+            Ok(())
+        }
+        fn clear_break(&self) -> serialport::Result<()> {
+            Ok(())
+        }
+    }
+
+    // Everything the reader hands over until `until` says it's enough, or a few seconds pass
+    fn collect(reader: &mut Reader, until: impl Fn(&[RxEvent]) -> bool) -> Vec<RxEvent> {
+        let mut events = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !until(&events) && Instant::now() < deadline {
+            match reader.try_recv() {
+                Some(event) => events.push(event),
+                None => std::thread::sleep(Duration::from_millis(1)),
+            }
+        }
+        events
+    }
+
+    fn data_len(events: &[RxEvent]) -> usize {
+        events
+            .iter()
+            .map(|event| match event {
+                RxEvent::Data { data, .. } => data.len(),
+                RxEvent::Error(_) => 0,
+            })
+            .sum()
+    }
+
+    // A few MB already waiting, as at high baud when the UI was busy: each read takes as much
+    // as the cap allows, and every byte arrives in order with nothing dropped
+    #[test]
+    fn reader_streams_a_backlog_in_capped_reads() {
+        const SIZE: usize = 4 * 1024 * 1024 + 1000;
+        let port = FakePort::new(SIZE);
+        let reads = Arc::clone(&port.reads);
+        let mut reader = Reader::spawn(
+            Box::new(port),
+            OverflowPolicy::DropNewest,
+            READ_SIZE,
+            RING_CAPACITY,
+        );
+        let events = collect(&mut reader, |events| data_len(events) >= SIZE);
+        let mut at = 0;
+        for event in &events {
+            let RxEvent::Data { data, .. } = event else {
+                panic!("unexpected error from the reader");
+            };
+            assert!(data.iter().enumerate().all(|(i, &got)| got == byte(at + i)));
+            at += data.len();
+        }
+        assert_eq!(at, SIZE);
+        assert_eq!(reader.dropped(), (0, 0));
+        let mut expected = vec![MAX_READ_SIZE; SIZE / MAX_READ_SIZE];
+        expected.push((SIZE % MAX_READ_SIZE).max(READ_SIZE));
+        assert_eq!(*reads.lock().unwrap(), expected);
+        assert_eq!(events.len(), SIZE.div_ceil(MAX_READ_SIZE));
+    }
+
+    // The error that stops the reader gets through a full ring whatever the overflow policy,
+    // after the UI has fallen behind
+    #[test]
+    fn reader_error_survives_a_full_ring() {
+        for policy in OverflowPolicy::ALL {
+            let port = FakePort {
+                fail: true,
+                ..FakePort::new(MAX_READ_SIZE * 8)
+            };
+            let mut reader = Reader::spawn(Box::new(port), policy, READ_SIZE, 2);
+            std::thread::sleep(Duration::from_millis(100));
+            let events = collect(&mut reader, |events| {
+                matches!(events.last(), Some(RxEvent::Error(_)))
+            });
+            assert!(
+                matches!(events.last(), Some(RxEvent::Error(_))),
+                "no error under {policy}"
+            );
+        }
+    }
+
+    #[test]
+    fn read_len_falls_back_to_the_read_size() {
+        let idle = FakePort::new(0);
+        assert_eq!(read_len(&idle, READ_SIZE), READ_SIZE);
+        assert_eq!(read_len(&idle, 0), 1);
+        let few = FakePort::new(READ_SIZE - 1);
+        assert_eq!(read_len(&few, READ_SIZE), READ_SIZE);
+        let many = FakePort::new(MAX_READ_SIZE * 3);
+        assert_eq!(read_len(&many, READ_SIZE), MAX_READ_SIZE);
+        let broken = FakePort {
+            broken: true,
+            ..FakePort::new(MAX_READ_SIZE)
+        };
+        assert_eq!(read_len(&broken, READ_SIZE), READ_SIZE);
+    }
+}
@@ -0,0 +1,72 @@
+use std::cell::UnsafeCell;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Bounded Single-Producer Single-Consumer Ring Buffer
+//
+// `head` and `tail` only ever grow; a slot is `index % capacity`. The producer owns `tail` and
+// the consumer owns `head`, so each side only reads the other's counter and never blocks.
+struct Ring<T> {
+    slots: Box<[UnsafeCell<Option<T>>]>,
+    head: AtomicUsize,
+    tail: AtomicUsize,
+}
+
+// SAFETY: a slot is only touched by the producer before `tail` is published past it and only
+// by the consumer before `head` is published past it, so no slot is ever shared
+unsafe impl<T: Send> Sync for Ring<T> {}
+
+pub struct Producer<T> {
+    ring: Arc<Ring<T>>,
+}
+
+pub struct Consumer<T> {
+    ring: Arc<Ring<T>>,
+}
+
+pub fn ring<T>(capacity: usize) -> (Producer<T>, Consumer<T>) {
+    let ring = Arc::new(Ring {
+        slots: (0..capacity.max(1))
+            .map(|_| UnsafeCell::new(None))
+            .collect(),
+        head: AtomicUsize::new(0),
+        tail: AtomicUsize::new(0),
+    });
+    (Producer { ring: ring.clone() }, Consumer { ring })
+}
+
+impl<T> Producer<T> {
+    // Hand the item back when the ring is full
+    pub fn push(&mut self, item: T) -> Result<(), T> {
+        let ring = &*self.ring;
+        let tail = ring.tail.load(Ordering::Relaxed);
+        if tail - ring.head.load(Ordering::Acquire) == ring.slots.len() {
+            return Err(item);
+        }
+        // SAFETY: the slot at `tail` is free and the consumer won't read it until `tail` moves
+        unsafe { *ring.slots[tail % ring.slots.len()].get() = Some(item) };
+        ring.tail.store(tail + 1, Ordering::Release);
+        Ok(())
+    }
+}
+
+impl<T> Consumer<T> {
+    pub fn pop(&mut self) -> Option<T> {
+        let ring = &*self.ring;
+        let head = ring.head.load(Ordering::Relaxed);
+        if head == ring.tail.load(Ordering::Acquire) {
+            return None;
+        }
+        // SAFETY: the slot at `head` was published by the producer and it won't reuse it until
+        // `head` moves past it
+        let item = unsafe { (*ring.slots[head % ring.slots.len()].get()).take() };
+        ring.head.store(head + 1, Ordering::Release);
+        item
+    }
+    pub fn len(&self) -> usize {
+        self.ring.tail.load(Ordering::Acquire) - self.ring.head.load(Ordering::Relaxed)
+    }
+    pub fn capacity(&self) -> usize {
+        self.ring.slots.len()
+    }
+}
@@ -0,0 +1,76 @@
+use crate::timestamp::utc_offset;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Scheduled Sends
+//
+// When a command goes out is given as a countdown up to a week, `30`, `90s`, `5m` or `1h`, or
+// as a clock time, `14:05` or `14:05:30`, which is the next time the clock shows it: later
+// today, or tomorrow when that's already past.
+
+const DAY: i64 = 86_400;
+// The longest countdown, a week
+const MAX_DELAY: Duration = Duration::from_secs(7 * DAY as u64);
+
+// How long from `now` until the send
+pub fn parse(input: &str, now: SystemTime) -> Result<Duration, String> {
+    let input = input.trim();
+    if input.contains(':') {
+        return until_clock(input, now);
+    }
+    let (number, unit) = match input.find(|c: char| !c.is_ascii_digit() && c != '.') {
+        Some(split) => input.split_at(split),
+        None => (input, "s"),
+    };
+    let seconds = match unit.trim() {
+        "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        _ => return Err(format!("'{input}' isn't a delay like 30s, 5m or 1h")),
+    };
+    number
+        .parse::<f64>()
+        .ok()
+        .and_then(|number| Duration::try_from_secs_f64(number * seconds).ok())
+        .filter(|delay| *delay <= MAX_DELAY)
+        .ok_or_else(|| format!("'{input}' isn't a delay like 30s, 5m or 1h"))
+}
+
+fn until_clock(input: &str, now: SystemTime) -> Result<Duration, String> {
+    let invalid = || format!("'{input}' isn't a clock time like 14:05 or 14:05:30");
+    let parts = input
+        .split(':')
+        .map(|part| part.trim().parse::<i64>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (hour, minute, second) = match parts[..] {
+        [hour, minute] => (hour, minute, 0),
+        [hour, minute, second] => (hour, minute, second),
+        _ => return Err(invalid()),
+    };
+    if !(0..24).contains(&hour) || !(0..60).contains(&minute) || !(0..60).contains(&second) {
+        return Err(invalid());
+    }
+    let unix = now
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| String::from("System clock is before 1970"))?;
+    let local = unix.as_secs() as i64 + utc_offset(unix.as_secs() as i64);
+    let target = hour * 3600 + minute * 60 + second;
+    let seconds = (target - local.rem_euclid(DAY)).rem_euclid(DAY);
+    // Whole seconds on the clock, so the part of the current second already gone comes off
+    Ok(Duration::from_secs(seconds as u64)
+        .saturating_sub(Duration::from_nanos(u64::from(unix.subsec_nanos()))))
+}
+
+// e.g. "1:05:09", or "42 s" under a minute
+pub fn countdown(left: Duration) -> String {
+    let seconds = left.as_secs() + u64::from(left.subsec_nanos() > 0);
+    match seconds {
+        0..60 => format!("{seconds} s"),
+        60..3600 => format!("{}:{:02}", seconds / 60, seconds % 60),
+        _ => format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        ),
+    }
+}
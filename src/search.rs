@@ -0,0 +1,51 @@
+use regex::{Regex, RegexBuilder};
+use std::ops::Range;
+
+// Log Search
+//
+// Plain text or a regular expression, matched against the log lines as they're displayed.
+// Both go through the regex crate, which matches in time linear in the line, so a pattern
+// like (a*)*b can't freeze the UI while it's typed; plain text is escaped first.
+pub struct Search {
+    pattern: Regex,
+}
+
+// Compiled patterns past this many bytes are refused, e.g. a{1000}{1000}
+const SIZE_LIMIT: usize = 1 << 20;
+
+impl Search {
+    pub fn new(query: &str, regex: bool, ignore_case: bool) -> Result<Self, String> {
+        let query = match regex {
+            true => query.to_string(),
+            false => regex::escape(query),
+        };
+        let pattern = RegexBuilder::new(&query)
+            .case_insensitive(ignore_case)
+            .size_limit(SIZE_LIMIT)
+            .build()
+            .map_err(|e| match e {
+                regex::Error::CompiledTooBig(_) => String::from("pattern too complex"),
+                // The last line of a syntax error says what's wrong, after the pattern and a caret
+                e => e
+                    .to_string()
+                    .lines()
+                    .last()
+                    .map(|line| line.trim_start_matches("error: ").to_string())
+                    .unwrap_or_default(),
+            })?;
+        Ok(Self { pattern })
+    }
+    // Byte ranges of every non-empty, non-overlapping match, left to right
+    pub fn find_all(&self, text: &str) -> Vec<Range<usize>> {
+        self.pattern
+            .find_iter(text)
+            .map(|found| found.range())
+            .filter(|range| !range.is_empty())
+            .collect()
+    }
+    pub fn is_match(&self, text: &str) -> bool {
+        self.pattern
+            .find_iter(text)
+            .any(|found| !found.range().is_empty())
+    }
+}
@@ -0,0 +1,86 @@
+use crate::workspace;
+use std::fs;
+use std::io;
+
+// Choices Made in the First-Run Wizard
+#[derive(Debug, Clone, Default)]
+pub struct Preferences {
+    pub port: Option<String>,
+    pub theme: Option<String>,
+}
+
+// Saved preferences, or None until the wizard has been completed once
+pub fn load() -> Option<Preferences> {
+    let path = workspace::config_dir()?.join("settings");
+    let text = fs::read_to_string(path).ok()?;
+    let mut preferences = Preferences::default();
+    for (key, value) in text.lines().filter_map(|line| line.split_once('=')) {
+        let value = Some(value.trim().to_string()).filter(|value| !value.is_empty());
+        match key.trim() {
+            "port" => preferences.port = value,
+            "theme" => preferences.theme = value,
+            _ => {}
+        }
+    }
+    Some(preferences)
+}
+
+pub fn save(preferences: &Preferences) -> io::Result<()> {
+    let dir = workspace::config_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+    fs::create_dir_all(&dir)?;
+    let text = format!(
+        "port={}\ntheme={}\n",
+        preferences.port.as_deref().unwrap_or_default(),
+        preferences.theme.as_deref().unwrap_or_default()
+    );
+    fs::write(dir.join("settings"), text)
+}
+
+// Ports the current user can't open, with what to do about it
+#[cfg(target_os = "linux")]
+pub fn permission_problems(ports: &[String]) -> Vec<String> {
+    use std::ffi::CString;
+    use std::os::unix::fs::MetadataExt;
+
+    let user = std::env::var("USER").unwrap_or_default();
+    // gid -> (group name, members) from the group database
+    let groups = fs::read_to_string("/etc/group").unwrap_or_default();
+    let group = |gid: u32| {
+        groups.lines().find_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let id = fields.nth(1)?.parse::<u32>().ok()?;
+            let members = fields.next().unwrap_or_default();
+            (id == gid).then(|| (name, members.split(',').any(|member| member == user)))
+        })
+    };
+    let mut problems = Vec::new();
+    for port in ports {
+        let Ok(path) = CString::new(port.as_str()) else {
+            continue;
+        };
+        // SAFETY: `path` is a valid NUL-terminated string for the duration of the call
+        if unsafe { libc::access(path.as_ptr(), libc::R_OK | libc::W_OK) } == 0 {
+            continue;
+        }
+        let problem = match fs::metadata(port).ok().and_then(|meta| group(meta.gid())) {
+            Some((name, true)) => format!(
+                "{port}: you are in the '{name}' group, but this session started before you \
+                 were added. Log out and back in."
+            ),
+            Some((name, false)) => format!(
+                "{port}: owned by the '{name}' group. Run \"sudo usermod -aG {name} {user}\", \
+                 then log out and back in."
+            ),
+            None => format!("{port}: no read/write access for this user."),
+        };
+        problems.push(problem);
+    }
+    problems
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn permission_problems(_ports: &[String]) -> Vec<String> {
+    Vec::new()
+}
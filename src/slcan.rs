@@ -0,0 +1,224 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::Duration;
+
+// Frames kept for the table, oldest dropped first
+const MAX_FRAMES: usize = 500;
+// The longest line an adapter sends is an extended frame with a timestamp, 30 characters
+const MAX_LINE: usize = 64;
+const BELL: u8 = 0x07;
+
+// Bitrates S0 to S8 of the LAWICEL protocol
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Bitrate(u8);
+
+impl Bitrate {
+    pub const ALL: [Bitrate; 9] = [
+        Bitrate(0),
+        Bitrate(1),
+        Bitrate(2),
+        Bitrate(3),
+        Bitrate(4),
+        Bitrate(5),
+        Bitrate(6),
+        Bitrate(7),
+        Bitrate(8),
+    ];
+    pub const DEFAULT: Bitrate = Bitrate(6);
+    fn kbits(self) -> u32 {
+        [10, 20, 50, 100, 125, 250, 500, 800, 1000][usize::from(self.0)]
+    }
+    // Close first, since the bitrate can only change on a closed channel
+    pub fn open_command(self) -> Vec<u8> {
+        format!("C\rS{}\rO\r", self.0).into_bytes()
+    }
+}
+
+impl fmt::Display for Bitrate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kbits() {
+            1000 => write!(f, "1 Mbit/s"),
+            kbits => write!(f, "{kbits} kbit/s"),
+        }
+    }
+}
+
+pub const CLOSE_COMMAND: &[u8] = b"C\r";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CanFrame {
+    pub id: u32,
+    pub extended: bool,
+    // A remote request has a DLC but no data
+    pub remote: bool,
+    pub dlc: u8,
+    pub data: Vec<u8>,
+    // The adapter's own milliseconds, 0 to 59999, when it has timestamps on
+    pub timestamp: Option<u16>,
+}
+
+impl CanFrame {
+    pub fn id_text(&self) -> String {
+        match self.extended {
+            true => format!("{:08X}", self.id),
+            false => format!("{:03X}", self.id),
+        }
+    }
+    pub fn data_text(&self) -> String {
+        match self.remote {
+            true => String::from("remote request"),
+            false => self
+                .data
+                .iter()
+                .map(|byte| format!("{byte:02X}"))
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+    // The command that sends this frame, e.g. t1232AABB\r
+    pub fn encode(&self) -> Vec<u8> {
+        let kind = match (self.extended, self.remote) {
+            (false, false) => 't',
+            (true, false) => 'T',
+            (false, true) => 'r',
+            (true, true) => 'R',
+        };
+        let data = match self.remote {
+            true => String::new(),
+            false => hex::encode_upper(&self.data),
+        };
+        format!("{kind}{}{}{data}\r", self.id_text(), self.dlc).into_bytes()
+    }
+}
+
+// A received frame like t1232AABB or T1234567881122334455667788, with an optional
+// 4-digit timestamp at the end
+pub fn parse_frame(line: &str) -> Option<CanFrame> {
+    let (extended, remote) = match line.as_bytes().first()? {
+        b't' => (false, false),
+        b'T' => (true, false),
+        b'r' => (false, true),
+        b'R' => (true, true),
+        _ => return None,
+    };
+    let rest = &line[1..];
+    let (id_len, max_id) = match extended {
+        true => (8, 0x1FFF_FFFF),
+        false => (3, 0x7FF),
+    };
+    let id = u32::from_str_radix(rest.get(..id_len)?, 16)
+        .ok()
+        .filter(|id| *id <= max_id)?;
+    let dlc = rest
+        .get(id_len..id_len + 1)?
+        .parse::<u8>()
+        .ok()
+        .filter(|dlc| *dlc <= 8)?;
+    let at = id_len + 1;
+    let data_len = match remote {
+        true => 0,
+        false => usize::from(dlc) * 2,
+    };
+    let data = hex::decode(rest.get(at..at + data_len)?).ok()?;
+    let timestamp = match &rest[at + data_len..] {
+        "" => None,
+        tail if tail.len() == 4 => Some(u16::from_str_radix(tail, 16).ok()?),
+        _ => return None,
+    };
+    Some(CanFrame {
+        id,
+        extended,
+        remote,
+        dlc,
+        data,
+        timestamp,
+    })
+}
+
+// A frame from the compose form: the ID in hex, and the data in hex, spaces allowed, or for a
+// remote request the DLC
+pub fn compose(id: &str, data: &str, extended: bool, remote: bool) -> Result<CanFrame, String> {
+    let max_id = if extended { 0x1FFF_FFFF } else { 0x7FF };
+    let id = u32::from_str_radix(id.trim().trim_start_matches("0x"), 16)
+        .ok()
+        .filter(|id| *id <= max_id)
+        .ok_or_else(|| format!("Invalid CAN ID '{}': enter hex up to {max_id:X}", id.trim()))?;
+    let (dlc, data) = match remote {
+        true => {
+            let dlc =
+                match data.trim() {
+                    "" => 0,
+                    dlc => dlc.parse().ok().filter(|dlc| *dlc <= 8).ok_or_else(|| {
+                        format!("Invalid DLC '{dlc}' for a remote request: 0 to 8")
+                    })?,
+                };
+            (dlc, Vec::new())
+        }
+        false => {
+            let digits = data.split_whitespace().collect::<String>();
+            let data = hex::decode(&digits)
+                .map_err(|_| format!("Invalid CAN data '{}': enter hex bytes", data.trim()))?;
+            if data.len() > 8 {
+                return Err(format!("{} data bytes, a CAN frame takes 8", data.len()));
+            }
+            (data.len() as u8, data)
+        }
+    };
+    Ok(CanFrame {
+        id,
+        extended,
+        remote,
+        dlc,
+        data,
+        timestamp: None,
+    })
+}
+
+// SLCAN Decoder: adapter output in, frames and replies counted
+#[derive(Default)]
+pub struct Decoder {
+    // With the time since the port opened
+    pub frames: VecDeque<(Duration, CanFrame)>,
+    pub received: u64,
+    // The adapter's z or Z for each frame it sent, and its BEL for a command it refused
+    pub sent: u64,
+    pub errors: u64,
+    partial: String,
+}
+
+impl Decoder {
+    pub fn feed(&mut self, data: &[u8], time: Duration) {
+        for &byte in data {
+            match byte {
+                b'\r' => {
+                    let line = std::mem::take(&mut self.partial);
+                    if let Some(frame) = parse_frame(&line) {
+                        self.received += 1;
+                        if self.frames.len() == MAX_FRAMES {
+                            self.frames.pop_front();
+                        }
+                        self.frames.push_back((time, frame));
+                    } else if line == "z" || line == "Z" {
+                        self.sent += 1;
+                    }
+                }
+                BELL => {
+                    self.errors += 1;
+                    self.partial.clear();
+                }
+                byte if byte.is_ascii_graphic() => {
+                    if self.partial.len() < MAX_LINE {
+                        self.partial.push(char::from(byte));
+                    } else {
+                        self.partial.clear();
+                    }
+                }
+                // Line feeds some adapters add, and noise
+                _ => {}
+            }
+        }
+    }
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
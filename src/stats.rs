@@ -0,0 +1,231 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// Upper bounds (bytes per second) of the throughput histogram buckets; the last is open-ended
+const HISTOGRAM_BOUNDS: [u64; 5] = [0, 100, 1_000, 10_000, 100_000];
+
+// Per-Connection Traffic Counters
+pub struct SessionStats {
+    port: String,
+    opened: Instant,
+    opened_at: SystemTime,
+    pub bytes_rx: u64,
+    pub bytes_tx: u64,
+    pub frames_rx: u64,
+    pub frames_tx: u64,
+    // Frames or lines the framer split the received bytes into
+    pub lines_rx: u64,
+    pub errors: u64,
+    // Bytes moved in each whole second since the port was opened
+    per_second: Vec<u64>,
+    // Gaps between received chunks, in microseconds, from reader-thread timestamps
+    last_rx: Option<Instant>,
+    rx_gap_min: u64,
+    rx_gap_max: u64,
+    rx_gap_total: u64,
+    rx_gaps: u64,
+}
+
+impl SessionStats {
+    pub fn new(port: &str) -> Self {
+        Self {
+            port: port.to_string(),
+            opened: Instant::now(),
+            opened_at: SystemTime::now(),
+            bytes_rx: 0,
+            bytes_tx: 0,
+            frames_rx: 0,
+            frames_tx: 0,
+            lines_rx: 0,
+            errors: 0,
+            per_second: Vec::new(),
+            last_rx: None,
+            rx_gap_min: u64::MAX,
+            rx_gap_max: 0,
+            rx_gap_total: 0,
+            rx_gaps: 0,
+        }
+    }
+    pub fn opened_at(&self) -> SystemTime {
+        self.opened_at
+    }
+    // Wall-clock time of an instant during the session
+    pub fn wall_time(&self, at: Instant) -> SystemTime {
+        self.opened_at + self.since_open(at)
+    }
+    pub fn elapsed(&self) -> Duration {
+        self.opened.elapsed()
+    }
+    // Time since the port was opened, the reference for log timestamps
+    pub fn since_open(&self, at: Instant) -> Duration {
+        at.saturating_duration_since(self.opened)
+    }
+    pub fn record_rx(&mut self, bytes: usize, at: Instant) {
+        self.bytes_rx += bytes as u64;
+        self.frames_rx += 1;
+        self.add_throughput(bytes, at);
+        if let Some(last) = self.last_rx.replace(at) {
+            let gap = at.saturating_duration_since(last).as_micros() as u64;
+            self.rx_gap_min = self.rx_gap_min.min(gap);
+            self.rx_gap_max = self.rx_gap_max.max(gap);
+            self.rx_gap_total += gap;
+            self.rx_gaps += 1;
+        }
+    }
+    // Smallest, mean, and largest gap between received chunks in microseconds
+    fn rx_gaps(&self) -> (u64, u64, u64) {
+        match self.rx_gaps {
+            0 => (0, 0, 0),
+            n => (self.rx_gap_min, self.rx_gap_total / n, self.rx_gap_max),
+        }
+    }
+    pub fn record_tx(&mut self, bytes: usize, at: Instant) {
+        self.bytes_tx += bytes as u64;
+        self.frames_tx += 1;
+        self.add_throughput(bytes, at);
+    }
+    pub fn record_line(&mut self) {
+        self.lines_rx += 1;
+    }
+    pub fn record_error(&mut self) {
+        self.errors += 1;
+    }
+    fn add_throughput(&mut self, bytes: usize, at: Instant) {
+        let second = at.duration_since(self.opened).as_secs() as usize;
+        if self.per_second.len() <= second {
+            self.per_second.resize(second + 1, 0);
+        }
+        self.per_second[second] += bytes as u64;
+    }
+    // Number of seconds whose throughput fell into each bucket of HISTOGRAM_BOUNDS
+    fn histogram(&self) -> [u64; HISTOGRAM_BOUNDS.len() + 1] {
+        let mut buckets = [0; HISTOGRAM_BOUNDS.len() + 1];
+        let seconds = self.opened.elapsed().as_secs() as usize;
+        for second in 0..seconds.max(self.per_second.len()) {
+            let bytes = self.per_second.get(second).copied().unwrap_or(0);
+            let bucket = HISTOGRAM_BOUNDS
+                .iter()
+                .position(|bound| bytes <= *bound)
+                .unwrap_or(HISTOGRAM_BOUNDS.len());
+            buckets[bucket] += 1;
+        }
+        buckets
+    }
+    fn bucket_label(bucket: usize) -> String {
+        match bucket {
+            0 => "0".to_string(),
+            b if b < HISTOGRAM_BOUNDS.len() => {
+                format!("{}-{}", HISTOGRAM_BOUNDS[b - 1] + 1, HISTOGRAM_BOUNDS[b])
+            }
+            _ => format!(">{}", HISTOGRAM_BOUNDS[HISTOGRAM_BOUNDS.len() - 1]),
+        }
+    }
+    fn opened_unix(&self) -> u64 {
+        self.opened_at
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("field,value\n");
+        let _ = writeln!(csv, "port,{}", self.port.replace(',', ";"));
+        let _ = writeln!(csv, "opened_unix,{}", self.opened_unix());
+        let _ = writeln!(csv, "duration_s,{:.3}", self.opened.elapsed().as_secs_f64());
+        let _ = writeln!(csv, "bytes_rx,{}", self.bytes_rx);
+        let _ = writeln!(csv, "bytes_tx,{}", self.bytes_tx);
+        let _ = writeln!(csv, "frames_rx,{}", self.frames_rx);
+        let _ = writeln!(csv, "frames_tx,{}", self.frames_tx);
+        let _ = writeln!(csv, "lines_rx,{}", self.lines_rx);
+        let _ = writeln!(csv, "errors,{}", self.errors);
+        let (min, mean, max) = self.rx_gaps();
+        let _ = writeln!(csv, "rx_gap_min_us,{min}");
+        let _ = writeln!(csv, "rx_gap_mean_us,{mean}");
+        let _ = writeln!(csv, "rx_gap_max_us,{max}");
+        for (bucket, seconds) in self.histogram().iter().enumerate() {
+            let _ = writeln!(
+                csv,
+                "seconds_at_{}_Bps,{seconds}",
+                Self::bucket_label(bucket)
+            );
+        }
+        csv
+    }
+    pub fn to_json(&self) -> String {
+        let histogram = self
+            .histogram()
+            .iter()
+            .enumerate()
+            .map(|(bucket, seconds)| format!("\"{}\": {seconds}", Self::bucket_label(bucket)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut json = String::from("{\n");
+        let _ = writeln!(json, "  \"port\": {},", json_string(&self.port));
+        let _ = writeln!(json, "  \"opened_unix\": {},", self.opened_unix());
+        let _ = writeln!(
+            json,
+            "  \"duration_s\": {:.3},",
+            self.opened.elapsed().as_secs_f64()
+        );
+        let _ = writeln!(json, "  \"bytes_rx\": {},", self.bytes_rx);
+        let _ = writeln!(json, "  \"bytes_tx\": {},", self.bytes_tx);
+        let _ = writeln!(json, "  \"frames_rx\": {},", self.frames_rx);
+        let _ = writeln!(json, "  \"frames_tx\": {},", self.frames_tx);
+        let _ = writeln!(json, "  \"lines_rx\": {},", self.lines_rx);
+        let _ = writeln!(json, "  \"errors\": {},", self.errors);
+        let (min, mean, max) = self.rx_gaps();
+        let _ = writeln!(
+            json,
+            "  \"rx_gap_us\": {{ \"min\": {min}, \"mean\": {mean}, \"max\": {max} }},"
+        );
+        let _ = writeln!(json, "  \"throughput_histogram_Bps\": {{ {histogram} }}");
+        json.push_str("}\n");
+        json
+    }
+    // Write the summary as both CSV and JSON next to each other, returning the CSV path. A
+    // session label goes into the file name, e.g. serial-session-motor-controller-1700000000.
+    pub fn export(&self, dir: &Path, label: &str) -> std::io::Result<PathBuf> {
+        let slug = slug(label);
+        let name = match slug.is_empty() {
+            true => format!("serial-session-{}", self.opened_unix()),
+            false => format!("serial-session-{slug}-{}", self.opened_unix()),
+        };
+        let csv_path = dir.join(format!("{name}.csv"));
+        std::fs::write(&csv_path, self.to_csv())?;
+        std::fs::write(dir.join(format!("{name}.json")), self.to_json())?;
+        Ok(csv_path)
+    }
+}
+
+// A session label made safe for a file name, e.g. "Motor Controller" to motor-controller
+pub fn slug(label: &str) -> String {
+    label
+        .trim()
+        .chars()
+        .map(|c| match c.is_alphanumeric() {
+            true => c.to_ascii_lowercase(),
+            false => '-',
+        })
+        .collect()
+}
+
+// Quote and escape a string for embedding in JSON
+pub fn json_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if u32::from(c) < 0x20 => {
+                let _ = write!(quoted, "\\u{:04x}", u32::from(c));
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,104 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Log Timestamps
+//
+// Every entry keeps the wall-clock time it was logged, and received data also its time since
+// the port opened, so the format can be switched at any point without losing anything.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TimestampFormat {
+    Off,
+    Clock,
+    Iso8601,
+    SinceConnect,
+}
+
+impl TimestampFormat {
+    pub const ALL: [TimestampFormat; 4] = [
+        TimestampFormat::Off,
+        TimestampFormat::Clock,
+        TimestampFormat::Iso8601,
+        TimestampFormat::SinceConnect,
+    ];
+
+    // `since` is the time since the port opened when it's known more precisely than from
+    // `at`, and `opened` when the port was opened
+    pub fn format(
+        self,
+        at: SystemTime,
+        since: Option<Duration>,
+        opened: Option<SystemTime>,
+    ) -> Option<String> {
+        match self {
+            TimestampFormat::Off => None,
+            TimestampFormat::Clock => Some(local(at)[11..23].to_string()),
+            TimestampFormat::Iso8601 => {
+                let offset = utc_offset(unix_seconds(at));
+                let zone = match offset {
+                    0 => String::from("Z"),
+                    _ => format!(
+                        "{}{:02}:{:02}",
+                        if offset < 0 { '-' } else { '+' },
+                        offset.abs() / 3600,
+                        offset.abs() / 60 % 60
+                    ),
+                };
+                Some(format!("{}{zone}", local(at)[..23].replacen(' ', "T", 1)))
+            }
+            TimestampFormat::SinceConnect => {
+                let since = since.or_else(|| at.duration_since(opened?).ok());
+                Some(match since {
+                    Some(since) => format!("{:.3} s", since.as_secs_f64()),
+                    // Logged before this connection
+                    None => String::from("-"),
+                })
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for TimestampFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            TimestampFormat::Off => "No timestamps",
+            TimestampFormat::Clock => "Wall clock",
+            TimestampFormat::Iso8601 => "ISO 8601",
+            TimestampFormat::SinceConnect => "Since connect",
+        };
+        write!(f, "{name}")
+    }
+}
+
+fn unix_seconds(at: SystemTime) -> i64 {
+    at.duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() as i64)
+}
+
+// Local date and time to the microsecond, e.g. 2024-05-01 13:45:07.123456
+fn local(at: SystemTime) -> String {
+    let micros = at
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_micros() as i64);
+    crate::capture::format_time(micros + utc_offset(unix_seconds(at)) * 1_000_000)
+}
+
+// Seconds the local time zone is ahead of UTC at the given time
+#[cfg(target_os = "linux")]
+pub fn utc_offset(unix: i64) -> i64 {
+    let time = unix as libc::time_t;
+    // SAFETY: an all-zero `tm` is valid, and both pointers are valid for the call
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    match unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        true => 0,
+        false => tm.tm_gmtoff,
+    }
+}
+
+// Elsewhere times are shown and taken as UTC
+#[cfg(not(target_os = "linux"))]
+pub fn utc_offset(_unix: i64) -> i64 {
+    0
+}
+
+pub fn clock_is_local() -> bool {
+    cfg!(target_os = "linux")
+}
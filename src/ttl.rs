@@ -0,0 +1,633 @@
+use crate::encoding::TextEncoding;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// Received text kept for `wait`; the oldest is dropped first
+const RECEIVE_LIMIT: usize = 64 * 1024;
+// Statements run per tick, so a loop without a pause can't freeze the window
+const STEPS_PER_TICK: usize = 1000;
+// Longest operators first so "<=" isn't read as "<" then "="
+const OPERATORS: [&str; 17] = [
+    "==", "<>", "!=", "<=", ">=", "&&", "||", "=", "<", ">", "+", "-", "*", "/", "%", "(", ")",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Int(i64),
+    Str(String),
+    Ident(String),
+    Op(&'static str),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Int(i64),
+    Str(String),
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Int(n) => write!(f, "{n}"),
+            Value::Str(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Expr {
+    Value(Value),
+    Var(String),
+    Neg(Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug)]
+enum Stmt {
+    Send { args: Vec<Expr>, newline: bool },
+    Wait(Vec<Expr>),
+    Pause { time: Expr, millis: bool },
+    Goto(String),
+    Call(String),
+    Return,
+    Assign(String, Expr),
+    If(Expr, Box<Stmt>),
+    // Block `if`/`while` compile down to jumps to statement indices
+    JumpUnless(Expr, usize),
+    Jump(usize),
+    Show(Vec<Expr>),
+    FlushRecv,
+    End,
+}
+
+struct Statement {
+    line: usize,
+    stmt: Stmt,
+}
+
+// Open `if ... then` and `while` blocks while parsing
+enum Block {
+    If {
+        pending: Option<usize>,
+        ends: Vec<usize>,
+    },
+    While {
+        start: usize,
+        exit: usize,
+    },
+}
+
+enum State {
+    Ready,
+    Pausing(Instant),
+    Waiting {
+        patterns: Vec<String>,
+        deadline: Option<Instant>,
+    },
+}
+
+// What the app has to do next for a running macro
+pub enum Step {
+    Send(Vec<u8>),
+    Blocked,
+    Finished,
+    Failed(String),
+}
+
+// A TeraTerm TTL Macro: send/sendln, wait, pause/mpause, goto/call/return, labels,
+// integer and string variables, if/elseif/else/endif, while/endwhile, and messages
+pub struct Script {
+    program: Arc<[Statement]>,
+    labels: HashMap<String, usize>,
+    pc: usize,
+    calls: Vec<usize>,
+    vars: HashMap<String, Value>,
+    received: String,
+    state: State,
+    // Messages from messagebox/dispstr/logwrite, shown in the log
+    pub output: Vec<String>,
+}
+
+impl Script {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut program = Vec::new();
+        let mut labels = HashMap::new();
+        let mut blocks = Vec::new();
+        for (i, text) in source.lines().enumerate() {
+            let line = i + 1;
+            let at = |e: String| format!("line {line}: {e}");
+            let text = text.trim();
+            if let Some(label) = text.strip_prefix(':') {
+                let label = label.split(';').next().unwrap_or_default().trim();
+                if labels.insert(label.to_lowercase(), program.len()).is_some() {
+                    return Err(at(format!("label '{label}' is defined twice")));
+                }
+                continue;
+            }
+            let tokens = tokenize(text).map_err(at)?;
+            if tokens.is_empty() {
+                continue;
+            }
+            parse_line(tokens, line, &mut program, &mut blocks).map_err(at)?;
+        }
+        if !blocks.is_empty() {
+            return Err(String::from("missing endif or endwhile at end of macro"));
+        }
+        for statement in &program {
+            if let Stmt::Goto(label) | Stmt::Call(label) = &statement.stmt
+                && !labels.contains_key(label)
+            {
+                return Err(format!("line {}: no label ':{label}'", statement.line));
+            }
+        }
+        let vars = [
+            ("result", Value::Int(0)),
+            ("timeout", Value::Int(0)),
+            ("mtimeout", Value::Int(0)),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+        Ok(Self {
+            program: program.into(),
+            labels,
+            pc: 0,
+            calls: Vec::new(),
+            vars,
+            received: String::new(),
+            state: State::Ready,
+            output: Vec::new(),
+        })
+    }
+    // Text received while the macro runs, matched by `wait`
+    pub fn receive(&mut self, text: &str) {
+        self.received.push_str(text);
+        if self.received.len() > RECEIVE_LIMIT {
+            let mut cut = self.received.len() - RECEIVE_LIMIT;
+            while !self.received.is_char_boundary(cut) {
+                cut += 1;
+            }
+            self.received.drain(..cut);
+        }
+    }
+    // Run until the macro has to send, wait, or pause
+    pub fn run(&mut self, now: Instant, encoding: TextEncoding) -> Step {
+        let program = Arc::clone(&self.program);
+        for _ in 0..STEPS_PER_TICK {
+            if self.blocked(now) {
+                return Step::Blocked;
+            }
+            let Some(statement) = program.get(self.pc) else {
+                return Step::Finished;
+            };
+            self.pc += 1;
+            match self.execute(&statement.stmt, now, encoding) {
+                Ok(Some(step)) => return step,
+                Ok(None) => {}
+                Err(e) => return Step::Failed(format!("line {}: {e}", statement.line)),
+            }
+        }
+        Step::Blocked
+    }
+    // Ends a pause or wait once it's over, setting `result` for waits
+    fn blocked(&mut self, now: Instant) -> bool {
+        match &self.state {
+            State::Ready => false,
+            State::Pausing(until) if now < *until => true,
+            State::Pausing(_) => {
+                self.state = State::Ready;
+                false
+            }
+            State::Waiting { patterns, deadline } => {
+                // The pattern that completes first in the received text wins
+                let found = patterns
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, p)| self.received.find(p.as_str()).map(|at| (at + p.len(), i)))
+                    .min();
+                let result = match found {
+                    Some((end, i)) => {
+                        self.received.drain(..end);
+                        i as i64 + 1
+                    }
+                    None if deadline.is_some_and(|deadline| now >= deadline) => 0,
+                    None => return true,
+                };
+                self.vars.insert(String::from("result"), Value::Int(result));
+                self.state = State::Ready;
+                false
+            }
+        }
+    }
+    fn execute(
+        &mut self,
+        stmt: &Stmt,
+        now: Instant,
+        encoding: TextEncoding,
+    ) -> Result<Option<Step>, String> {
+        match stmt {
+            Stmt::Send { args, newline } => {
+                let mut data = Vec::new();
+                for arg in args {
+                    match self.eval(arg)? {
+                        // Numbers are sent as a single byte, like TeraTerm
+                        Value::Int(n) => data.push(n as u8),
+                        Value::Str(s) => data.extend(encoding.encode(&s).ok_or_else(|| {
+                            format!("'{s}' has characters that can't be sent as {encoding}")
+                        })?),
+                    }
+                }
+                if *newline {
+                    data.push(b'\r');
+                }
+                if !data.is_empty() {
+                    return Ok(Some(Step::Send(data)));
+                }
+            }
+            Stmt::Wait(args) => {
+                let patterns = args
+                    .iter()
+                    .map(|arg| self.eval(arg).map(|value| value.to_string()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let millis = self.int_var("timeout")? * 1000 + self.int_var("mtimeout")?;
+                let deadline = (millis > 0).then(|| now + Duration::from_millis(millis as u64));
+                self.state = State::Waiting { patterns, deadline };
+            }
+            Stmt::Pause { time, millis } => {
+                let time = self.int(time)?.max(0) as u64;
+                let time = match millis {
+                    true => Duration::from_millis(time),
+                    false => Duration::from_secs(time),
+                };
+                self.state = State::Pausing(now + time);
+            }
+            Stmt::Goto(label) => self.pc = self.labels[label],
+            Stmt::Call(label) => {
+                self.calls.push(self.pc);
+                self.pc = self.labels[label];
+            }
+            Stmt::Return => {
+                self.pc = self
+                    .calls
+                    .pop()
+                    .ok_or_else(|| String::from("return without call"))?;
+            }
+            Stmt::Assign(name, expr) => {
+                let value = self.eval(expr)?;
+                self.vars.insert(name.clone(), value);
+            }
+            Stmt::If(condition, stmt) => {
+                if self.int(condition)? != 0 {
+                    return self.execute(stmt, now, encoding);
+                }
+            }
+            Stmt::JumpUnless(condition, target) => {
+                if self.int(condition)? == 0 {
+                    self.pc = *target;
+                }
+            }
+            Stmt::Jump(target) => self.pc = *target,
+            Stmt::Show(args) => {
+                let text = args
+                    .iter()
+                    .map(|arg| self.eval(arg).map(|value| value.to_string()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.output.push(text.join(" "));
+            }
+            Stmt::FlushRecv => self.received.clear(),
+            Stmt::End => return Ok(Some(Step::Finished)),
+        }
+        Ok(None)
+    }
+    fn int_var(&self, name: &str) -> Result<i64, String> {
+        self.int(&Expr::Var(name.to_string()))
+    }
+    fn int(&self, expr: &Expr) -> Result<i64, String> {
+        match self.eval(expr)? {
+            Value::Int(n) => Ok(n),
+            Value::Str(s) => Err(format!("expected a number, got '{s}'")),
+        }
+    }
+    fn eval(&self, expr: &Expr) -> Result<Value, String> {
+        match expr {
+            Expr::Value(value) => Ok(value.clone()),
+            Expr::Var(name) => self
+                .vars
+                .get(name)
+                .cloned()
+                .ok_or_else(|| format!("variable '{name}' is not set")),
+            Expr::Neg(expr) => Ok(Value::Int(self.int(expr)?.wrapping_neg())),
+            Expr::Binary(op, left, right) => {
+                let (left, right) = (self.eval(left)?, self.eval(right)?);
+                let truth = |b: bool| -> Result<Value, String> { Ok(Value::Int(b as i64)) };
+                match (op, left, right) {
+                    (&"+", Value::Str(a), b) => Ok(Value::Str(format!("{a}{b}"))),
+                    (&"=" | &"==", a, b) => truth(a == b),
+                    (&"<>" | &"!=", a, b) => truth(a != b),
+                    (op, Value::Int(a), Value::Int(b)) => match *op {
+                        "+" => Ok(Value::Int(a.wrapping_add(b))),
+                        "-" => Ok(Value::Int(a.wrapping_sub(b))),
+                        "*" => Ok(Value::Int(a.wrapping_mul(b))),
+                        "/" | "%" if b == 0 => Err(String::from("division by zero")),
+                        // Only the most negative number divided by -1 is left to overflow
+                        "/" => a
+                            .checked_div(b)
+                            .map(Value::Int)
+                            .ok_or_else(|| format!("{a} / {b} overflows")),
+                        "%" => a
+                            .checked_rem(b)
+                            .map(Value::Int)
+                            .ok_or_else(|| format!("{a} % {b} overflows")),
+                        "<" => truth(a < b),
+                        ">" => truth(a > b),
+                        "<=" => truth(a <= b),
+                        ">=" => truth(a >= b),
+                        "&&" => truth(a != 0 && b != 0),
+                        _ => truth(a != 0 || b != 0),
+                    },
+                    (op, _, _) => Err(format!("'{op}' needs numbers")),
+                }
+            }
+        }
+    }
+}
+
+// Quoted strings and #char codes written back to back ('ok'#13#10) form one string
+fn tokenize(line: &str) -> Result<Vec<Token>, String> {
+    let chars = line.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while let Some(&c) = chars.get(i) {
+        if c == ';' {
+            break;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if matches!(c, '\'' | '"' | '#') {
+            let mut s = String::new();
+            while let Some(&c) = chars.get(i).filter(|c| matches!(**c, '\'' | '"' | '#')) {
+                if c == '#' {
+                    let (code, len) = number(&chars[i + 1..])
+                        .ok_or_else(|| String::from("'#' must be followed by a character code"))?;
+                    s.push(
+                        u32::try_from(code)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| format!("#{code} is not a character"))?,
+                    );
+                    i += 1 + len;
+                } else {
+                    let len = chars[i + 1..]
+                        .iter()
+                        .position(|&end| end == c)
+                        .ok_or_else(|| String::from("string is missing its closing quote"))?;
+                    s.extend(&chars[i + 1..i + 1 + len]);
+                    i += len + 2;
+                }
+            }
+            tokens.push(Token::Str(s));
+        } else if c.is_ascii_digit() || c == '$' {
+            let (n, len) = number(&chars[i..]).ok_or_else(|| format!("bad number at '{c}'"))?;
+            tokens.push(Token::Int(n));
+            i += len;
+        } else if c.is_alphabetic() || c == '_' {
+            let len = chars[i..]
+                .iter()
+                .take_while(|c| c.is_alphanumeric() || **c == '_')
+                .count();
+            let word = chars[i..i + len].iter().collect::<String>();
+            tokens.push(Token::Ident(word.to_lowercase()));
+            i += len;
+        } else {
+            let rest = chars[i..].iter().take(2).collect::<String>();
+            let op = OPERATORS
+                .into_iter()
+                .find(|op| rest.starts_with(op))
+                .ok_or_else(|| format!("unexpected '{c}'"))?;
+            tokens.push(Token::Op(op));
+            i += op.len();
+        }
+    }
+    Ok(tokens)
+}
+
+// A decimal number, or hex after `$`, with the characters it used
+fn number(chars: &[char]) -> Option<(i64, usize)> {
+    let (radix, skip) = match chars.first() {
+        Some('$') => (16, 1),
+        _ => (10, 0),
+    };
+    let digits = chars[skip..]
+        .iter()
+        .take_while(|c| c.is_digit(radix))
+        .collect::<String>();
+    let n = i64::from_str_radix(&digits, radix).ok()?;
+    Some((n, skip + digits.len()))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+    fn done(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+    // Binary operator at the cursor; TTL also spells them `and` and `or`
+    fn operator(&self) -> Option<&'static str> {
+        match self.tokens.get(self.pos)? {
+            Token::Op(op) => Some(*op),
+            Token::Ident(word) if word == "and" => Some("&&"),
+            Token::Ident(word) if word == "or" => Some("||"),
+            _ => None,
+        }
+    }
+    fn expr(&mut self) -> Result<Expr, String> {
+        self.binary(0)
+    }
+    fn binary(&mut self, min: u8) -> Result<Expr, String> {
+        let mut left = self.unary()?;
+        while let Some(op) = self.operator() {
+            let precedence = match op {
+                "||" => 0,
+                "&&" => 1,
+                "=" | "==" | "<>" | "!=" | "<" | ">" | "<=" | ">=" => 2,
+                "+" | "-" => 3,
+                "*" | "/" | "%" => 4,
+                _ => break,
+            };
+            if precedence < min {
+                break;
+            }
+            self.pos += 1;
+            let right = self.binary(precedence + 1)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Int(n)) => Ok(Expr::Value(Value::Int(n))),
+            Some(Token::Str(s)) => Ok(Expr::Value(Value::Str(s))),
+            Some(Token::Ident(name)) => Ok(Expr::Var(name)),
+            Some(Token::Op("-")) => Ok(Expr::Neg(Box::new(self.unary()?))),
+            Some(Token::Op("(")) => {
+                let expr = self.expr()?;
+                match self.next() {
+                    Some(Token::Op(")")) => Ok(expr),
+                    _ => Err(String::from("missing ')'")),
+                }
+            }
+            Some(token) => Err(format!("unexpected {token:?}")),
+            None => Err(String::from("expected a value")),
+        }
+    }
+    // Space separated arguments, each a full expression
+    fn args(&mut self) -> Result<Vec<Expr>, String> {
+        let mut args = Vec::new();
+        while !self.done() {
+            args.push(self.expr()?);
+        }
+        Ok(args)
+    }
+    fn label(&mut self) -> Result<String, String> {
+        match self.next() {
+            Some(Token::Ident(label)) => Ok(label),
+            _ => Err(String::from("expected a label")),
+        }
+    }
+    // `then` ends a block `if`; anything else after the condition is a one-line `if`
+    fn then(&mut self) -> bool {
+        let then = self.tokens.get(self.pos) == Some(&Token::Ident(String::from("then")));
+        self.pos += then as usize;
+        then
+    }
+}
+
+fn parse_line(
+    tokens: Vec<Token>,
+    line: usize,
+    program: &mut Vec<Statement>,
+    blocks: &mut Vec<Block>,
+) -> Result<(), String> {
+    let mut parser = Parser { tokens, pos: 0 };
+    let Some(Token::Ident(command)) = parser.next() else {
+        return Err(String::from("expected a command"));
+    };
+    let push = |program: &mut Vec<Statement>, stmt| {
+        program.push(Statement { line, stmt });
+        program.len() - 1
+    };
+    match command.as_str() {
+        "if" => {
+            let condition = parser.expr()?;
+            if parser.then() {
+                let at = push(program, Stmt::JumpUnless(condition, 0));
+                blocks.push(Block::If {
+                    pending: Some(at),
+                    ends: Vec::new(),
+                });
+            } else {
+                let stmt = statement(String::new(), &mut parser)?;
+                push(program, Stmt::If(condition, Box::new(stmt)));
+            }
+        }
+        "elseif" | "else" => {
+            let Some(Block::If { pending, ends }) = blocks.last_mut() else {
+                return Err(format!("{command} without if"));
+            };
+            let Some(skip) = pending.take() else {
+                return Err(format!("{command} after else"));
+            };
+            ends.push(push(program, Stmt::Jump(0)));
+            patch(program, skip);
+            if command == "elseif" {
+                let condition = parser.expr()?;
+                if !parser.then() {
+                    return Err(String::from("elseif needs then"));
+                }
+                *pending = Some(push(program, Stmt::JumpUnless(condition, 0)));
+            }
+        }
+        "endif" => {
+            let Some(Block::If { pending, ends }) = blocks.pop() else {
+                return Err(String::from("endif without if"));
+            };
+            for at in pending.into_iter().chain(ends) {
+                patch(program, at);
+            }
+        }
+        "while" => {
+            let condition = parser.expr()?;
+            let start = program.len();
+            let exit = push(program, Stmt::JumpUnless(condition, 0));
+            blocks.push(Block::While { start, exit });
+        }
+        "endwhile" => {
+            let Some(Block::While { start, exit }) = blocks.pop() else {
+                return Err(String::from("endwhile without while"));
+            };
+            push(program, Stmt::Jump(start));
+            patch(program, exit);
+        }
+        _ => {
+            let stmt = statement(command, &mut parser)?;
+            push(program, stmt);
+        }
+    }
+    if !parser.done() {
+        return Err(String::from("unexpected text at end of line"));
+    }
+    Ok(())
+}
+
+// Point an already parsed jump at the next statement to be parsed
+fn patch(program: &mut [Statement], at: usize) {
+    let next = program.len();
+    if let Stmt::JumpUnless(_, target) | Stmt::Jump(target) = &mut program[at].stmt {
+        *target = next;
+    }
+}
+
+// A single statement; `command` is empty when it hasn't been read yet (one-line `if`)
+fn statement(command: String, parser: &mut Parser) -> Result<Stmt, String> {
+    let command = match command.is_empty() {
+        true => match parser.next() {
+            Some(Token::Ident(command)) => command,
+            _ => return Err(String::from("expected a command")),
+        },
+        false => command,
+    };
+    if parser.tokens.get(parser.pos) == Some(&Token::Op("=")) {
+        parser.pos += 1;
+        return Ok(Stmt::Assign(command, parser.expr()?));
+    }
+    let stmt = match command.as_str() {
+        "send" | "sendln" => Stmt::Send {
+            args: parser.args()?,
+            newline: command == "sendln",
+        },
+        "wait" => match parser.args()? {
+            args if args.is_empty() => return Err(String::from("wait needs a string")),
+            args => Stmt::Wait(args),
+        },
+        "pause" | "mpause" => Stmt::Pause {
+            time: parser.expr()?,
+            millis: command == "mpause",
+        },
+        "goto" => Stmt::Goto(parser.label()?),
+        "call" => Stmt::Call(parser.label()?),
+        "return" => Stmt::Return,
+        "end" | "exit" => Stmt::End,
+        "flushrecv" => Stmt::FlushRecv,
+        "messagebox" | "dispstr" | "logwrite" | "statusbox" => Stmt::Show(parser.args()?),
+        _ => return Err(format!("unsupported command '{command}'")),
+    };
+    Ok(stmt)
+}
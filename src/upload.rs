@@ -0,0 +1,114 @@
+use std::time::{Duration, Instant};
+
+// ACK-Gated Uploads
+//
+// A payload sent a chunk at a time, each chunk waiting for the device to answer with an ACK
+// pattern (or print its prompt) before the next one goes. A chunk with no answer in time is
+// sent again, up to a number of retries, which is how line-by-line bootloaders expect it.
+pub struct Upload {
+    chunks: Vec<Vec<u8>>,
+    ack: Vec<u8>,
+    timeout: Duration,
+    retries: u32,
+    next: usize,
+    tries: u32,
+    retried: u32,
+    // The chunk `next` is out and the answer is awaited; the deadline is set once the write
+    // has finished, so a slow write doesn't eat into the timeout
+    awaiting: bool,
+    deadline: Option<Instant>,
+    received: Vec<u8>,
+}
+
+pub enum UploadStep {
+    Send(Vec<u8>),
+    Wait,
+    Finished,
+    Failed(String),
+}
+
+impl Upload {
+    // Split after every LF when `chunk` is None, otherwise into pieces of `chunk` bytes
+    pub fn new(
+        data: &[u8],
+        chunk: Option<usize>,
+        ack: Vec<u8>,
+        timeout: Duration,
+        retries: u32,
+    ) -> Self {
+        let chunks = match chunk {
+            Some(size) => data.chunks(size.max(1)).map(<[u8]>::to_vec).collect(),
+            None => data
+                .split_inclusive(|&byte| byte == b'\n')
+                .map(<[u8]>::to_vec)
+                .collect(),
+        };
+        Self {
+            chunks,
+            ack,
+            timeout,
+            retries,
+            next: 0,
+            tries: 0,
+            retried: 0,
+            awaiting: false,
+            deadline: None,
+            received: Vec::new(),
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+    // Chunks answered so far
+    pub fn done(&self) -> usize {
+        self.next
+    }
+    pub fn retried(&self) -> u32 {
+        self.retried
+    }
+    pub fn receive(&mut self, data: &[u8]) {
+        if self.awaiting {
+            self.received.extend_from_slice(data);
+        }
+    }
+    // Called while nothing is being written
+    pub fn step(&mut self, now: Instant) -> UploadStep {
+        if self.awaiting {
+            if self.acked() {
+                self.next += 1;
+                self.tries = 0;
+                self.awaiting = false;
+            } else {
+                let deadline = *self.deadline.get_or_insert(now + self.timeout);
+                if now < deadline {
+                    return UploadStep::Wait;
+                }
+                if self.tries >= self.retries {
+                    return UploadStep::Failed(format!(
+                        "no ACK for chunk {} of {} after {} tries",
+                        self.next + 1,
+                        self.chunks.len(),
+                        self.tries + 1
+                    ));
+                }
+                self.tries += 1;
+                self.retried += 1;
+                self.awaiting = false;
+            }
+        }
+        let Some(chunk) = self.chunks.get(self.next) else {
+            return UploadStep::Finished;
+        };
+        self.awaiting = true;
+        self.deadline = None;
+        self.received.clear();
+        UploadStep::Send(chunk.clone())
+    }
+    fn acked(&self) -> bool {
+        self.ack.is_empty()
+            || self
+                .received
+                .windows(self.ack.len())
+                .any(|window| window == self.ack)
+    }
+}
@@ -0,0 +1,159 @@
+// Numeric Interpretation of Received Frames
+//
+// A layout like `id:u8 x1 temp:i16be volts:f32` is read front to back over each frame: a type
+// per field, optionally named, with multi-byte fields little endian unless marked `be`, and
+// `xN` skipping N bytes of padding.
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Kind {
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    F32,
+    F64,
+}
+
+impl Kind {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "u8" => Kind::U8,
+            "i8" => Kind::I8,
+            "u16" => Kind::U16,
+            "i16" => Kind::I16,
+            "u32" => Kind::U32,
+            "i32" => Kind::I32,
+            "u64" => Kind::U64,
+            "i64" => Kind::I64,
+            "f32" => Kind::F32,
+            "f64" => Kind::F64,
+            _ => return None,
+        })
+    }
+    fn size(self) -> usize {
+        match self {
+            Kind::U8 | Kind::I8 => 1,
+            Kind::U16 | Kind::I16 => 2,
+            Kind::U32 | Kind::I32 | Kind::F32 => 4,
+            Kind::U64 | Kind::I64 | Kind::F64 => 8,
+        }
+    }
+    // `bytes` is exactly `size()` long
+    fn read(self, bytes: &[u8], big_endian: bool) -> String {
+        macro_rules! read {
+            ($t:ty) => {{
+                let bytes = bytes.try_into().unwrap();
+                match big_endian {
+                    true => <$t>::from_be_bytes(bytes).to_string(),
+                    false => <$t>::from_le_bytes(bytes).to_string(),
+                }
+            }};
+        }
+        match self {
+            Kind::U8 => read!(u8),
+            Kind::I8 => read!(i8),
+            Kind::U16 => read!(u16),
+            Kind::I16 => read!(i16),
+            Kind::U32 => read!(u32),
+            Kind::I32 => read!(i32),
+            Kind::U64 => read!(u64),
+            Kind::I64 => read!(i64),
+            Kind::F32 => read!(f32),
+            Kind::F64 => read!(f64),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Field {
+    Value {
+        name: Option<String>,
+        kind: Kind,
+        big_endian: bool,
+    },
+    Skip(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    fields: Vec<Field>,
+}
+
+impl Layout {
+    // Fields are separated by spaces or commas
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut fields = Vec::new();
+        for token in spec
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|token| !token.is_empty())
+        {
+            let lower = token.to_ascii_lowercase();
+            if let Some(count) = lower.strip_prefix('x') {
+                let count = match count {
+                    "" => 1,
+                    _ => count
+                        .parse()
+                        .map_err(|_| format!("'{token}' isn't a byte count to skip"))?,
+                };
+                fields.push(Field::Skip(count));
+                continue;
+            }
+            let (name, kind) = match lower.rsplit_once(':') {
+                Some((_, kind)) => (token.rsplit_once(':').map(|(name, _)| name), kind),
+                None => (None, lower.as_str()),
+            };
+            let (kind, big_endian) = match kind.strip_suffix("be") {
+                Some(kind) => (kind, true),
+                None => (kind.strip_suffix("le").unwrap_or(kind), false),
+            };
+            let kind = Kind::parse(kind).ok_or_else(|| {
+                format!("Unknown type in '{token}', expected u8-u64, i8-i64, f32 or f64")
+            })?;
+            fields.push(Field::Value {
+                name: name.filter(|name| !name.is_empty()).map(str::to_string),
+                kind,
+                big_endian,
+            });
+        }
+        match fields.is_empty() {
+            true => Err(String::from("No fields given")),
+            false => Ok(Self { fields }),
+        }
+    }
+    // Values of one frame, noting a frame that's too short or has bytes left over
+    pub fn decode(&self, data: &[u8]) -> String {
+        let mut values = Vec::new();
+        let mut offset = 0;
+        for field in &self.fields {
+            let size = match field {
+                Field::Value { kind, .. } => kind.size(),
+                Field::Skip(count) => *count,
+            };
+            let Some(bytes) = data.get(offset..offset + size) else {
+                values.push(format!("{} bytes short", offset + size - data.len()));
+                return values.join(", ");
+            };
+            offset += size;
+            if let Field::Value {
+                name,
+                kind,
+                big_endian,
+            } = field
+            {
+                let value = kind.read(bytes, *big_endian);
+                values.push(match name {
+                    Some(name) => format!("{name}={value}"),
+                    None => value,
+                });
+            }
+        }
+        if offset < data.len() {
+            values.push(format!("+{} bytes", data.len() - offset));
+        }
+        values.join(", ")
+    }
+}
@@ -0,0 +1,351 @@
+use crate::parity::LineParity;
+use serialport::{DataBits, FlowControl, StopBits};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// Everything Needed to Bring One Window Back
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub name: String,
+    pub port: Option<String>,
+    pub baud_rate: u32,
+    pub data_bits: DataBits,
+    pub parity: LineParity,
+    pub stop_bits: StopBits,
+    pub flow_control: FlowControl,
+    pub theme: String,
+    pub overflow_policy: String,
+    pub framing: String,
+    pub frame_delimiter: String,
+    pub frame_gap_ms: String,
+    pub frame_length: String,
+    pub rx_utf8: bool,
+    pub rx_hex: bool,
+    pub rx_binary: bool,
+    pub rx_dump: bool,
+    pub rx_base64: bool,
+    pub value_layout: String,
+    pub encoding: String,
+    pub ansi: String,
+    pub show_deltas: bool,
+    pub timestamps: String,
+    pub tx_hex: bool,
+    pub tx_base64: bool,
+    pub tx_decimal: bool,
+    pub tx_ending: String,
+    pub tx_escapes: bool,
+    pub flush_after_send: bool,
+    pub verify_echo: bool,
+    pub cts_gate: bool,
+    pub show_timeline: bool,
+    pub split_log: bool,
+    pub export_stats_on_close: bool,
+    pub log_budget_mb: String,
+    pub log_max_lines: String,
+    pub log_keep_on_disk: bool,
+    pub port_open: bool,
+    pub listening: bool,
+    pub width: f32,
+    pub height: f32,
+    pub position: Option<(f32, f32)>,
+    // Opened as a tab in the previous session's window
+    pub tab: bool,
+    // The tab shown in its window, and the one beside it in split view
+    pub active: bool,
+    pub split: bool,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            port: None,
+            baud_rate: 9600,
+            data_bits: DataBits::Eight,
+            parity: LineParity::None,
+            stop_bits: StopBits::One,
+            flow_control: FlowControl::None,
+            theme: String::new(),
+            overflow_policy: String::new(),
+            framing: String::new(),
+            frame_delimiter: String::new(),
+            frame_gap_ms: String::new(),
+            frame_length: String::new(),
+            rx_utf8: false,
+            rx_hex: true,
+            rx_binary: false,
+            rx_dump: false,
+            rx_base64: false,
+            value_layout: String::new(),
+            encoding: String::new(),
+            ansi: String::new(),
+            show_deltas: false,
+            timestamps: String::new(),
+            tx_hex: false,
+            tx_base64: false,
+            tx_decimal: false,
+            tx_ending: String::new(),
+            tx_escapes: true,
+            flush_after_send: true,
+            verify_echo: false,
+            cts_gate: false,
+            show_timeline: false,
+            split_log: false,
+            export_stats_on_close: false,
+            log_budget_mb: String::new(),
+            log_max_lines: String::new(),
+            log_keep_on_disk: true,
+            port_open: false,
+            listening: false,
+            width: 500.0,
+            height: 500.0,
+            position: None,
+            tab: false,
+            active: false,
+            split: false,
+        }
+    }
+}
+
+impl Session {
+    fn to_text(&self) -> String {
+        let mut text = String::from("[session]\n");
+        let mut field = |key: &str, value: String| text.push_str(&format!("{key}={value}\n"));
+        field("name", self.name.clone());
+        if let Some(port) = &self.port {
+            field("port", port.clone());
+        }
+        field("baud_rate", self.baud_rate.to_string());
+        field("data_bits", self.data_bits.to_string());
+        field("parity", self.parity.to_string());
+        field("stop_bits", self.stop_bits.to_string());
+        field("flow_control", self.flow_control.to_string());
+        field("theme", self.theme.clone());
+        field("overflow_policy", self.overflow_policy.clone());
+        field("framing", self.framing.clone());
+        field("frame_delimiter", self.frame_delimiter.clone());
+        field("frame_gap_ms", self.frame_gap_ms.clone());
+        field("frame_length", self.frame_length.clone());
+        field("rx_utf8", self.rx_utf8.to_string());
+        field("rx_hex", self.rx_hex.to_string());
+        field("rx_binary", self.rx_binary.to_string());
+        field("rx_dump", self.rx_dump.to_string());
+        field("rx_base64", self.rx_base64.to_string());
+        field("value_layout", self.value_layout.clone());
+        field("encoding", self.encoding.clone());
+        field("ansi", self.ansi.clone());
+        field("show_deltas", self.show_deltas.to_string());
+        field("timestamps", self.timestamps.clone());
+        field("tx_hex", self.tx_hex.to_string());
+        field("tx_base64", self.tx_base64.to_string());
+        field("tx_decimal", self.tx_decimal.to_string());
+        field("tx_ending", self.tx_ending.clone());
+        field("tx_escapes", self.tx_escapes.to_string());
+        field("flush_after_send", self.flush_after_send.to_string());
+        field("verify_echo", self.verify_echo.to_string());
+        field("cts_gate", self.cts_gate.to_string());
+        field("show_timeline", self.show_timeline.to_string());
+        field("split_log", self.split_log.to_string());
+        field(
+            "export_stats_on_close",
+            self.export_stats_on_close.to_string(),
+        );
+        field("log_budget_mb", self.log_budget_mb.clone());
+        field("log_max_lines", self.log_max_lines.clone());
+        field("log_keep_on_disk", self.log_keep_on_disk.to_string());
+        field("port_open", self.port_open.to_string());
+        field("listening", self.listening.to_string());
+        field("size", format!("{}x{}", self.width, self.height));
+        if let Some((x, y)) = self.position {
+            field("position", format!("{x},{y}"));
+        }
+        field("tab", self.tab.to_string());
+        field("active", self.active.to_string());
+        field("split", self.split.to_string());
+        text
+    }
+    // Unknown keys and unparsable values keep their defaults
+    fn set(&mut self, key: &str, value: &str) {
+        let flag = |current: bool| value.parse().unwrap_or(current);
+        match key {
+            "name" => self.name = value.to_string(),
+            "port" => self.port = Some(value.to_string()).filter(|port| !port.is_empty()),
+            "baud_rate" => self.baud_rate = value.parse().unwrap_or(self.baud_rate),
+            "data_bits" => {
+                self.data_bits = [
+                    DataBits::Five,
+                    DataBits::Six,
+                    DataBits::Seven,
+                    DataBits::Eight,
+                ]
+                .into_iter()
+                .find(|bits| bits.to_string() == value)
+                .unwrap_or(self.data_bits)
+            }
+            "parity" => {
+                self.parity = LineParity::ALL
+                    .into_iter()
+                    .find(|parity| parity.to_string() == value)
+                    .unwrap_or(self.parity)
+            }
+            "stop_bits" => {
+                self.stop_bits = [StopBits::One, StopBits::Two]
+                    .into_iter()
+                    .find(|bits| bits.to_string() == value)
+                    .unwrap_or(self.stop_bits)
+            }
+            "flow_control" => {
+                self.flow_control = [
+                    FlowControl::None,
+                    FlowControl::Software,
+                    FlowControl::Hardware,
+                ]
+                .into_iter()
+                .find(|flow| flow.to_string() == value)
+                .unwrap_or(self.flow_control)
+            }
+            "theme" => self.theme = value.to_string(),
+            "overflow_policy" => self.overflow_policy = value.to_string(),
+            "framing" => self.framing = value.to_string(),
+            "frame_delimiter" => self.frame_delimiter = value.to_string(),
+            "frame_gap_ms" => self.frame_gap_ms = value.to_string(),
+            "frame_length" => self.frame_length = value.to_string(),
+            "rx_utf8" => self.rx_utf8 = flag(self.rx_utf8),
+            "rx_hex" => self.rx_hex = flag(self.rx_hex),
+            "rx_binary" => self.rx_binary = flag(self.rx_binary),
+            "rx_dump" => self.rx_dump = flag(self.rx_dump),
+            "rx_base64" => self.rx_base64 = flag(self.rx_base64),
+            "value_layout" => self.value_layout = value.to_string(),
+            "encoding" => self.encoding = value.to_string(),
+            "ansi" => self.ansi = value.to_string(),
+            "show_deltas" => self.show_deltas = flag(self.show_deltas),
+            "timestamps" => self.timestamps = value.to_string(),
+            "tx_hex" => self.tx_hex = flag(self.tx_hex),
+            "tx_base64" => self.tx_base64 = flag(self.tx_base64),
+            "tx_decimal" => self.tx_decimal = flag(self.tx_decimal),
+            "tx_ending" => self.tx_ending = value.to_string(),
+            "tx_escapes" => self.tx_escapes = flag(self.tx_escapes),
+            "flush_after_send" => self.flush_after_send = flag(self.flush_after_send),
+            "verify_echo" => self.verify_echo = flag(self.verify_echo),
+            "cts_gate" => self.cts_gate = flag(self.cts_gate),
+            "show_timeline" => self.show_timeline = flag(self.show_timeline),
+            "split_log" => self.split_log = flag(self.split_log),
+            "export_stats_on_close" => {
+                self.export_stats_on_close = flag(self.export_stats_on_close)
+            }
+            "log_budget_mb" => self.log_budget_mb = value.to_string(),
+            "log_max_lines" => self.log_max_lines = value.to_string(),
+            "log_keep_on_disk" => self.log_keep_on_disk = flag(self.log_keep_on_disk),
+            "port_open" => self.port_open = flag(self.port_open),
+            "listening" => self.listening = flag(self.listening),
+            "tab" => self.tab = flag(self.tab),
+            "active" => self.active = flag(self.active),
+            "split" => self.split = flag(self.split),
+            "size" => {
+                if let Some((width, height)) = value.split_once('x')
+                    && let (Ok(width), Ok(height)) = (width.parse(), height.parse())
+                {
+                    self.width = width;
+                    self.height = height;
+                }
+            }
+            "position" => {
+                if let Some((x, y)) = value.split_once(',')
+                    && let (Ok(x), Ok(y)) = (x.parse(), y.parse())
+                {
+                    self.position = Some((x, y));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// Per-user settings directory: $XDG_CONFIG_HOME or ~/.config on Unix, %APPDATA% on Windows
+pub fn config_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+    base.map(|base| base.join("serial-app"))
+}
+
+fn dir() -> io::Result<PathBuf> {
+    let dir = config_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+    Ok(dir.join("workspaces"))
+}
+
+fn path(name: &str) -> io::Result<PathBuf> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ' ' | '.'))
+        && !name.starts_with('.');
+    if !valid {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "workspace names may only use letters, digits, spaces, '-', '_' and '.'",
+        ));
+    }
+    Ok(dir()?.join(format!("{name}.workspace")))
+}
+
+// Names of the saved workspaces, sorted
+pub fn list() -> Vec<String> {
+    let Ok(entries) = dir().and_then(fs::read_dir) else {
+        return Vec::new();
+    };
+    let mut names = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let path = entry.path();
+            match path.extension()?.to_str()? {
+                "workspace" => Some(path.file_stem()?.to_str()?.to_string()),
+                _ => None,
+            }
+        })
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+// Save every session under `name`, replacing any workspace already saved with that name
+pub fn save(name: &str, sessions: &[Session]) -> io::Result<PathBuf> {
+    let path = path(name)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let text = sessions
+        .iter()
+        .map(Session::to_text)
+        .collect::<Vec<_>>()
+        .join("\n");
+    fs::write(&path, text)?;
+    Ok(path)
+}
+
+pub fn load(name: &str) -> io::Result<Vec<Session>> {
+    let text = fs::read_to_string(path(name)?)?;
+    let mut sessions = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line == "[session]" {
+            sessions.push(Session::default());
+        } else if let Some((key, value)) = line.split_once('=')
+            && let Some(session) = sessions.last_mut()
+        {
+            session.set(key.trim(), value.trim());
+        }
+    }
+    if sessions.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "workspace has no sessions",
+        ));
+    }
+    Ok(sessions)
+}
@@ -0,0 +1,256 @@
+use bytes::Bytes;
+use iced::futures::channel::mpsc;
+use iced::futures::{SinkExt, Stream, StreamExt};
+use serialport::SerialPort;
+use std::io::ErrorKind;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+// Bytes written between progress reports
+pub const CHUNK_SIZE: usize = 4096;
+// Retry delays grow from the first to the last while the port refuses data
+const FIRST_BACKOFF: Duration = Duration::from_millis(1);
+const MAX_BACKOFF: Duration = Duration::from_millis(100);
+// How long the port may accept nothing before the write is abandoned
+const STALL_LIMIT: Duration = Duration::from_secs(2);
+// How often a paused write checks whether it may go on
+const PAUSE_POLL: Duration = Duration::from_millis(10);
+// Largest write while gated on CTS, so little is already on its way when CTS drops
+const CTS_CHUNK: usize = 64;
+
+// Progress Reported by a Background Write
+#[derive(Debug, Clone)]
+pub enum WriteEvent {
+    Progress {
+        sent: usize,
+        total: usize,
+    },
+    // Everything is written and the OS output buffer is being drained
+    Draining,
+    // `cts_wait` is the total time spent holding for CTS
+    Done {
+        sent: usize,
+        retries: usize,
+        cts_wait: Duration,
+    },
+    Failed {
+        sent: usize,
+        error: WriteError,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum WriteError {
+    // The port kept timing out without taking any bytes
+    Stalled,
+    Cancelled,
+    Io(String),
+}
+
+impl std::fmt::Display for WriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WriteError::Stalled => write!(f, "device not accepting data (flow control?)"),
+            WriteError::Cancelled => write!(f, "cancelled"),
+            WriteError::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+// How a long write is split up, e.g. for a bootloader that needs time between blocks or
+// a slow MCU that drops bytes sent back to back
+#[derive(Debug, Clone, Copy)]
+pub struct Pacing {
+    pub chunk: usize,
+    pub delay: Duration,
+    // After every byte, and after every LF in place of that
+    pub char_delay: Duration,
+    pub line_delay: Duration,
+    // Hold each write until the device asserts CTS, without hardware flow control
+    pub cts_gate: bool,
+}
+
+impl Default for Pacing {
+    fn default() -> Self {
+        Self {
+            chunk: CHUNK_SIZE,
+            delay: Duration::ZERO,
+            char_delay: Duration::ZERO,
+            line_delay: Duration::ZERO,
+            cts_gate: false,
+        }
+    }
+}
+
+// Pause and cancel requests from the UI, checked by the writer thread between writes
+#[derive(Debug, Default)]
+pub struct Control {
+    paused: AtomicBool,
+    cancelled: AtomicBool,
+}
+
+impl Control {
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+// Write `data` to a clone of the port on its own thread, reporting progress as it goes. With
+// `flush`, completion waits until the OS has actually transmitted the bytes.
+pub fn write(
+    mut port: Box<dyn SerialPort>,
+    data: Bytes,
+    flush: bool,
+    pacing: Pacing,
+    control: Arc<Control>,
+) -> impl Stream<Item = WriteEvent> {
+    iced::stream::channel(16, move |mut output| async move {
+        let (events, mut received) = mpsc::unbounded();
+        std::thread::spawn(move || {
+            let written = write_with_retries(&mut *port, &data, pacing, &control, |sent, total| {
+                let _ = events.unbounded_send(WriteEvent::Progress { sent, total });
+            });
+            let event = match written {
+                Ok((retries, cts_wait)) if flush => {
+                    let _ = events.unbounded_send(WriteEvent::Draining);
+                    match port.flush() {
+                        Ok(()) => WriteEvent::Done {
+                            sent: data.len(),
+                            retries,
+                            cts_wait,
+                        },
+                        Err(e) => WriteEvent::Failed {
+                            sent: data.len(),
+                            error: WriteError::Io(format!("flush failed: {e}")),
+                        },
+                    }
+                }
+                Ok((retries, cts_wait)) => WriteEvent::Done {
+                    sent: data.len(),
+                    retries,
+                    cts_wait,
+                },
+                Err((sent, error)) => WriteEvent::Failed { sent, error },
+            };
+            let _ = events.unbounded_send(event);
+        });
+        while let Some(event) = received.next().await {
+            let _ = output.send(event).await;
+        }
+    })
+}
+
+// Write everything on the calling thread with the same retries, e.g. from the bridge
+pub fn write_all(port: &mut dyn SerialPort, data: &[u8]) -> Result<(), WriteError> {
+    write_with_retries(
+        port,
+        data,
+        Pacing::default(),
+        &Control::default(),
+        |_, _| {},
+    )
+    .map(|_| ())
+    .map_err(|(_, error)| error)
+}
+
+// Write everything, retrying timeouts and partial writes with backoff. Returns the number of
+// retries needed and the time spent waiting for CTS, or how far the write got before failing.
+fn write_with_retries(
+    port: &mut dyn SerialPort,
+    data: &[u8],
+    pacing: Pacing,
+    control: &Control,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<(usize, Duration), (usize, WriteError)> {
+    let chunk = pacing.chunk.max(1);
+    let mut sent = 0;
+    let mut retries = 0;
+    let mut backoff = FIRST_BACKOFF;
+    let mut last_progress = Instant::now();
+    let mut reported = 0;
+    let mut cts_wait = Duration::ZERO;
+    while sent < data.len() {
+        while control.is_paused() && !control.is_cancelled() {
+            std::thread::sleep(PAUSE_POLL);
+            last_progress = Instant::now();
+        }
+        if pacing.cts_gate {
+            let held = Instant::now();
+            loop {
+                match port.read_clear_to_send() {
+                    Ok(true) => break,
+                    Ok(false) if !control.is_cancelled() => std::thread::sleep(PAUSE_POLL),
+                    Ok(false) => break,
+                    Err(e) => return Err((sent, WriteError::Io(format!("can't read CTS: {e}")))),
+                }
+            }
+            cts_wait += held.elapsed();
+            last_progress = Instant::now();
+        }
+        if control.is_cancelled() {
+            return Err((sent, WriteError::Cancelled));
+        }
+        // Writes never cross into the next chunk, so the delay falls between whole chunks.
+        // Likewise they stop after one byte or one line when there's a delay after those.
+        let mut end = data.len().min((sent / chunk + 1) * chunk);
+        if !pacing.char_delay.is_zero() {
+            end = sent + 1;
+        } else if !pacing.line_delay.is_zero()
+            && let Some(lf) = data[sent..end].iter().position(|&byte| byte == b'\n')
+        {
+            end = sent + lf + 1;
+        }
+        if pacing.cts_gate {
+            end = end.min(sent + CTS_CHUNK);
+        }
+        match port.write(&data[sent..end]) {
+            Ok(0) => {}
+            Ok(n) => {
+                sent += n;
+                backoff = FIRST_BACKOFF;
+                last_progress = Instant::now();
+                if sent - reported >= chunk.min(CHUNK_SIZE) && sent < data.len() {
+                    reported = sent;
+                    progress(sent, data.len());
+                }
+                if sent < data.len() {
+                    let mut pause = match data[sent - 1] {
+                        b'\n' => pacing.line_delay.max(pacing.char_delay),
+                        _ => pacing.char_delay,
+                    };
+                    if sent.is_multiple_of(chunk) {
+                        pause += pacing.delay;
+                    }
+                    if !pause.is_zero() {
+                        std::thread::sleep(pause);
+                        last_progress = Instant::now();
+                    }
+                }
+                continue;
+            }
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    ErrorKind::TimedOut | ErrorKind::WouldBlock | ErrorKind::Interrupted
+                ) => {}
+            Err(e) => return Err((sent, WriteError::Io(e.to_string()))),
+        }
+        if last_progress.elapsed() >= STALL_LIMIT {
+            return Err((sent, WriteError::Stalled));
+        }
+        retries += 1;
+        std::thread::sleep(backoff);
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+    Ok((retries, cts_wait))
+}
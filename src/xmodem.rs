@@ -0,0 +1,655 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::zmodem;
+
+const SOH: u8 = 0x01;
+const STX: u8 = 0x02;
+const EOT: u8 = 0x04;
+const ACK: u8 = 0x06;
+const NAK: u8 = 0x15;
+const CAN: u8 = 0x18;
+// Pads the last block out to full size
+const SUB: u8 = 0x1A;
+
+// How long the sender waits for the receiver to ask for the first block
+const START_TIMEOUT: Duration = Duration::from_secs(60);
+// How long either side waits for the other's next packet or answer
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+// The receiver asks for the first block this often until the sender starts
+const POKE_INTERVAL: Duration = Duration::from_secs(3);
+const POKES: u32 = 20;
+const RETRIES: u32 = 10;
+
+// XMODEM Variants
+// Plain XMODEM sends 128-byte blocks with an arithmetic checksum, XMODEM-CRC the same blocks
+// with a CRC-16, and XMODEM-1K 1024-byte blocks with a CRC-16. A sender follows what the
+// receiver asks for, so only the block size matters when sending. YMODEM is XMODEM-1K with
+// a block 0 ahead of each file giving its name and size, so several files go in one batch.
+// ZMODEM, in zmodem.rs, streams without waiting for each block and resumes from where a
+// damaged one left off.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Variant {
+    Xmodem,
+    XmodemCrc,
+    Xmodem1k,
+    Ymodem,
+    Zmodem,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 5] = [
+        Variant::Xmodem,
+        Variant::XmodemCrc,
+        Variant::Xmodem1k,
+        Variant::Ymodem,
+        Variant::Zmodem,
+    ];
+    // Whether files go by name, several at a time, into a directory when receiving
+    pub fn is_batch(self) -> bool {
+        matches!(self, Variant::Ymodem | Variant::Zmodem)
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Variant::Xmodem => "XMODEM",
+                Variant::XmodemCrc => "XMODEM-CRC",
+                Variant::Xmodem1k => "XMODEM-1K",
+                Variant::Ymodem => "YMODEM",
+                Variant::Zmodem => "ZMODEM",
+            }
+        )
+    }
+}
+
+pub enum XmodemStep {
+    Send(Vec<u8>),
+    Wait,
+    Finished,
+    Failed(String),
+}
+
+// A transfer either way, stepped while nothing is being written
+pub enum Transfer {
+    Send(Sender),
+    Receive(Receiver),
+    ZmodemSend(zmodem::Sender),
+    ZmodemReceive(zmodem::Receiver),
+}
+
+impl Transfer {
+    pub fn receive(&mut self, data: &[u8]) {
+        match self {
+            Transfer::Send(sender) => sender.received.extend_from_slice(data),
+            Transfer::Receive(receiver) => receiver.buffer.extend_from_slice(data),
+            Transfer::ZmodemSend(sender) => sender.receive(data),
+            Transfer::ZmodemReceive(receiver) => receiver.receive(data),
+        }
+    }
+    pub fn step(&mut self, now: Instant) -> XmodemStep {
+        match self {
+            Transfer::Send(sender) => sender.step(now),
+            Transfer::Receive(receiver) => receiver.step(now),
+            Transfer::ZmodemSend(sender) => sender.step(now),
+            Transfer::ZmodemReceive(receiver) => receiver.step(now),
+        }
+    }
+    // Retries and the like, for the log
+    pub fn take_notes(&mut self) -> Vec<String> {
+        let notes = match self {
+            Transfer::Send(sender) => &mut sender.notes,
+            Transfer::Receive(receiver) => &mut receiver.notes,
+            Transfer::ZmodemSend(sender) => return sender.take_notes(),
+            Transfer::ZmodemReceive(receiver) => return receiver.take_notes(),
+        };
+        std::mem::take(notes)
+    }
+    // YMODEM and ZMODEM files received whole since the last call, with the names the sender
+    // gave them
+    pub fn take_files(&mut self) -> Vec<(String, Vec<u8>)> {
+        match self {
+            Transfer::Receive(receiver) => std::mem::take(&mut receiver.files),
+            Transfer::ZmodemReceive(receiver) => receiver.take_files(),
+            _ => Vec::new(),
+        }
+    }
+    pub fn variant(&self) -> Variant {
+        match self {
+            Transfer::Send(sender) => sender.variant,
+            Transfer::Receive(receiver) => receiver.variant,
+            Transfer::ZmodemSend(_) | Transfer::ZmodemReceive(_) => Variant::Zmodem,
+        }
+    }
+    // What tells the other side to give up
+    pub fn cancel(&self) -> Vec<u8> {
+        match self.variant() {
+            Variant::Zmodem => zmodem::cancel(),
+            _ => vec![CAN; 2],
+        }
+    }
+    pub fn progress(&self) -> String {
+        match self {
+            Transfer::Send(sender) if !sender.started => {
+                String::from("Waiting for the receiver to start")
+            }
+            Transfer::Send(sender) if sender.ending => String::from("Ending the batch"),
+            Transfer::Send(sender) if sender.variant == Variant::Ymodem => format!(
+                "'{}' (file {} of {}): {} of {} bytes sent",
+                sender.name,
+                sender.file,
+                sender.files,
+                sender.offset.min(sender.data.len()),
+                sender.data.len()
+            ),
+            Transfer::Send(sender) => format!(
+                "{} of {} bytes sent",
+                sender.offset.min(sender.data.len()),
+                sender.data.len()
+            ),
+            Transfer::Receive(receiver) if !receiver.started => {
+                String::from("Waiting for the sender to start")
+            }
+            Transfer::Receive(receiver) if receiver.variant == Variant::Ymodem => {
+                match &receiver.header {
+                    Some((name, Some(size))) => format!(
+                        "'{name}' (file {}): {} of {size} bytes received",
+                        receiver.files_done + 1,
+                        receiver.data.len().min(*size)
+                    ),
+                    Some((name, None)) => format!(
+                        "'{name}' (file {}): {} bytes received",
+                        receiver.files_done + 1,
+                        receiver.data.len()
+                    ),
+                    None => format!("{} files received", receiver.files_done),
+                }
+            }
+            Transfer::Receive(receiver) => format!("{} bytes received", receiver.data.len()),
+            Transfer::ZmodemSend(sender) => sender.progress(),
+            Transfer::ZmodemReceive(receiver) => receiver.progress(),
+        }
+    }
+    pub fn retried(&self) -> u32 {
+        match self {
+            Transfer::Send(sender) => sender.retried,
+            Transfer::Receive(receiver) => receiver.retried,
+            Transfer::ZmodemSend(sender) => sender.retried(),
+            Transfer::ZmodemReceive(receiver) => receiver.retried(),
+        }
+    }
+}
+
+// Where a sender is in the current file
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Stage {
+    // YMODEM's block 0, naming the file
+    Header,
+    Data,
+    Eot,
+}
+
+pub struct Sender {
+    variant: Variant,
+    // The file going out, and for YMODEM the ones after it
+    name: String,
+    data: Vec<u8>,
+    queue: VecDeque<(String, Vec<u8>)>,
+    file: usize,
+    files: usize,
+    // 1024 for XMODEM-1K until a receiver asks for checksums, which only go with 128
+    block_size: usize,
+    crc: bool,
+    started: bool,
+    // Waiting for the receiver to ask with 'C' or NAK: at the start, and in YMODEM before
+    // each file's data and the header after it
+    waiting: bool,
+    stage: Stage,
+    // The empty header that ends a YMODEM batch is out
+    ending: bool,
+    finished: bool,
+    // Bytes acknowledged so far, and the size of the block out after them
+    offset: usize,
+    out: usize,
+    // Counted from 1; the block number sent is this modulo 256
+    block: usize,
+    awaiting: bool,
+    deadline: Option<Instant>,
+    tries: u32,
+    retried: u32,
+    received: Vec<u8>,
+    notes: Vec<String>,
+}
+
+impl Sender {
+    // XMODEM sends the first file and ignores its name
+    pub fn new(files: Vec<(String, Vec<u8>)>, variant: Variant) -> Self {
+        let files_len = files.len();
+        let mut queue = VecDeque::from(files);
+        let (name, data) = queue.pop_front().unwrap_or_default();
+        let ymodem = variant == Variant::Ymodem;
+        Self {
+            variant,
+            name,
+            data,
+            queue,
+            file: 1,
+            files: files_len,
+            block_size: match variant {
+                Variant::Xmodem1k | Variant::Ymodem => 1024,
+                _ => 128,
+            },
+            crc: false,
+            started: false,
+            waiting: true,
+            stage: match ymodem {
+                true => Stage::Header,
+                false => Stage::Data,
+            },
+            ending: ymodem && files_len == 0,
+            finished: false,
+            offset: 0,
+            out: 0,
+            block: usize::from(!ymodem),
+            awaiting: false,
+            deadline: None,
+            tries: 0,
+            retried: 0,
+            received: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+    pub fn blocks(&self) -> usize {
+        self.data.len().div_ceil(self.block_size)
+    }
+    fn step(&mut self, now: Instant) -> XmodemStep {
+        if self.finished {
+            return XmodemStep::Finished;
+        }
+        if self.received.windows(2).any(|pair| pair == [CAN, CAN]) {
+            return XmodemStep::Failed(String::from("cancelled by the receiver"));
+        }
+        if self.waiting {
+            // The receiver asks for CRCs with 'C', or checksums with NAK
+            match self
+                .received
+                .iter()
+                .rev()
+                .find(|&&byte| byte == b'C' || byte == NAK)
+            {
+                Some(_) if self.started => self.waiting = false,
+                Some(&byte) => {
+                    self.crc = byte == b'C';
+                    if !self.crc && self.block_size > 128 {
+                        self.block_size = 128;
+                        self.notes.push(String::from(
+                            "the receiver asked for checksums, so 128-byte blocks are sent",
+                        ));
+                    }
+                    self.started = true;
+                    self.waiting = false;
+                }
+                None => {}
+            }
+            if self.waiting {
+                let (timeout, what) = match self.started {
+                    false => (START_TIMEOUT, "the receiver never started"),
+                    true => (REPLY_TIMEOUT, "the receiver didn't ask for the next file"),
+                };
+                let deadline = *self.deadline.get_or_insert(now + timeout);
+                return match now < deadline {
+                    true => XmodemStep::Wait,
+                    false => XmodemStep::Failed(String::from(what)),
+                };
+            }
+        } else if self.awaiting {
+            let answer = self
+                .received
+                .iter()
+                .rposition(|&byte| byte == ACK || byte == NAK);
+            if let Some(at) = answer
+                && self.received[at] == ACK
+            {
+                // Whatever came after the ACK, like a YMODEM receiver's 'C', is kept
+                self.received.drain(..=at);
+                self.awaiting = false;
+                self.deadline = None;
+                self.tries = 0;
+                match self.stage {
+                    Stage::Header if self.ending => {
+                        self.finished = true;
+                        return XmodemStep::Finished;
+                    }
+                    Stage::Header => {
+                        self.stage = Stage::Data;
+                        self.block = 1;
+                        self.waiting = true;
+                        return self.step(now);
+                    }
+                    Stage::Data => {
+                        self.offset += self.out;
+                        self.block += 1;
+                    }
+                    Stage::Eot if self.variant != Variant::Ymodem => {
+                        self.finished = true;
+                        return XmodemStep::Finished;
+                    }
+                    Stage::Eot => {
+                        match self.queue.pop_front() {
+                            Some((name, data)) => {
+                                (self.name, self.data) = (name, data);
+                                self.file += 1;
+                            }
+                            None => self.ending = true,
+                        }
+                        self.stage = Stage::Header;
+                        self.offset = 0;
+                        self.block = 0;
+                        self.waiting = true;
+                        return self.step(now);
+                    }
+                }
+            } else {
+                let deadline = *self.deadline.get_or_insert(now + REPLY_TIMEOUT);
+                if answer.is_none() && now < deadline {
+                    return XmodemStep::Wait;
+                }
+                let what = match self.stage {
+                    Stage::Header if self.ending => String::from("end of batch"),
+                    Stage::Header => format!("header of '{}'", self.name),
+                    Stage::Data => format!("block {}", self.block),
+                    Stage::Eot => String::from("end of transmission"),
+                };
+                let why = match answer {
+                    Some(_) => "NAK",
+                    None => "no answer",
+                };
+                if self.tries >= RETRIES {
+                    return XmodemStep::Failed(format!(
+                        "{what} failed after {} tries ({why})",
+                        self.tries + 1
+                    ));
+                }
+                // YMODEM receivers answer the first EOT with a NAK to be sure of it
+                let expected = self.variant == Variant::Ymodem
+                    && self.stage == Stage::Eot
+                    && answer.is_some()
+                    && self.tries == 0;
+                if !expected {
+                    self.retried += 1;
+                    self.notes
+                        .push(format!("{why} for {what}, sending it again"));
+                }
+                self.tries += 1;
+            }
+        }
+        self.awaiting = true;
+        self.deadline = None;
+        self.received.clear();
+        match self.stage {
+            Stage::Header => XmodemStep::Send(self.header()),
+            Stage::Data if self.offset < self.data.len() => XmodemStep::Send(self.packet()),
+            _ => {
+                self.stage = Stage::Eot;
+                XmodemStep::Send(vec![EOT])
+            }
+        }
+    }
+    // YMODEM's block 0: the file name and size, or nothing at the end of the batch
+    fn header(&mut self) -> Vec<u8> {
+        let mut block = Vec::new();
+        if !self.ending {
+            block.extend_from_slice(self.name.as_bytes());
+            block.push(0);
+            block.extend_from_slice(self.data.len().to_string().as_bytes());
+        }
+        self.out = match block.len() < 128 {
+            true => 128,
+            false => 1024,
+        };
+        block.resize(self.out, 0);
+        self.frame(0, &block)
+    }
+    // The block after `offset`; the last block of XMODEM-1K drops to 128 bytes when that's
+    // enough for what's left
+    fn packet(&mut self) -> Vec<u8> {
+        let left = self.data.len() - self.offset;
+        self.out = match self.block_size > 128 && left > 128 {
+            true => self.block_size,
+            false => 128,
+        };
+        let mut block = self.data[self.offset..]
+            .iter()
+            .copied()
+            .take(self.out)
+            .collect::<Vec<_>>();
+        block.resize(self.out, SUB);
+        self.frame(self.block as u8, &block)
+    }
+    fn frame(&self, number: u8, block: &[u8]) -> Vec<u8> {
+        let header = match block.len() {
+            128 => SOH,
+            _ => STX,
+        };
+        let mut packet = vec![header, number, !number];
+        packet.extend_from_slice(block);
+        match self.crc {
+            true => packet.extend(crc16(block).to_be_bytes()),
+            false => packet.push(checksum(block)),
+        }
+        packet
+    }
+}
+
+pub struct Receiver {
+    variant: Variant,
+    crc: bool,
+    pub data: Vec<u8>,
+    buffer: Vec<u8>,
+    // Blocks taken so far
+    blocks: usize,
+    // YMODEM: the name and size of the file coming in, once its header is in
+    header: Option<(String, Option<usize>)>,
+    files: Vec<(String, Vec<u8>)>,
+    files_done: usize,
+    started: bool,
+    finished: bool,
+    deadline: Option<Instant>,
+    tries: u32,
+    retried: u32,
+    notes: Vec<String>,
+}
+
+impl Receiver {
+    pub fn new(variant: Variant) -> Self {
+        Self {
+            variant,
+            crc: variant != Variant::Xmodem,
+            data: Vec::new(),
+            buffer: Vec::new(),
+            blocks: 0,
+            header: None,
+            files: Vec::new(),
+            files_done: 0,
+            started: false,
+            finished: false,
+            deadline: None,
+            tries: 0,
+            retried: 0,
+            notes: Vec::new(),
+        }
+    }
+    // What was received, less the padding on the last block; XMODEM has no file length, so
+    // a file that really ends in SUB bytes loses them too
+    pub fn file(&self) -> &[u8] {
+        let end = self
+            .data
+            .iter()
+            .rposition(|&byte| byte != SUB)
+            .map_or(0, |last| last + 1);
+        &self.data[..end]
+    }
+    fn ymodem(&self) -> bool {
+        self.variant == Variant::Ymodem
+    }
+    fn step(&mut self, now: Instant) -> XmodemStep {
+        if self.finished {
+            return XmodemStep::Finished;
+        }
+        while let Some(&first) = self.buffer.first() {
+            match first {
+                EOT if !self.ymodem() => {
+                    self.finished = true;
+                    return XmodemStep::Send(vec![ACK]);
+                }
+                // The file is done; 'C' asks for the next one's header
+                EOT => {
+                    self.buffer.remove(0);
+                    if let Some((name, size)) = self.header.take() {
+                        let data = match size {
+                            Some(size) => self.data[..size.min(self.data.len())].to_vec(),
+                            None => self.file().to_vec(),
+                        };
+                        self.files.push((name, data));
+                        self.files_done += 1;
+                    }
+                    self.data.clear();
+                    self.blocks = 0;
+                    self.deadline = None;
+                    self.tries = 0;
+                    return XmodemStep::Send(vec![ACK, b'C']);
+                }
+                CAN if self.buffer.get(1) == Some(&CAN) => {
+                    return XmodemStep::Failed(String::from("cancelled by the sender"));
+                }
+                SOH | STX => {
+                    let size = match first {
+                        SOH => 128,
+                        _ => 1024,
+                    };
+                    let len = 3 + size + if self.crc { 2 } else { 1 };
+                    if self.buffer.len() < len {
+                        break;
+                    }
+                    let packet = self.buffer.drain(..len).collect::<Vec<_>>();
+                    return self.take(&packet, size);
+                }
+                // Line noise before a packet
+                _ => {
+                    self.buffer.remove(0);
+                }
+            }
+        }
+        let asking = !self.started || (self.ymodem() && self.header.is_none());
+        let timeout = match asking {
+            true => POKE_INTERVAL,
+            false => REPLY_TIMEOUT,
+        };
+        // The first request goes out straight away
+        let deadline = *self
+            .deadline
+            .get_or_insert(match self.started || self.tries > 0 {
+                true => now + timeout,
+                false => now,
+            });
+        if now < deadline {
+            return XmodemStep::Wait;
+        }
+        self.deadline = None;
+        self.buffer.clear();
+        if asking {
+            if self.tries >= POKES {
+                return XmodemStep::Failed(String::from(match self.started {
+                    true => "the sender never sent the next file",
+                    false => "the sender never started",
+                }));
+            }
+            self.tries += 1;
+            return XmodemStep::Send(vec![if self.crc { b'C' } else { NAK }]);
+        }
+        self.nak(format!("no block {} in time", self.blocks + 1))
+    }
+    fn take(&mut self, packet: &[u8], size: usize) -> XmodemStep {
+        let (block, inverse) = (packet[1], packet[2]);
+        let data = &packet[3..3 + size];
+        let good = match self.crc {
+            true => crc16(data).to_be_bytes() == packet[3 + size..],
+            false => checksum(data) == packet[3 + size],
+        };
+        if block != !inverse || !good {
+            return self.nak(format!("block {} arrived damaged", self.blocks + 1));
+        }
+        self.started = true;
+        self.deadline = None;
+        self.tries = 0;
+        // YMODEM's block 0 names the next file, or is empty at the end of the batch
+        if self.ymodem() && self.header.is_none() {
+            if block != 0 {
+                return XmodemStep::Failed(format!("block {block} arrived before a file header"));
+            }
+            // The name always ends in a NUL, so a header without one isn't YMODEM
+            let Some(end) = data.iter().position(|&byte| byte == 0) else {
+                return XmodemStep::Failed(String::from("file header without an end to its name"));
+            };
+            if end == 0 {
+                self.finished = true;
+                return XmodemStep::Send(vec![ACK]);
+            }
+            let name = String::from_utf8_lossy(&data[..end]).into_owned();
+            let size = data[end + 1..]
+                .split(|&byte| byte == 0 || byte == b' ')
+                .next()
+                .and_then(|field| std::str::from_utf8(field).ok()?.parse().ok());
+            self.header = Some((name, size));
+            return XmodemStep::Send(vec![ACK, b'C']);
+        }
+        let expected = (self.blocks + 1) as u8;
+        if block == expected {
+            self.data.extend_from_slice(data);
+            self.blocks += 1;
+        } else if block != expected.wrapping_sub(1) {
+            return XmodemStep::Failed(format!(
+                "block number {block} out of sequence, expected {expected}"
+            ));
+        } else if self.ymodem() && self.blocks == 0 {
+            // The header again: its ACK was lost, and the sender still needs the 'C'
+            return XmodemStep::Send(vec![ACK, b'C']);
+        }
+        // A repeat of the last block means its ACK was lost, so it's acknowledged again
+        XmodemStep::Send(vec![ACK])
+    }
+    fn nak(&mut self, why: String) -> XmodemStep {
+        if self.tries >= RETRIES {
+            return XmodemStep::Failed(format!("{why}, gave up after {} tries", self.tries + 1));
+        }
+        self.tries += 1;
+        self.retried += 1;
+        self.buffer.clear();
+        self.notes.push(format!("{why}, asking for it again"));
+        XmodemStep::Send(vec![NAK])
+    }
+}
+
+// CRC-16/XMODEM, sent high byte first
+pub fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for byte in data {
+        crc ^= u16::from(*byte) << 8;
+        for _ in 0..8 {
+            crc = match crc & 0x8000 {
+                0 => crc << 1,
+                _ => (crc << 1) ^ 0x1021,
+            };
+        }
+    }
+    crc
+}
+
+fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0, |sum, byte| sum.wrapping_add(*byte))
+}
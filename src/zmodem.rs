@@ -0,0 +1,771 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::xmodem::{XmodemStep, crc16};
+
+const ZPAD: u8 = b'*';
+// Also CAN, so five in a row abort a transfer
+const ZDLE: u8 = 0x18;
+const XON: u8 = 0x11;
+const XOFF: u8 = 0x13;
+
+// Frame Types
+const ZRQINIT: u8 = 0;
+const ZRINIT: u8 = 1;
+const ZSINIT: u8 = 2;
+const ZACK: u8 = 3;
+const ZFILE: u8 = 4;
+const ZSKIP: u8 = 5;
+const ZNAK: u8 = 6;
+const ZABORT: u8 = 7;
+const ZFIN: u8 = 8;
+const ZRPOS: u8 = 9;
+const ZDATA: u8 = 10;
+const ZEOF: u8 = 11;
+const ZFERR: u8 = 12;
+const ZCAN: u8 = 16;
+
+// Subpacket Ends: CRC next, then a header (E), more data (G), an ACK and more data (Q), or
+// an ACK and a header (W)
+const ZCRCE: u8 = b'h';
+const ZCRCG: u8 = b'i';
+const ZCRCQ: u8 = b'j';
+const ZCRCW: u8 = b'k';
+const ZRUB0: u8 = b'l';
+const ZRUB1: u8 = b'm';
+
+// ZRINIT flags: full duplex, receives while writing to disk, and takes 32-bit CRCs
+const CANFDX: u8 = 0x01;
+const CANOVIO: u8 = 0x02;
+const CANFC32: u8 = 0x20;
+// ZFILE conversion: binary, sent as is
+const ZCBIN: u8 = 1;
+
+const BLOCK_SIZE: usize = 1024;
+// Longer than any subpacket a sender should send, so a lost end doesn't buffer forever
+const MAX_SUBPACKET: usize = 8192;
+
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+// Each side asks the other to start this often until it does
+const POKE_INTERVAL: Duration = Duration::from_secs(3);
+const POKES: u32 = 20;
+const RETRIES: u32 = 10;
+
+// What sz sends after "rz\r": a hex ZRQINIT. rz starts with a hex ZRINIT instead.
+const ZRQINIT_START: &[u8] = b"**\x18B00";
+const ZRINIT_START: &[u8] = b"**\x18B01";
+
+// What the remote side asked for when it started a transfer on its own
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Offer {
+    // sz is sending, so we can receive
+    Receive,
+    // rz is waiting for a file from us
+    Send,
+}
+
+// Auto-Start Detection: watches received bytes for a transfer the remote side starts
+#[derive(Default)]
+pub struct Detector {
+    tail: Vec<u8>,
+}
+
+impl Detector {
+    pub fn feed(&mut self, data: &[u8]) -> Option<Offer> {
+        self.tail.extend_from_slice(data);
+        let found = [(ZRQINIT_START, Offer::Receive), (ZRINIT_START, Offer::Send)]
+            .into_iter()
+            .find(|(start, _)| {
+                self.tail
+                    .windows(start.len())
+                    .any(|window| window == *start)
+            })
+            .map(|(_, offer)| offer);
+        if found.is_some() {
+            self.tail.clear();
+        } else {
+            // Enough to catch a start split across reads
+            let keep = self.tail.len().saturating_sub(ZRQINIT_START.len() - 1);
+            self.tail.drain(..keep);
+        }
+        found
+    }
+    pub fn reset(&mut self) {
+        self.tail.clear();
+    }
+}
+
+// Eight CANs abort a transfer either way, and as many backspaces clear them off a shell's
+// line if it wasn't one
+pub fn cancel() -> Vec<u8> {
+    [[ZDLE; 8], [0x08; 8]].concat()
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Header {
+    kind: u8,
+    // ZP0 to ZP3: a position, low byte first, or flags, ZF0 last
+    args: [u8; 4],
+    crc32: bool,
+}
+
+impl Header {
+    fn position(&self) -> usize {
+        u32::from_le_bytes(self.args) as usize
+    }
+}
+
+// A transfer is over when five CANs arrive in a row
+fn cancelled(buffer: &[u8]) -> bool {
+    buffer
+        .windows(5)
+        .any(|window| window.iter().all(|&byte| byte == ZDLE))
+}
+
+fn hex_header(kind: u8, args: [u8; 4]) -> Vec<u8> {
+    let mut body = vec![kind];
+    body.extend(args);
+    let crc = crc16(&body);
+    let mut frame = vec![ZPAD, ZPAD, ZDLE, b'B'];
+    for byte in body.iter().chain(&crc.to_be_bytes()) {
+        frame.extend(format!("{byte:02x}").bytes());
+    }
+    frame.extend([b'\r', 0x8A]);
+    if kind != ZACK && kind != ZFIN {
+        frame.push(XON);
+    }
+    frame
+}
+
+fn binary_header(kind: u8, args: [u8; 4], crc32: bool) -> Vec<u8> {
+    let mut body = vec![kind];
+    body.extend(args);
+    let mut frame = vec![ZPAD, ZDLE, if crc32 { b'C' } else { b'A' }];
+    escape(&body, &mut frame);
+    escape(&crc(&body, crc32), &mut frame);
+    frame
+}
+
+fn position(position: usize) -> [u8; 4] {
+    (position as u32).to_le_bytes()
+}
+
+fn subpacket(data: &[u8], end: u8, crc32: bool) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(data.len() + 16);
+    escape(data, &mut packet);
+    packet.extend([ZDLE, end]);
+    let mut covered = data.to_vec();
+    covered.push(end);
+    escape(&crc(&covered, crc32), &mut packet);
+    packet
+}
+
+// CRC-16 goes high byte first, CRC-32 low byte first
+fn crc(data: &[u8], crc32: bool) -> Vec<u8> {
+    match crc32 {
+        true => crc32_of(data).to_le_bytes().to_vec(),
+        false => crc16(data).to_be_bytes().to_vec(),
+    }
+}
+
+fn crc32_of(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFF_u32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = match crc & 1 {
+                1 => (crc >> 1) ^ 0xEDB8_8320,
+                _ => crc >> 1,
+            };
+        }
+    }
+    !crc
+}
+
+// ZDLE itself and the flow control characters can't go over the line as they are
+fn escape(data: &[u8], out: &mut Vec<u8>) {
+    for &byte in data {
+        match byte & 0x7F {
+            ZDLE | 0x10 | XON | XOFF => out.extend([ZDLE, byte ^ 0x40]),
+            _ => out.push(byte),
+        }
+    }
+}
+
+enum Token {
+    Byte(u8),
+    End(u8),
+}
+
+// The next unescaped byte at `at`, or None until more has arrived
+fn token(buffer: &[u8], at: &mut usize) -> Option<Token> {
+    loop {
+        let byte = *buffer.get(*at)?;
+        match byte {
+            // Flow control the line let through
+            XON | XOFF | 0x91 | 0x93 => *at += 1,
+            ZDLE => {
+                let next = *buffer.get(*at + 1)?;
+                *at += 2;
+                return Some(match next {
+                    ZCRCE..=ZCRCW => Token::End(next),
+                    ZRUB0 => Token::Byte(0x7F),
+                    ZRUB1 => Token::Byte(0xFF),
+                    _ => Token::Byte(next ^ 0x40),
+                });
+            }
+            _ => {
+                *at += 1;
+                return Some(Token::Byte(byte));
+            }
+        }
+    }
+}
+
+// The next header in `buffer`, dropping whatever comes before it; None until one is whole
+fn take_header(buffer: &mut Vec<u8>) -> Option<Result<Header, ()>> {
+    loop {
+        let Some(start) = buffer.iter().position(|&byte| byte == ZPAD) else {
+            buffer.clear();
+            return None;
+        };
+        buffer.drain(..start);
+        let mut at = buffer.iter().position(|&byte| byte != ZPAD)?;
+        if buffer[at] != ZDLE {
+            buffer.drain(..at);
+            continue;
+        }
+        let format = *buffer.get(at + 1)?;
+        at += 2;
+        let (bytes, crc32) = match format {
+            b'B' => {
+                let hex = buffer.get(at..at + 14)?;
+                at += 14;
+                let bytes = hex
+                    .chunks(2)
+                    .map(|pair| {
+                        std::str::from_utf8(pair)
+                            .ok()
+                            .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                    })
+                    .collect::<Option<Vec<_>>>();
+                (bytes, false)
+            }
+            b'A' | b'C' => {
+                let crc32 = format == b'C';
+                let len = if crc32 { 9 } else { 7 };
+                let mut bytes = Some(Vec::new());
+                while let Some(got) = bytes.as_mut().filter(|got| got.len() < len) {
+                    match token(buffer, &mut at)? {
+                        Token::Byte(byte) => got.push(byte),
+                        Token::End(_) => bytes = None,
+                    }
+                }
+                (bytes, crc32)
+            }
+            // Not a header after all
+            _ => {
+                buffer.drain(..at);
+                continue;
+            }
+        };
+        buffer.drain(..at);
+        let header = bytes
+            .filter(|bytes| crc(&bytes[..5], crc32) == bytes[5..])
+            .map(|bytes| Header {
+                kind: bytes[0],
+                args: [bytes[1], bytes[2], bytes[3], bytes[4]],
+                crc32,
+            });
+        return Some(header.ok_or(()));
+    }
+}
+
+// The next data subpacket and how it ends; None until it's whole
+fn take_subpacket(buffer: &mut Vec<u8>, crc32: bool) -> Option<Result<(Vec<u8>, u8), ()>> {
+    let mut at = 0;
+    let mut data = Vec::new();
+    let end = loop {
+        match token(buffer, &mut at)? {
+            Token::Byte(byte) => data.push(byte),
+            Token::End(end) => break end,
+        }
+        if data.len() > MAX_SUBPACKET {
+            buffer.drain(..at);
+            return Some(Err(()));
+        }
+    };
+    let mut check = Vec::new();
+    while check.len() < if crc32 { 4 } else { 2 } {
+        match token(buffer, &mut at)? {
+            Token::Byte(byte) => check.push(byte),
+            Token::End(_) => {
+                buffer.drain(..at);
+                return Some(Err(()));
+            }
+        }
+    }
+    buffer.drain(..at);
+    data.push(end);
+    let good = crc(&data, crc32) == check;
+    data.pop();
+    Some(if good { Ok((data, end)) } else { Err(()) })
+}
+
+// The file name and, when given, its size from a ZFILE subpacket
+fn file_info(data: &[u8]) -> (String, Option<usize>) {
+    let end = data
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(data.len());
+    let name = String::from_utf8_lossy(&data[..end]).into_owned();
+    let size = data
+        .get(end + 1..)
+        .and_then(|rest| rest.split(|&byte| byte == 0 || byte == b' ').next())
+        .and_then(|field| std::str::from_utf8(field).ok()?.parse().ok());
+    (name, size)
+}
+
+// Where a sender is in the batch
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Stage {
+    // ZRQINIT out, waiting for ZRINIT
+    Start,
+    // ZFILE out, waiting for ZRPOS
+    File,
+    Data,
+    // ZEOF out, waiting for ZRINIT
+    Eof,
+    // ZFIN out, waiting for ZFIN
+    Fin,
+}
+
+pub struct Sender {
+    name: String,
+    data: Vec<u8>,
+    queue: VecDeque<(String, Vec<u8>)>,
+    file: usize,
+    files: usize,
+    stage: Stage,
+    crc32: bool,
+    // The next byte to send
+    offset: usize,
+    // Sent again when the receiver doesn't answer
+    last: Vec<u8>,
+    finished: bool,
+    deadline: Option<Instant>,
+    tries: u32,
+    retried: u32,
+    received: Vec<u8>,
+    notes: Vec<String>,
+}
+
+impl Sender {
+    pub fn new(files: Vec<(String, Vec<u8>)>) -> Self {
+        let files_len = files.len();
+        let mut queue = VecDeque::from(files);
+        let (name, data) = queue.pop_front().unwrap_or_default();
+        // "rz\r" starts the receiver on a shell that doesn't auto-start
+        let mut last = b"rz\r".to_vec();
+        last.extend(hex_header(ZRQINIT, [0; 4]));
+        Self {
+            name,
+            data,
+            queue,
+            file: 1,
+            files: files_len,
+            stage: Stage::Start,
+            crc32: false,
+            offset: 0,
+            last,
+            finished: false,
+            deadline: None,
+            tries: 0,
+            retried: 0,
+            received: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+    pub fn receive(&mut self, data: &[u8]) {
+        self.received.extend_from_slice(data);
+    }
+    pub fn take_notes(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.notes)
+    }
+    pub fn retried(&self) -> u32 {
+        self.retried
+    }
+    pub fn progress(&self) -> String {
+        match self.stage {
+            Stage::Start => String::from("Waiting for the receiver to start"),
+            Stage::Fin => String::from("Ending the batch"),
+            _ => format!(
+                "'{}' (file {} of {}): {} of {} bytes sent",
+                self.name,
+                self.file,
+                self.files,
+                self.offset,
+                self.data.len()
+            ),
+        }
+    }
+    pub fn step(&mut self, now: Instant) -> XmodemStep {
+        if self.finished {
+            return XmodemStep::Finished;
+        }
+        if cancelled(&self.received) {
+            return XmodemStep::Failed(String::from("cancelled by the receiver"));
+        }
+        while let Some(header) = take_header(&mut self.received) {
+            let Ok(header) = header else {
+                continue;
+            };
+            match (header.kind, self.stage) {
+                (ZRINIT, Stage::Start) => {
+                    self.crc32 = header.args[3] & CANFC32 != 0;
+                    return self.send_file();
+                }
+                (ZRINIT, Stage::Eof) => return self.next_file(),
+                (ZRPOS, Stage::File | Stage::Data | Stage::Eof) => {
+                    if self.stage != Stage::File {
+                        self.retried += 1;
+                        self.notes.push(format!(
+                            "the receiver asked for '{}' again from byte {}",
+                            self.name,
+                            header.position()
+                        ));
+                    }
+                    self.offset = header.position().min(self.data.len());
+                    self.stage = Stage::Data;
+                    self.deadline = None;
+                    self.tries = 0;
+                    // What was in flight is dropped by the receiver
+                    return XmodemStep::Send(binary_header(
+                        ZDATA,
+                        position(self.offset),
+                        self.crc32,
+                    ));
+                }
+                (ZSKIP, Stage::File | Stage::Data | Stage::Eof) => {
+                    self.notes
+                        .push(format!("the receiver skipped '{}'", self.name));
+                    return self.next_file();
+                }
+                (ZFIN, Stage::Fin) => {
+                    self.finished = true;
+                    // "Over and out"
+                    return XmodemStep::Send(b"OO".to_vec());
+                }
+                (ZNAK, Stage::File | Stage::Eof | Stage::Fin) => {
+                    self.retried += 1;
+                    self.notes.push(String::from("NAK, sending it again"));
+                    return XmodemStep::Send(self.last.clone());
+                }
+                (ZCAN | ZABORT | ZFERR, _) => {
+                    return XmodemStep::Failed(String::from("the receiver gave up"));
+                }
+                _ => {}
+            }
+        }
+        if self.stage == Stage::Data {
+            let end = (self.offset + BLOCK_SIZE).min(self.data.len());
+            let block = &self.data[self.offset..end];
+            self.offset = end;
+            if end < self.data.len() {
+                return XmodemStep::Send(subpacket(block, ZCRCG, self.crc32));
+            }
+            let mut packet = subpacket(block, ZCRCE, self.crc32);
+            self.last = binary_header(ZEOF, position(end), self.crc32);
+            packet.extend(&self.last);
+            self.stage = Stage::Eof;
+            self.deadline = None;
+            return XmodemStep::Send(packet);
+        }
+        let (timeout, limit) = match self.stage {
+            Stage::Start => (POKE_INTERVAL, POKES),
+            _ => (REPLY_TIMEOUT, RETRIES),
+        };
+        // The first ZRQINIT goes out straight away
+        let deadline =
+            *self
+                .deadline
+                .get_or_insert(match self.stage == Stage::Start && self.tries == 0 {
+                    true => now,
+                    false => now + timeout,
+                });
+        if now < deadline {
+            return XmodemStep::Wait;
+        }
+        self.deadline = None;
+        if self.tries >= limit {
+            return XmodemStep::Failed(String::from(match self.stage {
+                Stage::Start => "the receiver never started",
+                _ => "the receiver stopped answering",
+            }));
+        }
+        if self.tries > 0 && self.stage != Stage::Start {
+            self.retried += 1;
+            self.notes.push(String::from("no answer, sending it again"));
+        }
+        self.tries += 1;
+        XmodemStep::Send(self.last.clone())
+    }
+    // ZFILE and the file's name and size
+    fn send_file(&mut self) -> XmodemStep {
+        let mut info = self.name.as_bytes().to_vec();
+        info.push(0);
+        info.extend(self.data.len().to_string().bytes());
+        info.push(0);
+        self.last = binary_header(ZFILE, [0, 0, 0, ZCBIN], self.crc32);
+        self.last.extend(subpacket(&info, ZCRCW, self.crc32));
+        self.stage = Stage::File;
+        self.offset = 0;
+        self.deadline = None;
+        self.tries = 1;
+        XmodemStep::Send(self.last.clone())
+    }
+    fn next_file(&mut self) -> XmodemStep {
+        match self.queue.pop_front() {
+            Some((name, data)) => {
+                (self.name, self.data) = (name, data);
+                self.file += 1;
+                self.send_file()
+            }
+            None => {
+                self.last = hex_header(ZFIN, [0; 4]);
+                self.stage = Stage::Fin;
+                self.deadline = None;
+                self.tries = 1;
+                XmodemStep::Send(self.last.clone())
+            }
+        }
+    }
+}
+
+// What a receiver expects next
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Expect {
+    Header,
+    // The subpacket after ZSINIT, with the sender's attention string
+    Sinit,
+    // The subpacket after ZFILE
+    FileInfo,
+    // Subpackets after ZDATA
+    Data,
+}
+
+pub struct Receiver {
+    expect: Expect,
+    // Whether the subpackets coming follow a header with a 32-bit CRC
+    crc32: bool,
+    // The name and size of the file coming in, once its ZFILE is in
+    header: Option<(String, Option<usize>)>,
+    pub data: Vec<u8>,
+    buffer: Vec<u8>,
+    files: Vec<(String, Vec<u8>)>,
+    files_done: usize,
+    finished: bool,
+    deadline: Option<Instant>,
+    tries: u32,
+    retried: u32,
+    notes: Vec<String>,
+}
+
+impl Receiver {
+    pub fn new() -> Self {
+        Self {
+            expect: Expect::Header,
+            crc32: false,
+            header: None,
+            data: Vec::new(),
+            buffer: Vec::new(),
+            files: Vec::new(),
+            files_done: 0,
+            finished: false,
+            deadline: None,
+            tries: 0,
+            retried: 0,
+            notes: Vec::new(),
+        }
+    }
+    pub fn receive(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+    pub fn take_notes(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.notes)
+    }
+    pub fn take_files(&mut self) -> Vec<(String, Vec<u8>)> {
+        std::mem::take(&mut self.files)
+    }
+    pub fn retried(&self) -> u32 {
+        self.retried
+    }
+    pub fn progress(&self) -> String {
+        match &self.header {
+            Some((name, Some(size))) => format!(
+                "'{name}' (file {}): {} of {size} bytes received",
+                self.files_done + 1,
+                self.data.len()
+            ),
+            Some((name, None)) => format!(
+                "'{name}' (file {}): {} bytes received",
+                self.files_done + 1,
+                self.data.len()
+            ),
+            None if self.files_done == 0 => String::from("Waiting for the sender to start"),
+            None => format!("{} files received", self.files_done),
+        }
+    }
+    fn zrinit() -> Vec<u8> {
+        hex_header(ZRINIT, [0, 0, 0, CANFDX | CANOVIO | CANFC32])
+    }
+    fn zrpos(&self) -> Vec<u8> {
+        hex_header(ZRPOS, position(self.data.len()))
+    }
+    pub fn step(&mut self, now: Instant) -> XmodemStep {
+        if self.finished {
+            return XmodemStep::Finished;
+        }
+        if cancelled(&self.buffer) {
+            return XmodemStep::Failed(String::from("cancelled by the sender"));
+        }
+        loop {
+            if self.expect != Expect::Header {
+                let Some(packet) = take_subpacket(&mut self.buffer, self.crc32) else {
+                    break;
+                };
+                self.deadline = None;
+                self.tries = 0;
+                let Ok((data, end)) = packet else {
+                    self.expect = Expect::Header;
+                    return self.damaged();
+                };
+                match self.expect {
+                    Expect::Sinit => {
+                        self.expect = Expect::Header;
+                        return XmodemStep::Send(hex_header(ZACK, [0; 4]));
+                    }
+                    Expect::FileInfo => {
+                        self.header = Some(file_info(&data));
+                        self.data.clear();
+                        self.expect = Expect::Header;
+                        return XmodemStep::Send(self.zrpos());
+                    }
+                    _ => {}
+                }
+                self.data.extend_from_slice(&data);
+                let ack = hex_header(ZACK, position(self.data.len()));
+                match end {
+                    ZCRCW => {
+                        self.expect = Expect::Header;
+                        return XmodemStep::Send(ack);
+                    }
+                    ZCRCQ => return XmodemStep::Send(ack),
+                    ZCRCE => self.expect = Expect::Header,
+                    _ => {}
+                }
+                continue;
+            }
+            let Some(header) = take_header(&mut self.buffer) else {
+                break;
+            };
+            let Ok(header) = header else {
+                if self.header.is_some() {
+                    return self.damaged();
+                }
+                continue;
+            };
+            self.deadline = None;
+            match header.kind {
+                ZRQINIT => return XmodemStep::Send(Self::zrinit()),
+                ZSINIT => {
+                    self.crc32 = header.crc32;
+                    self.expect = Expect::Sinit;
+                }
+                ZFILE => {
+                    self.crc32 = header.crc32;
+                    self.expect = Expect::FileInfo;
+                }
+                ZDATA if self.header.is_some() => {
+                    if header.position() != self.data.len() {
+                        // Data the last ZRPOS asked the sender to drop; whatever follows
+                        // is skipped while looking for the next header
+                        return XmodemStep::Send(self.zrpos());
+                    }
+                    self.crc32 = header.crc32;
+                    self.expect = Expect::Data;
+                }
+                ZEOF if self.header.is_some() && header.position() == self.data.len() => {
+                    let (name, size) = self.header.take().unwrap();
+                    let mut data = std::mem::take(&mut self.data);
+                    if let Some(size) = size {
+                        data.truncate(size);
+                    }
+                    self.files.push((name, data));
+                    self.files_done += 1;
+                    self.tries = 0;
+                    return XmodemStep::Send(Self::zrinit());
+                }
+                ZFIN => {
+                    self.finished = true;
+                    return XmodemStep::Send(hex_header(ZFIN, [0; 4]));
+                }
+                ZCAN | ZABORT => {
+                    return XmodemStep::Failed(String::from("cancelled by the sender"));
+                }
+                _ => {}
+            }
+        }
+        // Between files ZRINIT asks for the next one, the first time straight away
+        let between = self.header.is_none();
+        let timeout = match between {
+            true => POKE_INTERVAL,
+            false => REPLY_TIMEOUT,
+        };
+        let deadline = *self.deadline.get_or_insert(
+            match self.files_done == 0 && between && self.tries == 0 {
+                true => now,
+                false => now + timeout,
+            },
+        );
+        if now < deadline {
+            return XmodemStep::Wait;
+        }
+        self.deadline = None;
+        if between {
+            if self.tries >= POKES {
+                return XmodemStep::Failed(String::from("the sender never started"));
+            }
+            self.tries += 1;
+            return XmodemStep::Send(Self::zrinit());
+        }
+        if self.tries >= RETRIES {
+            return XmodemStep::Failed(format!(
+                "no data after byte {}, gave up after {} tries",
+                self.data.len(),
+                self.tries + 1
+            ));
+        }
+        self.tries += 1;
+        self.retried += 1;
+        self.notes.push(format!(
+            "no data after byte {} in time, asking for it again",
+            self.data.len()
+        ));
+        XmodemStep::Send(self.zrpos())
+    }
+    // Asks for the data again from what was received whole, or for the ZFILE again
+    fn damaged(&mut self) -> XmodemStep {
+        self.retried += 1;
+        if self.header.is_none() {
+            self.notes
+                .push(String::from("damaged file header, asking for it again"));
+            return XmodemStep::Send(hex_header(ZNAK, [0; 4]));
+        }
+        self.notes.push(format!(
+            "damaged data after byte {}, asking for it again",
+            self.data.len()
+        ));
+        XmodemStep::Send(self.zrpos())
+    }
+}